@@ -0,0 +1,11 @@
+use tauri::AppHandle;
+
+use crate::{exit_app, ToyResult};
+
+/// 退出 ToyStudio，`stop_products` 为 `false` 时运行中的产品继续运行
+#[tauri::command]
+pub fn app_exit(app_handle: AppHandle, stop_products: bool) -> ToyResult<()> {
+    println!("app_exit: stop_products={}", stop_products);
+    exit_app(&app_handle, stop_products);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::fs;
+
+use tauri::{AppHandle, State};
+
+use crate::{
+    backup_install_dir, delete_backup, find_backup, get_file_name_without_suffix, latest_backup,
+    list_backups, move_data_paths, prune_backups, restore_backup, AppConfig, JobQueue, Product,
+    Supervisor, ToyResult, ToyStudioError,
+};
+
+/// 获取产品的备份列表，从新到旧排列
+#[tauri::command]
+pub fn product_list_backups(app_handle: AppHandle, pid: String) -> ToyResult<String> {
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let backup_root = app_config.get_product_backup_path(&get_file_name_without_suffix(&pid));
+    serde_json::to_string(&list_backups(&backup_root)).map_err(ToyStudioError::internal)
+}
+
+/// 从最近一次备份恢复产品
+#[tauri::command]
+pub fn product_restore(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+) -> ToyResult<()> {
+    println!("--------------------product_restore--------------------------------");
+    restore_product(&app_handle, &supervisor, &jobs, &pid, None)
+}
+
+/// 从指定备份恢复产品
+#[tauri::command]
+pub fn product_restore_backup(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+    backup_id: String,
+) -> ToyResult<()> {
+    println!("--------------------product_restore_backup--------------------------------");
+    restore_product(&app_handle, &supervisor, &jobs, &pid, Some(&backup_id))
+}
+
+/// 删除产品的备份
+#[tauri::command]
+pub fn product_delete_backup(
+    app_handle: AppHandle,
+    pid: String,
+    backup_id: String,
+) -> ToyResult<()> {
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let backup_root = app_config.get_product_backup_path(&get_file_name_without_suffix(&pid));
+    let backup_dir = find_backup(&backup_root, &backup_id)
+        .ok_or(ToyStudioError::BackupNotFound { product_id: pid })?;
+    delete_backup(&backup_dir)
+}
+
+/// 从备份恢复产品，`backup_id` 为空时使用最近一次备份
+///
+/// 产品已安装时先备份当前安装，再将其中的数据路径移到恢复的安装中。
+fn restore_product(
+    app_handle: &AppHandle,
+    supervisor: &Supervisor,
+    jobs: &JobQueue,
+    pid: &str,
+    backup_id: Option<&str>,
+) -> ToyResult<()> {
+    println!("product_id:{}, backup_id:{:?}", pid, backup_id);
+    if supervisor.status(pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning {
+            product_id: pid.to_string(),
+        });
+    }
+    if jobs.is_busy(pid) {
+        return Err(ToyStudioError::ProductBusy {
+            product_id: pid.to_string(),
+        });
+    }
+
+    let app_config = AppConfig::get_app_config(app_handle)?;
+    let product_name = get_file_name_without_suffix(pid);
+    let backup_root = app_config.get_product_backup_path(&product_name);
+    let backup_dir = match backup_id {
+        Some(backup_id) => find_backup(&backup_root, backup_id),
+        None => latest_backup(&backup_root),
+    }
+    .ok_or_else(|| ToyStudioError::BackupNotFound {
+        product_id: pid.to_string(),
+    })?;
+    let install_dir = app_config.get_product_install_path().join(&product_name);
+
+    if supervisor.is_installed(pid) && install_dir.exists() {
+        let data_paths = Product::parse_product_toml(&app_config.get_meta_product_dir(pid))
+            .map(|product| product.data_paths.unwrap_or_default())
+            .unwrap_or_default();
+        let current = backup_install_dir(&install_dir, &backup_root)?;
+        if let Err(e) = restore_backup(&backup_dir, &install_dir) {
+            let _ = fs::rename(&current, &install_dir);
+            return Err(e);
+        }
+        move_data_paths(&current, &install_dir, &data_paths)?;
+        prune_backups(&backup_root, app_config.max_backups());
+    } else {
+        restore_backup(&backup_dir, &install_dir)?;
+    }
+
+    supervisor.mark_installed(pid);
+
+    Ok(())
+}
@@ -1,23 +1,52 @@
 use std::fs;
 use tauri::AppHandle;
 
-use crate::AppConfig;
+use crate::{AppConfig, LaunchProfile, RestartPolicy, ToyResult, ToyStudioError};
 
 #[tauri::command]
-pub fn get_config(app_handle: AppHandle) -> Result<String, String> {
+pub fn get_config(app_handle: AppHandle) -> ToyResult<String> {
     let app_config = AppConfig::get_app_config(&app_handle)?;
     let json = serde_json::to_string_pretty(&app_config);
-    json.map_err(|e| e.to_string())
+    json.map_err(ToyStudioError::config)
 }
 
 #[tauri::command]
-pub fn set_config(app_handle: AppHandle, config: String) -> Result<String, String> {
+pub fn set_config(app_handle: AppHandle, config: String) -> ToyResult<String> {
     let config_path = AppConfig::get_config_file_path(&app_handle);
 
     // 验证配置格式是否正确
-    serde_json::from_str::<AppConfig>(&config).map_err(|e| e.to_string())?;
+    serde_json::from_str::<AppConfig>(&config).map_err(ToyStudioError::config)?;
 
-    fs::write(&config_path, &config).map_err(|e| e.to_string())?;
+    fs::write(&config_path, &config).map_err(|e| ToyStudioError::io(&config_path, e))?;
 
     Ok(config)
 }
+
+/// 获取产品的启动配置
+#[tauri::command]
+pub fn get_launch_profiles(app_handle: AppHandle, pid: String) -> ToyResult<String> {
+    let profiles = LaunchProfile::load(&app_handle, &pid)?;
+    serde_json::to_string(&profiles).map_err(ToyStudioError::internal)
+}
+
+/// 保存产品的启动配置
+#[tauri::command]
+pub fn set_launch_profiles(app_handle: AppHandle, pid: String, profiles: String) -> ToyResult<()> {
+    let profiles =
+        serde_json::from_str::<Vec<LaunchProfile>>(&profiles).map_err(ToyStudioError::config)?;
+    LaunchProfile::save(&app_handle, &pid, profiles)
+}
+
+/// 获取产品的自动重启策略
+#[tauri::command]
+pub fn get_restart_policy(app_handle: AppHandle, pid: String) -> ToyResult<String> {
+    let policy = RestartPolicy::load(&app_handle, &pid)?;
+    serde_json::to_string(&policy).map_err(ToyStudioError::internal)
+}
+
+/// 保存产品的自动重启策略
+#[tauri::command]
+pub fn set_restart_policy(app_handle: AppHandle, pid: String, policy: String) -> ToyResult<()> {
+    let policy = serde_json::from_str::<RestartPolicy>(&policy).map_err(ToyStudioError::config)?;
+    RestartPolicy::save(&app_handle, &pid, policy)
+}
@@ -5,10 +5,12 @@ use std::{
 use tauri::{AppHandle, Manager};
 use tauri_plugin_dialog::DialogExt;
 
-use crate::{get_file_name_without_suffix, is_wsl, AppConfig};
+use crate::{
+    export_logs, get_file_name_without_suffix, is_wsl, AppConfig, ToyResult, ToyStudioError,
+};
 
 #[tauri::command]
-pub async fn select_directory(app_handle: AppHandle) -> Result<String, String> {
+pub async fn select_directory(app_handle: AppHandle) -> ToyResult<String> {
     let f = app_handle.dialog().file();
     let f = f
         .set_directory(PathBuf::from("."))
@@ -20,16 +22,45 @@ pub async fn select_directory(app_handle: AppHandle) -> Result<String, String> {
                 println!("path: {:?}", path);
                 Ok(path)
             } else {
-                Err("invalid path".to_string())
+                Err(ToyStudioError::invalid_argument("invalid path"))
             }
         }
         None => Ok("".to_string()),
     }
 }
 
+/// 选择保存位置并导出产品日志，用户取消时返回空字符串
+#[tauri::command]
+pub async fn product_export_logs(
+    app_handle: AppHandle,
+    pid: String,
+    session: String,
+) -> ToyResult<String> {
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let product_name = get_file_name_without_suffix(&pid);
+    let log_dir = app_config.get_product_log_path(&product_name);
+
+    let f = app_handle
+        .dialog()
+        .file()
+        .set_title("save logs")
+        .set_file_name(format!("{product_name}-{session}.log"))
+        .add_filter("log", &["log", "txt"]);
+    match f.blocking_save_file() {
+        Some(path) => {
+            let Some(path) = path.as_path() else {
+                return Err(ToyStudioError::invalid_argument("invalid path"));
+            };
+            export_logs(&log_dir, &session, path)?;
+            Ok(path.to_string_lossy().into_owned())
+        }
+        None => Ok("".to_string()),
+    }
+}
+
 /// 打开系统目录（ToyStudio 系统 自己的目录）
 #[tauri::command]
-pub async fn open_system_directory(app_handle: AppHandle, dir: &str) -> Result<(), String> {
+pub async fn open_system_directory(app_handle: AppHandle, dir: &str) -> ToyResult<()> {
     println!("------------------open_system_directory--------------------------------");
     println!("open_system_directory: {:?}", dir);
     let dist = match dir {
@@ -46,13 +77,16 @@ pub async fn open_system_directory(app_handle: AppHandle, dir: &str) -> Result<(
             show_path_in_file_manager(&dist.to_string_lossy().into_owned());
             Ok(())
         }
-        _ => Err("invalid directory".to_string()),
+        _ => Err(ToyStudioError::invalid_argument(format!(
+            "invalid directory: {}",
+            dir
+        ))),
     }
 }
 
 /// 打开托管目录（ToyStudio中用于托管产品的目录）
 #[tauri::command]
-pub async fn open_managed_directory(app_handle: AppHandle, dir: &str) -> Result<(), String> {
+pub async fn open_managed_directory(app_handle: AppHandle, dir: &str) -> ToyResult<()> {
     println!("------------------open_managed_directory--------------------------------");
     println!("dir: {:?}", dir);
     let app_config = AppConfig::get_app_config(&app_handle)?;
@@ -71,10 +105,7 @@ pub async fn open_managed_directory(app_handle: AppHandle, dir: &str) -> Result<
 
 /// 打开产品目录（ToyStudio中托管的产品目录）
 #[tauri::command]
-pub async fn open_product_directory(
-    app_handle: AppHandle,
-    product_id: String,
-) -> Result<(), String> {
+pub async fn open_product_directory(app_handle: AppHandle, product_id: String) -> ToyResult<()> {
     println!("product_id:{}", product_id);
 
     let app_config = AppConfig::get_app_config(&app_handle)?;
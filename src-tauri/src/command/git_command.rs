@@ -1,8 +1,46 @@
 use std::path::Path;
 use std::process::Command;
+use std::{fs, thread, time::Duration};
+
+use crate::{
+    backup_install_dir, prune_backups, run_with_output, GitCommit, GitMirror, LogStream,
+    OutputSink, ToyResult, ToyStudioError,
+};
+
+/// 克隆方式，由 `AppConfig::clone_options` 生成
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    /// 只下载最近的提交数（`--depth`），为空时下载完整历史
+    pub depth: Option<u32>,
+    /// 部分克隆（`--filter=blob:none`）：文件内容在检出时才下载
+    pub partial: bool,
+    /// 同时克隆子模块
+    pub submodules: bool,
+    /// 网络中断等失败后的重试次数
+    pub retries: u32,
+    /// 直连失败时依次尝试的镜像或代理，见 `with_mirrors`
+    pub mirrors: Vec<GitMirror>,
+    /// 本次使用的镜像或代理，为空时直连
+    pub mirror: Option<GitMirror>,
+    /// 目标目录已存在但不是git仓库时会被备份，每个产品保留的备份数
+    pub max_backups: usize,
+}
+
+impl CloneOptions {
+    /// 访问远程仓库的git命令参数，使用镜像时在前面加上镜像的配置
+    fn remote_args(&self, args: &[&str]) -> Vec<String> {
+        let mut remote_args = self
+            .mirror
+            .as_ref()
+            .map(GitMirror::git_config)
+            .unwrap_or_default();
+        remote_args.extend(args.iter().map(|arg| arg.to_string()));
+        remote_args
+    }
+}
 
 /// 执行git命令并返回结果
-fn execute_git_command<P: AsRef<Path>>(dir: P, args: &[&str]) -> Result<String, String> {
+fn execute_git_command<P: AsRef<Path>>(dir: P, args: &[&str]) -> ToyResult<String> {
     println!(
         "execute_git_command:cd {} && git {:?}",
         dir.as_ref().to_string_lossy(),
@@ -12,12 +50,54 @@ fn execute_git_command<P: AsRef<Path>>(dir: P, args: &[&str]) -> Result<String,
         .current_dir(dir)
         .args(args)
         .output()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToyStudioError::GitNotFound,
+            _ => ToyStudioError::spawn("git", e),
+        })?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        Err(ToyStudioError::GitCommandFailed {
+            args: args.join(" "),
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// 执行耗时的git命令（clone、pull 等），输出逐行交给 `output`
+fn execute_git_command_with_output<P: AsRef<Path>>(
+    dir: P,
+    args: &[&str],
+    output: OutputSink,
+) -> ToyResult<()> {
+    println!(
+        "execute_git_command:cd {} && git {:?}",
+        dir.as_ref().to_string_lossy(),
+        args
+    );
+    let mut command = Command::new("git");
+    command
+        .current_dir(dir)
+        .args(args)
+        // 克隆、检出时不下载 Git LFS 文件，由安装流程中单独的步骤下载
+        .env("GIT_LFS_SKIP_SMUDGE", "1")
+        // 输出不是终端时 git-lfs 默认不显示进度
+        .env("GIT_LFS_FORCE_PROGRESS", "1");
+    let (status, stderr) = run_with_output(&mut command, output).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ToyStudioError::GitNotFound,
+        _ => ToyStudioError::spawn("git", e),
+    })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ToyStudioError::GitCommandFailed {
+            args: args.join(" "),
+            exit_code: status.code(),
+            stderr,
+        })
     }
 }
 
@@ -31,8 +111,163 @@ pub fn is_git_repository<P: AsRef<Path>>(path: P) -> bool {
         .unwrap_or(false)
 }
 
+/// 获取git仓库当前的提交
+pub fn git_head_commit<P: AsRef<Path>>(path: P) -> ToyResult<String> {
+    execute_git_command(path, &["rev-parse", "HEAD"])
+}
+
+/// 将git仓库重置到指定提交，丢弃之后的修改
+pub fn git_reset_hard<P: AsRef<Path>>(path: P, commit: &str) -> ToyResult<()> {
+    execute_git_command(path, &["reset", "--hard", commit]).map(|_| ())
+}
+
+/// 拉取远程的分支、标签或提交但不合并，返回其指向的提交
+pub fn git_fetch<P: AsRef<Path>>(
+    path: P,
+    refspec: &str,
+    options: &CloneOptions,
+) -> ToyResult<String> {
+    let path = path.as_ref();
+    let args = options.remote_args(&["fetch", "origin", refspec]);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    execute_git_command(path, &args)?;
+    execute_git_command(path, &["rev-parse", "FETCH_HEAD^{commit}"])
+}
+
+/// 不下载仓库，通过 `git ls-remote` 获取远程分支、标签指向的提交
+///
+/// `revision` 为完整的提交哈希时直接返回；为缩写的提交或远程没有对应的引用时返回 `None`。
+pub fn git_remote_commit(
+    url: &str,
+    branch: &str,
+    revision: Option<&str>,
+    options: &CloneOptions,
+) -> ToyResult<Option<String>> {
+    if let Some(revision) = revision.filter(|revision| is_full_commit(revision)) {
+        return Ok(Some(revision.to_lowercase()));
+    }
+    let name = revision.unwrap_or(branch);
+    let refs = [
+        format!("refs/tags/{}", name),
+        format!("refs/tags/{}^{{}}", name),
+        format!("refs/heads/{}", name),
+    ];
+    let mut args = vec!["ls-remote", url];
+    args.extend(refs.iter().map(String::as_str));
+    let args = options.remote_args(&args);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = execute_git_command(std::env::temp_dir(), &args)?;
+    Ok(parse_ls_remote(&output, name))
+}
+
+/// 完整的提交哈希（SHA-1 或 SHA-256）
+fn is_full_commit(revision: &str) -> bool {
+    matches!(revision.len(), 40 | 64) && revision.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// 从 `git ls-remote` 的输出中取出 `name` 指向的提交：附注标签取其指向的提交，标签优先于分支
+fn parse_ls_remote(output: &str, name: &str) -> Option<String> {
+    let refs: Vec<(&str, &str)> = output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect();
+    [
+        format!("refs/tags/{}^{{}}", name),
+        format!("refs/tags/{}", name),
+        format!("refs/heads/{}", name),
+    ]
+    .iter()
+    .find_map(|target| {
+        refs.iter()
+            .find(|(_, name)| name.trim() == target)
+            .map(|(commit, _)| commit.trim().to_string())
+    })
+}
+
+/// 获取 `from` 之后到 `to` 为止的提交，最新的在前
+pub fn git_log<P: AsRef<Path>>(path: P, from: &str, to: &str) -> ToyResult<Vec<GitCommit>> {
+    let range = format!("{}..{}", from, to);
+    let output = execute_git_command(path, &["log", "--format=%H%x1f%an%x1f%at%x1f%s", &range])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\x1f');
+            let hash = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let timestamp = fields.next()?.parse::<u64>().ok()?;
+            let summary = fields.next().unwrap_or_default().to_string();
+            Some(GitCommit {
+                hash,
+                author,
+                committed_at: timestamp * 1000,
+                summary,
+            })
+        })
+        .collect())
+}
+
+/// 读取指定提交中的文件内容，文件不存在时返回 `None`
+///
+/// 部分克隆的仓库中文件内容可能需要从远程下载，下载时使用 `options` 中的镜像。
+pub fn git_show_file<P: AsRef<Path>>(
+    path: P,
+    commit: &str,
+    file: &str,
+    options: &CloneOptions,
+) -> ToyResult<Option<String>> {
+    let path = path.as_ref();
+    // 部分克隆也包含目录结构，判断文件是否存在不需要访问远程
+    if execute_git_command(path, &["ls-tree", "--name-only", commit, "--", file])?.is_empty() {
+        return Ok(None);
+    }
+    let spec = format!("{}:{}", commit, file);
+    let args = options.remote_args(&["show", &spec]);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    execute_git_command(path, &args).map(Some)
+}
+
+/// 仓库是否使用 Git LFS：任一 `.gitattributes` 中配置了 `filter=lfs`
+pub fn is_lfs_repository(path: &Path) -> bool {
+    execute_git_command(path, &["ls-files", "--", ":(glob)**/.gitattributes"])
+        .map(|files| {
+            files.lines().any(|file| {
+                fs::read_to_string(path.join(file))
+                    .is_ok_and(|attributes| attributes.contains("filter=lfs"))
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// 检查是否安装了 git-lfs
+fn ensure_git_lfs(path: &Path) -> ToyResult<()> {
+    execute_git_command(path, &["lfs", "version"])
+        .map(|_| ())
+        .map_err(|e| match e {
+            ToyStudioError::GitCommandFailed { .. } => ToyStudioError::GitLfsNotFound,
+            e => e,
+        })
+}
+
+/// 下载 Git LFS 文件，进度输出逐行交给 `output`；已下载的文件在重试时不会重新下载
+pub fn git_lfs_pull(path: &Path, options: &CloneOptions, output: OutputSink) -> ToyResult<()> {
+    ensure_git_lfs(path)?;
+    // 之后的检出、拉取同样不自动下载，LFS 文件只由本函数下载
+    execute_git_command(path, &["lfs", "install", "--local", "--skip-smudge"])?;
+    execute_remote_command(path, &["lfs", "pull"], options, output)
+}
+
+/// 是否有尚未下载（仍是指针文件）的 Git LFS 文件
+pub fn has_missing_lfs_files(path: &Path) -> ToyResult<bool> {
+    ensure_git_lfs(path)?;
+    // 每行格式为 `<oid> <*|-> <路径>`，`-` 表示尚未下载
+    let files = execute_git_command(path, &["lfs", "ls-files"])?;
+    Ok(files
+        .lines()
+        .any(|line| line.split_whitespace().nth(1) == Some("-")))
+}
+
 /// 获取git仓库的远程地址
-fn get_remote_url<P: AsRef<Path>>(path: P) -> Result<String, String> {
+fn get_remote_url<P: AsRef<Path>>(path: P) -> ToyResult<String> {
     execute_git_command(path, &["config", "--get", "remote.origin.url"])
 }
 
@@ -44,68 +279,263 @@ fn normalize_url(url: &str) -> String {
         .to_string()
 }
 
-/// 更新已存在的git仓库
-fn update_repository<P: AsRef<Path>>(path: P) -> Result<String, String> {
-    execute_git_command(path, &["pull"])
+/// 执行可能因网络中断失败的git命令，失败后依次等待 2、4、8…秒重试，最多重试 `retries` 次；
+/// 任务被取消后不再重试
+fn with_retries<F>(retries: u32, output: OutputSink, mut run: F) -> ToyResult<()>
+where
+    F: FnMut() -> ToyResult<()>,
+{
+    let mut attempt = 0;
+    loop {
+        match run() {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= retries || output.is_cancelled() => return Err(e),
+            Err(e) => {
+                attempt += 1;
+                let delay = Duration::from_secs(1 << attempt.min(6));
+                println!("git retry {}/{}:{}", attempt, retries, e);
+                output.line(
+                    LogStream::Stderr,
+                    &format!(
+                        "{}; retrying ({}/{}) in {}s",
+                        e,
+                        attempt,
+                        retries,
+                        delay.as_secs()
+                    ),
+                );
+                thread::sleep(delay);
+            }
+        }
+    }
+}
+
+/// 执行访问远程仓库的git命令：使用 `options` 中的镜像，失败后按 `options.retries` 重试
+fn execute_remote_command(
+    path: &Path,
+    args: &[&str],
+    options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<()> {
+    let args = options.remote_args(args);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    with_retries(options.retries, output, || {
+        execute_git_command_with_output(path, &args, output)
+    })
+}
+
+/// 更新已存在的git仓库：固定版本时检出该版本，否则切回分支（之前可能固定过版本）后拉取
+fn update_repository<P: AsRef<Path>>(
+    path: P,
+    branch: &str,
+    revision: Option<&str>,
+    options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<()> {
+    let path = path.as_ref();
+    match revision {
+        Some(revision) => {
+            let target = resolve_revision(path, revision, options, output)?;
+            execute_remote_command(
+                path,
+                &["checkout", "--progress", "--detach", &target],
+                options,
+                output,
+            )
+        }
+        None => {
+            execute_git_command(path, &["checkout", branch])?;
+            execute_remote_command(path, &["pull", "--progress"], options, output)
+        }
+    }
+}
+
+/// 标签或提交在本地的引用，本地没有时先从远程拉取
+fn resolve_revision(
+    path: &Path,
+    revision: &str,
+    options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<String> {
+    let local = format!("{}^{{commit}}", revision);
+    if execute_git_command(path, &["rev-parse", "--verify", "--quiet", &local]).is_ok() {
+        return Ok(local);
+    }
+    // 浅克隆的仓库按相同深度拉取，避免下载该版本的完整历史
+    let depth = options.depth.map(|depth| format!("--depth={}", depth));
+    let mut args = vec!["fetch", "--progress"];
+    args.extend(depth.as_deref());
+    args.extend(["origin", revision]);
+    execute_remote_command(path, &args, options, output)?;
+    Ok("FETCH_HEAD".to_string())
 }
 
 /// 克隆git仓库
-fn clone_repository<P: AsRef<Path>>(url: &str, branch: &str, path: P) -> Result<String, String> {
-    println!("clone_repository:{}", path.as_ref().to_string_lossy());
-    execute_git_command(
-        &Path::new("."),
-        &[
-            "clone",
-            url,
-            "-b",
-            &branch,
-            "--single-branch",
-            &path.as_ref().to_string_lossy(),
-        ],
+///
+/// 不使用 git clone（失败时会删除整个目录），而是先初始化空仓库并添加远程，再按 `options.retries`
+/// 重试 `git fetch` 下载提交历史，重试时 `.git` 中已下载完成的对象不会重新下载，最后检出工作区。
+/// 部分克隆检出时只下载缺少的文件内容。所有重试都失败后删除目录，由调用方（例如换用镜像）重新克隆。
+fn clone_repository(
+    url: &str,
+    branch: &str,
+    revision: Option<&str>,
+    path: &Path,
+    options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<()> {
+    println!("clone_repository:{}", path.to_string_lossy());
+    fs::create_dir_all(path).map_err(|e| ToyStudioError::io(path, e))?;
+    fetch_repository(url, branch, revision, path, options, output).inspect_err(|_| {
+        let _ = fs::remove_dir_all(path);
+    })
+}
+
+/// 在空目录中初始化仓库，只拉取 `branch`，建立跟踪远程的本地分支后检出
+fn fetch_repository(
+    url: &str,
+    branch: &str,
+    revision: Option<&str>,
+    path: &Path,
+    options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<()> {
+    execute_git_command(path, &["init"])?;
+    execute_git_command(path, &["remote", "add", "-t", branch, "origin", url])?;
+    if options.partial {
+        execute_git_command(path, &["config", "remote.origin.promisor", "true"])?;
+        execute_git_command(
+            path,
+            &["config", "remote.origin.partialclonefilter", "blob:none"],
+        )?;
+    }
+
+    let depth = options.depth.map(|depth| format!("--depth={}", depth));
+    let mut args = vec!["fetch", "--progress"];
+    args.extend(depth.as_deref());
+    if options.partial {
+        args.push("--filter=blob:none");
+    }
+    args.push("origin");
+    execute_remote_command(path, &args, options, output)?;
+
+    let upstream = format!("origin/{}", branch);
+    execute_git_command(path, &["branch", "--force", "--track", branch, &upstream])?;
+    let target = match revision {
+        Some(revision) => resolve_revision(path, revision, options, output)?,
+        None => branch.to_string(),
+    };
+    let mut args = vec!["checkout", "--progress", "--force"];
+    if revision.is_some() {
+        args.push("--detach");
+    }
+    args.push(&target);
+    execute_remote_command(path, &args, options, output)
+}
+
+/// 初始化并更新子模块，已下载完成的子模块在重试时不会重新下载
+fn update_submodules(path: &Path, options: &CloneOptions, output: OutputSink) -> ToyResult<()> {
+    execute_remote_command(
+        path,
+        &["submodule", "update", "--init", "--recursive", "--progress"],
+        options,
+        output,
     )
 }
 
 /// 验证远程地址是否匹配
-fn verify_remote_url<P: AsRef<Path>>(path: P, expected_url: &str) -> Result<(), String> {
-    let current_url = get_remote_url(path)?;
+fn verify_remote_url<P: AsRef<Path>>(path: P, expected_url: &str) -> ToyResult<()> {
+    let current_url = get_remote_url(&path)?;
     let current_url = normalize_url(&current_url);
     let expected_url = normalize_url(expected_url);
 
     if current_url != expected_url {
-        return Err(format!(
-            "目标目录已存在git仓库，但远程地址不匹配。\n现有远程地址: {}\n请求克隆地址: {}",
-            current_url, expected_url
-        ));
+        return Err(ToyStudioError::RemoteUrlMismatch {
+            path: path.as_ref().to_string_lossy().to_string(),
+            current_url,
+            expected_url,
+        });
     }
     Ok(())
 }
 
+/// 克隆git仓库，目录已是该仓库时拉取更新；git 的进度输出逐行交给 `output`
+///
+/// `revision` 为固定的标签或提交，克隆或拉取后检出该版本，为空时跟随 `branch` 的最新提交。
+/// 目录存在但不是git仓库时，先将其备份到 `bak/<时间戳>`。网络操作按 `options.retries` 重试。
 pub fn git_clone<P: AsRef<Path>>(
     url: &str,
     branch: &str,
+    revision: Option<&str>,
     path: P,
     bak: P,
-) -> Result<String, String> {
+    options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<()> {
     let path = path.as_ref();
     let bak = bak.as_ref();
-    if path.exists() {
+    if path.exists() && is_git_repository(path) {
         println!("path exists:{}", path.display());
-        if !is_git_repository(&path) {
-            let _ = move_to_bak(&path, &bak);
-            clone_repository(url, &branch, path)
-        } else {
-            verify_remote_url(&path, url)?;
-            update_repository(&path)
-        }
+        verify_remote_url(path, url)?;
+        update_repository(path, branch, revision, options, output)?;
     } else {
-        clone_repository(url, &branch, &path)
+        if path.exists() {
+            println!("path exists:{}", path.display());
+            backup_install_dir(path, bak)?;
+            prune_backups(bak, options.max_backups.max(1));
+        }
+        clone_repository(url, branch, revision, path, options, output)?;
     }
+    if options.submodules {
+        update_submodules(path, options, output)?;
+    }
+    Ok(())
 }
-fn move_to_bak<P: AsRef<Path>>(from: P, to: P) -> Result<(), String> {
-    println!(
-        "move_to_bak:{} to {}",
-        from.as_ref().to_string_lossy(),
-        to.as_ref().to_string_lossy()
-    );
-    std::fs::rename(from, to).map_err(|e| e.to_string())
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LS_REMOTE: &str = "\
+1111111111111111111111111111111111111111\trefs/heads/v1.0
+2222222222222222222222222222222222222222\trefs/tags/v1.0
+3333333333333333333333333333333333333333\trefs/tags/v1.0^{}
+4444444444444444444444444444444444444444\trefs/tags/light
+5555555555555555555555555555555555555555\trefs/heads/main
+";
+
+    #[test]
+    fn annotated_tag_resolves_to_tagged_commit() {
+        assert_eq!(
+            parse_ls_remote(LS_REMOTE, "v1.0").as_deref(),
+            Some("3333333333333333333333333333333333333333")
+        );
+    }
+
+    #[test]
+    fn lightweight_tag_and_branch_resolve_to_their_commit() {
+        assert_eq!(
+            parse_ls_remote(LS_REMOTE, "light").as_deref(),
+            Some("4444444444444444444444444444444444444444")
+        );
+        assert_eq!(
+            parse_ls_remote(LS_REMOTE, "main").as_deref(),
+            Some("5555555555555555555555555555555555555555")
+        );
+    }
+
+    #[test]
+    fn unknown_ref_is_not_resolved() {
+        assert_eq!(parse_ls_remote(LS_REMOTE, "v2.0"), None);
+        assert_eq!(parse_ls_remote(LS_REMOTE, "ma"), None);
+        assert_eq!(parse_ls_remote("", "main"), None);
+    }
+
+    #[test]
+    fn only_full_hashes_are_commits() {
+        assert!(is_full_commit("a1b2c3d4e5f60718293a4b5c6d7e8f9012345678"));
+        assert!(is_full_commit(&"ab".repeat(32)));
+        assert!(!is_full_commit("a1b2c3d"));
+        assert!(!is_full_commit("v1.0.0"));
+        assert!(!is_full_commit(&"g".repeat(40)));
+    }
 }
@@ -0,0 +1,22 @@
+use tauri::State;
+
+use crate::{JobQueue, ToyResult, ToyStudioError};
+
+/// 获取后台任务列表
+#[tauri::command]
+pub fn job_list(jobs: State<'_, JobQueue>) -> ToyResult<String> {
+    serde_json::to_string(&jobs.list()).map_err(ToyStudioError::internal)
+}
+
+/// 取消后台任务
+#[tauri::command]
+pub fn job_cancel(jobs: State<'_, JobQueue>, id: u64) -> ToyResult<()> {
+    jobs.cancel(id)
+}
+
+/// 清除已结束的后台任务
+#[tauri::command]
+pub fn job_clear_finished(jobs: State<'_, JobQueue>) -> ToyResult<()> {
+    jobs.clear_finished();
+    Ok(())
+}
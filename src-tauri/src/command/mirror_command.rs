@@ -0,0 +1,23 @@
+use tauri::AppHandle;
+
+use crate::{probe_mirrors, GitMirror, MirrorStats, ToyResult, ToyStudioError};
+
+/// 获取git镜像的使用统计：镜像地址 -> 统计
+#[tauri::command]
+pub fn git_mirror_stats(app_handle: AppHandle) -> ToyResult<String> {
+    let stats = MirrorStats::load_all(&app_handle)?;
+    serde_json::to_string(&stats).map_err(ToyStudioError::internal)
+}
+
+/// 测试git镜像的延迟并记录到镜像统计中，`mirrors` 为设置页面中编辑的镜像列表（可能尚未保存）
+///
+/// 延迟仅用于展示，下载时仍按配置中的顺序尝试镜像。
+#[tauri::command]
+pub async fn git_probe_mirrors(app_handle: AppHandle, mirrors: String) -> ToyResult<String> {
+    let mirrors =
+        serde_json::from_str::<Vec<GitMirror>>(&mirrors).map_err(ToyStudioError::config)?;
+    let probes = tauri::async_runtime::spawn_blocking(move || probe_mirrors(&app_handle, &mirrors))
+        .await
+        .map_err(ToyStudioError::internal)?;
+    serde_json::to_string(&probes).map_err(ToyStudioError::internal)
+}
@@ -1,10 +1,22 @@
+pub mod app_command;
+pub mod backup_command;
 pub mod config_command;
 pub mod dialog_command;
 pub mod git_command;
+pub mod job_command;
+pub mod mirror_command;
 pub mod product_command;
+pub mod update_command;
 pub mod uv_command;
+pub mod version_command;
+pub use app_command::*;
+pub use backup_command::*;
 pub use config_command::*;
 pub use dialog_command::*;
 pub use git_command::*;
+pub use job_command::*;
+pub use mirror_command::*;
 pub use product_command::*;
+pub use update_command::*;
 pub use uv_command::*;
+pub use version_command::*;
@@ -1,60 +1,98 @@
 use std::{
     collections::HashMap,
     fs::{self},
-    path::Path,
-    process::Child,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::Mutex,
 };
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_opener::OpenerExt;
 
 use crate::{
-    get_file_name_without_suffix, git_clone, is_git_repository, split_args, uv_sync, uv_venv,
-    Product,
+    active_version, allocate_port, backup_install_dir, capture_output, clean_staging_dir,
+    find_version, get_file_name_without_suffix, git_clone, git_head_commit, git_lfs_pull,
+    git_remote_commit, git_reset_hard, has_missing_lfs_files, install_revision, is_git_repository,
+    is_lfs_repository, list_log_sessions, move_data_paths, new_log_session, prune_backups,
+    read_logs, resolve_ui_url, save_port, split_args, ui_url_watcher, uv_sync, uv_venv, version_id,
+    wait_with_output, with_mirrors, write_version_file, CloneOptions, Download, HealthCheck,
+    InstallPipeline, InstallStage, JobKind, JobQueue, LaunchProfile, LfsMode, LogStream,
+    MetricsSampler, OutputSink, Product, ShutdownCommand, ShutdownOutcome, StagedInstall,
+    Supervisor, ToyResult, ToyStudioError, UninstallMode, UpdatePreference,
 };
 
 use crate::AppConfig;
 
-lazy_static! {
-    pub static ref APP_INSTALLED: Mutex<HashMap<String, Option<Arc<Mutex<Child>>>>> =
-        Mutex::new(HashMap::new());
-    pub static ref GIT_PROXY: Mutex<String> = Mutex::new("https://ghfast.top".to_string());
-}
+/// 产品元数据仓库的备份目录名
+const META_PRODUCTS_BACKUP: &str = "_products";
 
 /// 获取所有产品列表, 包括已安装和未安装的产品
 #[tauri::command]
-pub fn get_meta_product_list(app_handle: AppHandle) -> Result<String, String> {
+pub fn get_meta_product_list(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+) -> ToyResult<String> {
     println!("--------------------------------get_meta_product_list--------------------------------");
     let app_config = AppConfig::get_app_config(&app_handle)?;
-    let products = app_config.get_meta_product_list()?;
-    serde_json::to_string(&products).map_err(|e| e.to_string())
+    let products = app_config.get_meta_product_list(&supervisor)?;
+    serde_json::to_string(&products).map_err(ToyStudioError::internal)
 }
 
 /// 获取已安装的产品列表
 #[tauri::command]
-pub fn get_installed_product_list(app_handle: AppHandle) -> Result<String, String> {
+pub fn get_installed_product_list(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+) -> ToyResult<String> {
     println!("--------------------------------get_installed_product_list--------------------------------");
     let app_config = AppConfig::get_app_config(&app_handle)?;
-    let all_products = app_config.get_meta_product_list()?;
-    let installed_products: Vec<&Product> = all_products
-        .iter()
-        .filter(|product| {
-            if let Ok(map) = APP_INSTALLED.lock() {
-                map.contains_key(&product.id)
-            } else {
-                false
-            }
+    let all_products = app_config.get_meta_product_list(&supervisor)?;
+    let mut profiles = LaunchProfile::load_all(&app_handle).unwrap_or_else(|e| {
+        println!("load launch profiles error:{}", e);
+        HashMap::new()
+    });
+    let installed_products: Vec<Product> = all_products
+        .into_iter()
+        .filter(|product| supervisor.is_installed(&product.id))
+        .map(|mut product| {
+            product.launch_profiles = profiles
+                .remove(&product.id)
+                .map(|list| list.into_iter().map(|profile| profile.name).collect());
+            product
         })
         .collect();
-    serde_json::to_string(&installed_products).map_err(|e| e.to_string())
+    serde_json::to_string(&installed_products).map_err(ToyStudioError::internal)
 }
 
 /// 安装产品
+///
+/// 加入后台任务队列，命令立即返回任务；各步骤的进度和输出通过 `product://install-progress`、
+/// `product://log-line` 事件发送，任务状态变化通过 `job://updated` 事件发送。
 #[tauri::command]
-pub fn product_install(app_handle: AppHandle, pid: String) -> Result<(), String> {
+pub fn product_install(jobs: State<'_, JobQueue>, pid: String) -> ToyResult<String> {
     println!("product_id:{}", pid);
+    let job = jobs.enqueue(JobKind::Install, &pid)?;
+    serde_json::to_string(&job).map_err(ToyStudioError::internal)
+}
 
-    let app_config = AppConfig::get_app_config(&app_handle)?;
+/// 执行任务队列中的安装、重新安装、升级任务
+pub(crate) fn run_product_job(
+    app_handle: &AppHandle,
+    pipeline: &InstallPipeline,
+    kind: JobKind,
+    pid: &str,
+) -> ToyResult<()> {
+    match kind {
+        JobKind::Install => install_product(app_handle, pipeline, pid),
+        JobKind::Reinstall => reinstall_product(app_handle, pipeline, pid),
+        JobKind::Upgrade => upgrade_product(app_handle, pipeline, pid),
+        JobKind::InstallVersion => install_version(app_handle, pipeline, pid),
+        JobKind::PullLfs => download_lfs_files(app_handle, pipeline, pid),
+    }
+}
+
+/// 在临时目录中依次执行 git clone -> uv venv -> uv sync，全部成功后移动到安装目录
+fn install_product(app_handle: &AppHandle, pipeline: &InstallPipeline, pid: &str) -> ToyResult<()> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
 
     let product_dir = app_config.get_meta_products_dir();
     let product_file = product_dir.join(pid);
@@ -62,28 +100,51 @@ pub fn product_install(app_handle: AppHandle, pid: String) -> Result<(), String>
     let product_name = get_file_name_without_suffix(&product.id);
 
     let install_dir = app_config.get_product_install_path().join(&product_name);
-    fs::create_dir_all(&install_dir).map_err(|e| e.to_string())?;
     println!("install_dir:{:?}", &install_dir);
 
+    let revision = install_revision(app_handle, &product);
+    let revision = revision.as_deref();
+    let clone_options = app_config.clone_options(product.download.submodules());
     let git_url = product.download.git_url;
     let branch = product.download.branch;
-    let bak_dir = app_config.get_product_bak_path();
+    let bak_dir = app_config.get_product_backup_path(&product_name);
 
     if !app_config.dev_mode() {
+        let staged = StagedInstall::new(
+            &app_config.get_product_temp_path(),
+            &install_dir,
+            product.data_paths.clone().unwrap_or_default(),
+        )?;
+        let staging_dir = staged.dir().to_path_buf();
+
         // 1. git clone
-        if let Err(e) = git_clone(&git_url, &branch, &install_dir, &bak_dir) {
-            println!("git_clone error:{}", e);
-            let git_proxy = GIT_PROXY.lock().map_err(|e| e.to_string())?;
-            println!("try to use git proxy:{}", git_proxy);
-            let git_url_proxy = format!("{git_proxy}/{}", git_url);
-            git_clone(&git_url_proxy, &branch, &install_dir, &bak_dir)?;
-        }
+        pipeline.run_remote_stage(InstallStage::Clone, &clone_options, |options, output| {
+            git_clone(
+                &git_url,
+                &branch,
+                revision,
+                &staging_dir,
+                &bak_dir,
+                options,
+                output,
+            )
+        })?;
+
+        // 2. git lfs pull
+        pipeline.run_remote_stage(InstallStage::Lfs, &clone_options, |options, output| {
+            pull_lfs_files(product.download.lfs, &staging_dir, options, output)
+        })?;
 
-        // 2. create venv
-        uv_venv(&install_dir, &product.download.python_version)?;
+        // 3. create venv
+        pipeline.run_stage(InstallStage::CreateVenv, |output| {
+            uv_venv(&staging_dir, &product.download.python_version, output)
+        })?;
 
-        // 3. sync
-        uv_sync(&install_dir)?;
+        // 4. sync
+        pipeline.run_stage(InstallStage::Sync, |output| uv_sync(&staging_dir, output))?;
+
+        // 5. 移动到安装目录
+        pipeline.commit(staged)?;
     } else {
         // git 目录存在
 
@@ -99,46 +160,133 @@ pub fn product_install(app_handle: AppHandle, pid: String) -> Result<(), String>
             false
         };
 
-        let install_dir = install_dir.to_string_lossy().to_string();
+        // 已有仓库时直接在安装目录中执行，否则先安装到临时目录
+        let staged = if skip_clone {
+            None
+        } else {
+            Some(StagedInstall::new(
+                &app_config.get_product_temp_path(),
+                &install_dir,
+                product.data_paths.clone().unwrap_or_default(),
+            )?)
+        };
+        let script_dir = match &staged {
+            Some(staged) => staged.dir().to_path_buf(),
+            None => install_dir.clone(),
+        };
+        let install_dir = script_dir.to_string_lossy().to_string();
         // 开发模式下使用cmd方式完成，合并为一个脚本
-        let git_clone_cmd = format!("git clone -b {branch} {git_url} {install_dir}");
-        let git_proxy = GIT_PROXY.lock().map_err(|e| e.to_string())?;
-        let git_url_proxy = format!("{git_proxy}/{}", git_url);
-        let git_clone_cmd_proxy = format!("git clone -b {branch} {git_url_proxy} {install_dir}");
+        let mut clone_flags = String::new();
+        if let Some(depth) = clone_options.depth {
+            clone_flags.push_str(&format!(" --depth={depth}"));
+        }
+        if clone_options.partial {
+            clone_flags.push_str(" --filter=blob:none");
+        }
+        if clone_options.submodules {
+            clone_flags.push_str(" --recurse-submodules");
+        }
+        let git_clone_cmd = format!("git clone{clone_flags} -b {branch} {git_url} {install_dir}");
+        // 直连失败时依次通过启用的镜像克隆
+        let git_clone_cmd_mirrors: String = clone_options
+            .mirrors
+            .iter()
+            .filter(|mirror| mirror.is_usable())
+            .map(|mirror| {
+                let mirror_config = mirror.git_config().join(" ");
+                format!(
+                    " || git {mirror_config} clone{clone_flags} -b {branch} {git_url} {install_dir}"
+                )
+            })
+            .collect();
+        // 固定了版本时，克隆后检出该版本
+        let checkout_cmd = match revision {
+            Some(revision) if clone_options.submodules => format!(
+                " && git checkout --detach {revision} && git submodule update --init --recursive"
+            ),
+            Some(revision) => format!(" && git checkout --detach {revision}"),
+            None => String::new(),
+        };
+        let lfs_cmd = match product.download.lfs {
+            Some(LfsMode::Enabled) => " && git lfs pull",
+            _ => "",
+        };
         let python_version = product.download.python_version;
         let cmd_script = if skip_clone {
-            format!("cd {install_dir} && uv venv --python={python_version} && uv sync && pause")
+            format!(
+                "cd {install_dir}{lfs_cmd} && uv venv --relocatable --python={python_version} && uv sync"
+            )
         } else {
             format!(
-                "({git_clone_cmd} || {git_clone_cmd_proxy}) && cd {install_dir} && uv venv --python={python_version} && uv sync && pause"
+                "({git_clone_cmd}{git_clone_cmd_mirrors}) && cd {install_dir}{checkout_cmd}{lfs_cmd} && uv venv --relocatable --python={python_version} && uv sync"
             )
         };
-        let _ = crate::run_command(
+        let child = crate::run_command(
             &product_dir,
             "",
             &vec![cmd_script],
+            &HashMap::new(),
             &product_name,
             &product.id,
-        );
+        )?;
+        // 安装脚本的输出写入安装日志，等待脚本结束
+        let mut child = child.lock().map_err(ToyStudioError::internal)?;
+        let (status, stderr) = pipeline
+            .with_output(|output| wait_with_output(&mut child, output))
+            .map_err(|e| ToyStudioError::spawn("install script", e))?;
+        if pipeline.is_cancelled() {
+            return Err(ToyStudioError::Cancelled);
+        }
+        if !status.success() {
+            return Err(ToyStudioError::internal(format!(
+                "install script exited with {}: {}",
+                status, stderr
+            )));
+        }
+        if let Some(staged) = staged {
+            pipeline.commit(staged)?;
+        }
     }
 
     println!(
-        "--------------------------------APP_INSTALLED insert:{}-----------------------",
+        "--------------------------------installed:{}-----------------------",
         product.id
     );
-    if let Ok(mut map) = APP_INSTALLED.lock() {
-        map.insert(product.id, None);
-    }
+    app_handle.state::<Supervisor>().mark_installed(&product.id);
+    UpdatePreference::record_installed(app_handle, &product.id, &product.version);
 
     Ok(())
 }
 
-/// 重新安装产品
+/// 重新安装产品，加入后台任务队列
 #[tauri::command]
-pub fn product_reinstall(app_handle: AppHandle, pid: String) -> Result<(), String> {
+pub fn product_reinstall(
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+) -> ToyResult<String> {
     println!("product_id:{}", pid);
+    if supervisor.status(&pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning { product_id: pid });
+    }
+    let job = jobs.enqueue(JobKind::Reinstall, &pid)?;
+    serde_json::to_string(&job).map_err(ToyStudioError::internal)
+}
 
-    let app_config = AppConfig::get_app_config(&app_handle)?;
+/// 删除安装目录后重新安装
+fn reinstall_product(
+    app_handle: &AppHandle,
+    pipeline: &InstallPipeline,
+    pid: &str,
+) -> ToyResult<()> {
+    let supervisor = app_handle.state::<Supervisor>();
+    if supervisor.status(pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning {
+            product_id: pid.to_string(),
+        });
+    }
+
+    let app_config = AppConfig::get_app_config(app_handle)?;
 
     let product_dir = app_config.get_meta_products_dir();
     let product_file = product_dir.join(pid);
@@ -148,93 +296,398 @@ pub fn product_reinstall(app_handle: AppHandle, pid: String) -> Result<(), Strin
     println!("product:{:?}", product);
 
     let install_dir = app_config.get_product_install_path().join(&product_name);
-
-    fs::remove_dir_all(&install_dir).map_err(|e| e.to_string())?;
-    fs::create_dir_all(&install_dir).map_err(|e| e.to_string())?;
     println!("install_dir:{:?}", &install_dir);
 
-    let git_url = product.download.git_url;
-    let branch = product.download.branch;
-    let bak_dir = app_config.get_product_bak_path();
-
-    git_clone(&git_url, &branch, &install_dir, &bak_dir)?;
-    uv_venv(&install_dir, &product.download.python_version)?;
-    uv_sync(&install_dir)?;
+    let revision = install_revision(app_handle, &product);
+    let clone_options = app_config.clone_options(product.download.submodules());
+    let bak_dir = app_config.get_product_backup_path(&product_name);
+
+    // 在临时目录中重新安装，成功后才替换原安装目录
+    let staged = StagedInstall::new(
+        &app_config.get_product_temp_path(),
+        &install_dir,
+        product.data_paths.clone().unwrap_or_default(),
+    )?;
+    run_install_stages(
+        pipeline,
+        &product.download,
+        revision.as_deref(),
+        &clone_options,
+        staged.dir(),
+        &bak_dir,
+    )?;
+    pipeline.commit(staged)?;
 
     println!(
-        "--------------------------------APP_INSTALLED insert:{}-----------------------",
+        "--------------------------------installed:{}-----------------------",
         product.id
     );
-    if let Ok(mut map) = APP_INSTALLED.lock() {
-        map.insert(product.id, None);
+    supervisor.mark_installed(&product.id);
+    UpdatePreference::record_installed(app_handle, &product.id, &product.version);
+
+    Ok(())
+}
+
+/// 在临时目录中安装产品配置中的最新版本，完成后移到 `.local/versions/<产品>/<版本ID>`，
+/// 当前使用的版本保持不变
+fn install_version(app_handle: &AppHandle, pipeline: &InstallPipeline, pid: &str) -> ToyResult<()> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+
+    let product = Product::parse_product_toml(&app_config.get_meta_product_dir(pid))?;
+    let product_name = get_file_name_without_suffix(&product.id);
+    let install_dir = app_config.get_product_install_path().join(&product_name);
+    let versions_dir = app_config.get_product_versions_path(&product_name);
+    let bak_dir = app_config.get_product_backup_path(&product_name);
+
+    let installed_version = UpdatePreference::load(app_handle, &product.id)?.installed_version;
+    let active = active_version(&install_dir, installed_version.as_deref());
+    let check_installed = |id: &str| {
+        if active.as_ref().is_some_and(|active| active.id == id) || versions_dir.join(id).exists() {
+            return Err(ToyStudioError::invalid_argument(format!(
+                "version {} is already installed",
+                id
+            )));
+        }
+        Ok(())
+    };
+
+    // 先获取要安装的提交，已安装时不再下载
+    let revision = product.download.revision();
+    let clone_options = app_config.clone_options(product.download.submodules());
+    let mut remote_commit = None;
+    pipeline.with_output(|output| {
+        with_mirrors(app_handle, &clone_options, output, |options| {
+            remote_commit = git_remote_commit(
+                &product.download.git_url,
+                &product.download.branch,
+                revision,
+                options,
+            )?;
+            Ok(())
+        })
+    })?;
+    if let Some(commit) = &remote_commit {
+        check_installed(&version_id(&product.version, commit))?;
     }
 
+    let mut staged = StagedInstall::new(
+        &app_config.get_product_temp_path(),
+        &install_dir,
+        Vec::new(),
+    )?;
+    // 未使用的版本启动时不会下载 LFS 文件，`lfs = "lazy"` 时同样在安装时下载
+    let mut download = product.download.clone();
+    if download.lfs == Some(LfsMode::Lazy) {
+        download.lfs = Some(LfsMode::Enabled);
+    }
+    run_install_stages(
+        pipeline,
+        &download,
+        revision,
+        &clone_options,
+        staged.dir(),
+        &bak_dir,
+    )?;
+
+    // 缩写的提交无法预先获取，安装完成后再检查
+    let id = version_id(&product.version, &git_head_commit(staged.dir())?);
+    check_installed(&id)?;
+    write_version_file(staged.dir(), &product.version)?;
+    staged.set_install_dir(&versions_dir.join(&id));
+    pipeline.commit(staged)?;
+
+    println!(
+        "--------------------------------installed version:{} {}-----------------------",
+        product.id, id
+    );
     Ok(())
 }
 
 /// 卸载产品
+///
+/// 安装目录整体移入 `.local/bak/<产品>/<时间戳>`，可通过 `product_restore_backup` 恢复；
+/// `mode` 为 `keep_data`（默认）时，产品配置中声明的数据路径移回安装目录保留。
+/// 卸载只移动目录、很快完成，因此不加入任务队列而是直接返回结果，执行期间该产品不能加入新任务。
 #[tauri::command]
-pub fn product_uninstall(app_handle: AppHandle, pid: String) -> Result<(), String> {
+pub fn product_uninstall(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+    mode: Option<UninstallMode>,
+) -> ToyResult<()> {
     println!("--------------------product_uninstall--------------------------------");
     println!("product_id:{}", pid);
 
-    let app_config = AppConfig::get_app_config(&app_handle)?;
+    if supervisor.status(&pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning { product_id: pid });
+    }
+    jobs.run_exclusive(&pid, || {
+        uninstall_product(&app_handle, &pid, mode.unwrap_or_default())
+    })?;
+    supervisor.mark_uninstalled(&pid);
 
-    let product_name = get_file_name_without_suffix(&pid);
+    Ok(())
+}
+
+/// 将安装目录移入备份目录，`KeepData` 时移回数据路径
+fn uninstall_product(app_handle: &AppHandle, pid: &str, mode: UninstallMode) -> ToyResult<()> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+
+    let product_name = get_file_name_without_suffix(pid);
 
     println!("product_name:{:?}", product_name);
 
     let install_dir = app_config.get_product_install_path().join(&product_name);
     println!("install_dir:{:?}", install_dir);
 
-    fs::remove_dir_all(&install_dir).map_err(|e| e.to_string())?;
+    let backup_root = app_config.get_product_backup_path(&product_name);
+    let backup_dir = backup_install_dir(&install_dir, &backup_root)?;
 
-    if let Ok(mut map) = APP_INSTALLED.lock() {
-        map.remove(&pid);
+    if mode == UninstallMode::KeepData {
+        let data_paths = Product::parse_product_toml(&app_config.get_meta_product_dir(pid))
+            .map(|product| product.data_paths.unwrap_or_default())
+            .unwrap_or_default();
+        if !data_paths.is_empty() {
+            fs::create_dir_all(&install_dir).map_err(|e| ToyStudioError::io(&install_dir, e))?;
+            move_data_paths(&backup_dir, &install_dir, &data_paths)?;
+        }
     }
-
+    prune_backups(&backup_root, app_config.max_backups());
     Ok(())
 }
 
-/// 启动产品
+/// 启动产品，`profile` 为启动配置名称，为空时使用产品默认的启动命令；
+/// `version` 为未使用版本的ID，为空时启动当前使用的版本
 #[tauri::command]
-pub fn product_startup(app_handle: AppHandle, pid: String) -> Result<(), String> {
+pub fn product_startup(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+    profile: Option<String>,
+    version: Option<String>,
+) -> ToyResult<()> {
     println!("-----------------------product_startup--------------------------------");
-    println!("product_id:{}", pid);
+    if jobs.is_busy(&pid) {
+        return Err(ToyStudioError::ProductBusy { product_id: pid });
+    }
+    if version.as_deref().unwrap_or_default().is_empty() {
+        check_lazy_lfs(&app_handle, &jobs, &pid)?;
+    }
+    supervisor.reset_restart_attempts(&pid);
+    start_product(&app_handle, &supervisor, &pid, profile, version)
+}
+
+/// 产品配置为 `lfs = "lazy"` 且 LFS 文件尚未下载时，加入下载任务并返回 `LfsDownloadPending`
+fn check_lazy_lfs(app_handle: &AppHandle, jobs: &JobQueue, pid: &str) -> ToyResult<()> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+    let product = Product::parse_product_toml(&app_config.get_meta_product_dir(pid))?;
+    if product.download.lfs != Some(LfsMode::Lazy) {
+        return Ok(());
+    }
+    let install_dir = app_config
+        .get_product_install_path()
+        .join(get_file_name_without_suffix(pid));
+    if !is_lfs_repository(&install_dir) || !has_missing_lfs_files(&install_dir)? {
+        return Ok(());
+    }
+    jobs.enqueue(JobKind::PullLfs, pid)?;
+    Err(ToyStudioError::LfsDownloadPending {
+        product_id: pid.to_string(),
+    })
+}
+
+/// 下载 `lfs = "lazy"` 的产品的 Git LFS 文件
+fn download_lfs_files(
+    app_handle: &AppHandle,
+    pipeline: &InstallPipeline,
+    pid: &str,
+) -> ToyResult<()> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+    let product = Product::parse_product_toml(&app_config.get_meta_product_dir(pid))?;
+    let install_dir = app_config
+        .get_product_install_path()
+        .join(get_file_name_without_suffix(&product.id));
+    let clone_options = app_config.clone_options(product.download.submodules());
+    pipeline.run_remote_stage(InstallStage::Lfs, &clone_options, |options, output| {
+        git_lfs_pull(&install_dir, options, output)
+    })
+}
+
+/// 产品运行的目录：`version` 为未使用版本的ID时为该版本的目录，否则为安装目录
+fn product_run_dir(
+    app_config: &AppConfig,
+    product_id: &str,
+    version: Option<&str>,
+) -> ToyResult<PathBuf> {
+    let product_name = get_file_name_without_suffix(product_id);
+    match version {
+        Some(version) => find_version(
+            &app_config.get_product_versions_path(&product_name),
+            version,
+        )
+        .ok_or_else(|| ToyStudioError::VersionNotFound {
+            product_id: product_id.to_string(),
+            version: version.to_string(),
+        }),
+        None => Ok(app_config.get_product_install_path().join(product_name)),
+    }
+}
+
+/// 启动产品，手动启动和自动重启共用
+pub(crate) fn start_product(
+    app_handle: &AppHandle,
+    supervisor: &Supervisor,
+    pid: &str,
+    profile: Option<String>,
+    version: Option<String>,
+) -> ToyResult<()> {
+    println!(
+        "product_id:{}, profile:{:?}, version:{:?}",
+        pid, profile, version
+    );
 
     // 1. get product info by product_id
-    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let app_config = AppConfig::get_app_config(app_handle)?;
 
     let product_dir = app_config.get_meta_products_dir();
-    let product_file = product_dir.join(&pid);
+    let product_file = product_dir.join(pid);
     println!("abs product_file:{:?}", product_file);
-    let product = Product::parse_product_toml(&product_file)?;
+    let mut product = Product::parse_product_toml(&product_file)?;
     let product_name = get_file_name_without_suffix(&product.id);
     println!("product_name:{}", product_name);
 
     println!("product:{:?}", product);
 
+    let profile = match profile.filter(|name| !name.is_empty()) {
+        Some(name) => Some(LaunchProfile::find(app_handle, &product.id, &name)?),
+        None => None,
+    };
+    let version = version.filter(|version| !version.is_empty());
+    let run_dir = product_run_dir(&app_config, &product.id, version.as_deref())?;
+
+    // 分配端口，避免与其他产品冲突
+    let port_file = app_config.get_port_file_path();
+    let port = allocate_port(
+        &port_file,
+        &product.id,
+        product.port,
+        &supervisor.used_ports(),
+    )?;
+    product.resolve_port(port);
+    println!("port:{}", port);
+
+    let health_check = match &product.health {
+        Some(health) => HealthCheck::new(&product.id, health)?,
+        None => None,
+    };
+
     // 2. check if product is already running
-    if let Ok(map) = APP_INSTALLED.lock() {
-        if let Some(Some(child)) = map.get(&product.id) {
+    supervisor.begin_start(
+        &product.id,
+        health_check.is_some(),
+        port,
+        profile.as_ref().map(|profile| profile.name.as_str()),
+        version.as_deref(),
+    )?;
+
+    let result = spawn_product(
+        &app_config,
+        &product,
+        &product_name,
+        &run_dir,
+        port,
+        profile.as_ref(),
+    );
+    let child = match result {
+        Ok(child) => child,
+        Err(e) => {
+            supervisor.fail_start(&product.id);
+            return Err(e);
+        }
+    };
+
+    // 6. capture stdout/stderr into log files
+    let log_dir = app_config.get_product_log_path(&product_name);
+    let session = new_log_session();
+    let mut watchers = vec![ui_url_watcher(
+        app_handle,
+        &product.id,
+        product.web_url.clone(),
+    )];
+    watchers.extend(
+        health_check
+            .as_ref()
+            .and_then(|health| health.line_watcher()),
+    );
+    let relay = match capture_output(
+        app_handle,
+        &product.id,
+        &log_dir,
+        &session,
+        &child,
+        watchers,
+    ) {
+        Ok(relay) => relay,
+        Err(e) => {
+            // 输出无人读取会阻塞子进程，直接结束
             if let Ok(mut child) = child.lock() {
-                if let Ok(None) = child.try_wait() {
-                    return Err("Product already running".to_string());
-                }
+                let _ = child.kill();
+                let _ = child.wait();
             }
+            supervisor.fail_start(&product.id);
+            return Err(e);
         }
+    };
+    supervisor.attach(&product.id, child, &session, relay);
+    println!("product started:{}, log session:{}", pid, session);
+    if let Err(e) = save_port(&port_file, &product.id, port) {
+        println!("save port of {} error:{}", product.id, e);
     }
 
-    // 3. get install_dir
-    let install_dir = app_config.get_product_install_path().join(&product_name);
+    // 固定的 Web 界面地址，或就绪检查的 HTTP 地址；产品输出中识别到地址后会被替换
+    let health_url = product
+        .health
+        .as_ref()
+        .and_then(|health| health.http.as_deref());
+    if let Some(ui_url) = resolve_ui_url(product.web_url.as_deref(), health_url) {
+        supervisor.set_ui_url(&product.id, &ui_url);
+    }
+
+    // 7. wait for the product to become ready
+    if let Some(health_check) = health_check {
+        health_check.spawn(app_handle.clone(), product.id.clone());
+    }
+    Ok(())
+}
+
+/// 准备目录并在 `run_dir` 中创建产品进程
+fn spawn_product(
+    app_config: &AppConfig,
+    product: &Product,
+    product_name: &str,
+    run_dir: &Path,
+    port: u16,
+    profile: Option<&LaunchProfile>,
+) -> ToyResult<std::sync::Arc<Mutex<std::process::Child>>> {
+    // 3. get install_dir, or the working dir of the launch profile
+    let install_dir = run_dir.to_path_buf();
+    let working_dir = profile
+        .and_then(|profile| profile.working_dir.as_deref())
+        .filter(|dir| !dir.trim().is_empty());
+    let install_dir = match working_dir {
+        Some(dir) => install_dir.join(dir),
+        None => install_dir,
+    };
     println!("install_dir:{:?}", &install_dir);
 
     // 4. get output_dir & startup command
     let output_dir = app_config.get_output_path();
-    fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&output_dir).map_err(|e| ToyStudioError::io(&output_dir, e))?;
     println!("output_dir:{:?}", &output_dir);
 
-    let startup = product.get_startup_command(&output_dir)?;
+    let startup = product.get_startup_command(&output_dir, port, profile)?;
     println!("startup:{}", startup);
 
     // 5. run startup command
@@ -242,41 +695,164 @@ pub fn product_startup(app_handle: AppHandle, pid: String) -> Result<(), String>
     args.insert(0, "run".to_string());
     println!("args:{:?}", args);
 
-    let child = crate::run_command(install_dir, "uv", &args, &product_name, &product.id)?;
+    let envs = product.get_launch_envs(port, profile);
+    crate::run_command(install_dir, "uv", &args, &envs, product_name, &product.id)
+}
 
-    if let Ok(mut map) = APP_INSTALLED.lock() {
-        map.insert(pid.clone(), Some(child));
-        println!("APP_INSTALLED ok:{}", pid.clone());
-    } else {
-        println!("APP_INSTALLED failed:{}", pid.clone());
-    }
-    Ok(())
+/// 在浏览器中打开产品的 Web 界面
+#[tauri::command]
+pub fn product_open_ui(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    pid: String,
+) -> ToyResult<()> {
+    let status = supervisor.status(&pid);
+    let ui_url = status
+        .ui_url
+        .filter(|_| status.state.is_alive())
+        .ok_or_else(|| ToyStudioError::invalid_argument(format!("{} has no web UI", pid)))?;
+    println!("product_open_ui:{} {}", pid, ui_url);
+
+    app_handle
+        .opener()
+        .open_url(ui_url, None::<&str>)
+        .map_err(ToyStudioError::internal)
 }
 
-/// 关闭产品
+/// 关闭产品，返回最终使产品退出的步骤
 #[tauri::command]
-pub fn product_shutdown(pid: String) -> Result<(), String> {
+pub async fn product_shutdown(app_handle: AppHandle, pid: String) -> ToyResult<String> {
     println!("product_shutdown:{}", pid);
 
-    if let Ok(mut map) = APP_INSTALLED.lock() {
-        let mut child = map.remove(&pid);
-        if let Some(Some(child)) = child.take() {
-            if let Ok(mut child) = child.lock() {
-                child.kill().map_err(|e| e.to_string())?;
-            }
+    // 关闭过程会等待进程退出，放到阻塞线程中执行，避免占用异步运行时
+    let outcome = tauri::async_runtime::spawn_blocking(move || {
+        stop_product(&app_handle, &app_handle.state::<Supervisor>(), &pid)
+    })
+    .await
+    .map_err(ToyStudioError::internal)??;
+    serde_json::to_string(&outcome).map_err(ToyStudioError::internal)
+}
+
+/// 停止产品：先执行产品配置中的关闭命令，再逐步结束进程树
+pub(crate) fn stop_product(
+    app_handle: &AppHandle,
+    supervisor: &Supervisor,
+    pid: &str,
+) -> ToyResult<ShutdownOutcome> {
+    // 产品配置读取失败时直接结束进程
+    let shutdown = match shutdown_command(app_handle, supervisor, pid) {
+        Ok(shutdown) => shutdown,
+        Err(e) => {
+            println!("shutdown command of {} error:{}", pid, e);
+            None
         }
-    }
-    Ok(())
+    };
+    supervisor.stop(pid, shutdown)
 }
 
-/// 升级产品
+/// 根据产品配置生成关闭命令，与启动命令一样通过 `uv run` 在安装目录中执行
+fn shutdown_command(
+    app_handle: &AppHandle,
+    supervisor: &Supervisor,
+    pid: &str,
+) -> ToyResult<Option<ShutdownCommand>> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+    let product = Product::parse_product_toml(&app_config.get_meta_product_dir(pid))?;
+
+    let output_dir = app_config.get_output_path();
+    let status = supervisor.status(pid);
+    let Some(shutdown) = product.get_shutdown_command(&output_dir, status.os_pid, status.port)?
+    else {
+        return Ok(None);
+    };
+
+    let mut args = split_args(&shutdown);
+    args.insert(0, "run".to_string());
+    Ok(Some(ShutdownCommand {
+        current_dir: product_run_dir(&app_config, &product.id, status.version.as_deref())?,
+        program: "uv".to_string(),
+        args,
+    }))
+}
+
+/// 获取产品安装及运行状态
 #[tauri::command]
-pub fn product_upgrade(app_handle: AppHandle, pid: String) -> Result<(), String> {
-    println!("product_id:{}", pid);
+pub fn product_status(supervisor: State<'_, Supervisor>, pid: String) -> ToyResult<String> {
+    let status = supervisor.status(&pid);
+    serde_json::to_string(&status).map_err(ToyStudioError::internal)
+}
 
+/// 获取产品本次运行的资源占用采样记录（CPU、内存、显存）
+#[tauri::command]
+pub fn product_metrics(sampler: State<'_, MetricsSampler>, pid: String) -> ToyResult<String> {
+    let history = sampler.history(&pid);
+    serde_json::to_string(&history).map_err(ToyStudioError::internal)
+}
+
+/// 获取产品日志
+///
+/// `session` 为空时读取最新会话；`tail` 只返回最后 N 行；`since` 只返回该毫秒时间戳之后的行
+#[tauri::command]
+pub fn product_get_logs(
+    app_handle: AppHandle,
+    pid: String,
+    session: Option<String>,
+    tail: Option<usize>,
+    since: Option<u64>,
+) -> ToyResult<String> {
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let product_name = get_file_name_without_suffix(&pid);
+    let log_dir = app_config.get_product_log_path(&product_name);
+
+    let lines = read_logs(&log_dir, &pid, session.as_deref(), tail, since)?;
+    serde_json::to_string(&lines).map_err(ToyStudioError::internal)
+}
+
+/// 获取产品的日志会话列表，最新的在前
+#[tauri::command]
+pub fn product_list_log_sessions(app_handle: AppHandle, pid: String) -> ToyResult<String> {
     let app_config = AppConfig::get_app_config(&app_handle)?;
+    let product_name = get_file_name_without_suffix(&pid);
+    let log_dir = app_config.get_product_log_path(&product_name);
+
+    let sessions = list_log_sessions(&log_dir)?;
+    serde_json::to_string(&sessions).map_err(ToyStudioError::internal)
+}
+
+/// 升级产品，加入后台任务队列；用户固定了版本时不允许升级
+#[tauri::command]
+pub fn product_upgrade(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+) -> ToyResult<String> {
+    println!("product_id:{}", pid);
+    if supervisor.status(&pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning { product_id: pid });
+    }
+    if let Some(commit) = UpdatePreference::load(&app_handle, &pid)?.pinned_commit {
+        return Err(ToyStudioError::ProductPinned {
+            product_id: pid,
+            commit,
+        });
+    }
+    let job = jobs.enqueue(JobKind::Upgrade, &pid)?;
+    serde_json::to_string(&job).map_err(ToyStudioError::internal)
+}
 
-    let product_file = app_config.get_meta_product_dir(&pid);
+/// 拉取最新代码（产品配置固定了标签或提交时检出该版本）并同步依赖
+fn upgrade_product(app_handle: &AppHandle, pipeline: &InstallPipeline, pid: &str) -> ToyResult<()> {
+    let supervisor = app_handle.state::<Supervisor>();
+    if supervisor.status(pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning {
+            product_id: pid.to_string(),
+        });
+    }
+
+    let app_config = AppConfig::get_app_config(app_handle)?;
+
+    let product_file = app_config.get_meta_product_dir(pid);
     let product = Product::parse_product_toml(&product_file)?;
     let product_name = get_file_name_without_suffix(&product.id);
 
@@ -284,34 +860,101 @@ pub fn product_upgrade(app_handle: AppHandle, pid: String) -> Result<(), String>
 
     let install_dir = app_config.get_product_install_path().join(&product_name);
 
-    fs::create_dir_all(&install_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&install_dir).map_err(|e| ToyStudioError::io(&install_dir, e))?;
     println!("install_dir:{:?}", &install_dir);
 
-    let git_url = product.download.git_url;
-    let branch = product.download.branch;
-    let bak_dir = app_config.get_product_bak_path();
-
-    git_clone(&git_url, &branch, &install_dir, &bak_dir)?;
-    uv_venv(&install_dir, &product.download.python_version)?;
-    uv_sync(&install_dir)?;
+    let revision = install_revision(app_handle, &product);
+    let clone_options = app_config.clone_options(product.download.submodules());
+    let bak_dir = app_config.get_product_backup_path(&product_name);
+
+    // 升级失败或被取消时回到升级前的提交并重新同步依赖
+    let previous_commit = git_head_commit(&install_dir).ok();
+    if let Err(e) = run_install_stages(
+        pipeline,
+        &product.download,
+        revision.as_deref(),
+        &clone_options,
+        &install_dir,
+        &bak_dir,
+    ) {
+        if let Some(commit) = previous_commit {
+            pipeline.rollback(|output| {
+                git_reset_hard(&install_dir, &commit)?;
+                uv_sync(&install_dir, output)
+            });
+        }
+        return Err(e);
+    }
 
     println!(
-        "--------------------------------APP_INSTALLED insert:{}-----------------------",
+        "--------------------------------installed:{}-----------------------",
         product.id
     );
-    if let Ok(mut map) = APP_INSTALLED.lock() {
-        map.insert(product.id, None);
-    }
+    supervisor.mark_installed(&product.id);
+    UpdatePreference::record_installed(app_handle, &product.id, &product.version);
 
     Ok(())
 }
 
+/// 重新安装和升级共用的安装步骤：git clone（或 pull）-> uv venv -> uv sync
+fn run_install_stages(
+    pipeline: &InstallPipeline,
+    download: &Download,
+    revision: Option<&str>,
+    clone_options: &CloneOptions,
+    install_dir: &Path,
+    bak_dir: &Path,
+) -> ToyResult<()> {
+    pipeline.run_remote_stage(InstallStage::Clone, clone_options, |options, output| {
+        git_clone(
+            &download.git_url,
+            &download.branch,
+            revision,
+            install_dir,
+            bak_dir,
+            options,
+            output,
+        )
+    })?;
+    pipeline.run_remote_stage(InstallStage::Lfs, clone_options, |options, output| {
+        pull_lfs_files(download.lfs, install_dir, options, output)
+    })?;
+    pipeline.run_stage(InstallStage::CreateVenv, |output| {
+        uv_venv(install_dir, &download.python_version, output)
+    })?;
+    pipeline.run_stage(InstallStage::Sync, |output| uv_sync(install_dir, output))
+}
+
+/// 安装时下载 Git LFS 文件，产品配置中未设置 `lfs` 时根据仓库判断是否需要
+fn pull_lfs_files(
+    lfs: Option<LfsMode>,
+    dir: &Path,
+    clone_options: &CloneOptions,
+    output: OutputSink,
+) -> ToyResult<()> {
+    match lfs {
+        Some(LfsMode::Enabled) => git_lfs_pull(dir, clone_options, output),
+        None if is_lfs_repository(dir) => git_lfs_pull(dir, clone_options, output),
+        Some(LfsMode::Lazy) if is_lfs_repository(dir) => {
+            output.line(
+                LogStream::Stdout,
+                "LFS files will be downloaded on first launch",
+            );
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// 初始化已安装的产品
-pub(crate) fn init_installed_products(app_handle: &AppHandle) -> Result<(), String> {
+pub(crate) fn init_installed_products(app_handle: &AppHandle) -> ToyResult<()> {
     let app_config = AppConfig::get_app_config(&app_handle)?;
 
+    clean_staging_dir(&app_config.get_product_temp_path());
+
     let products_dir = app_config.get_product_install_path();
-    let product_files = fs::read_dir(&products_dir).map_err(|e| e.to_string())?;
+    let product_files =
+        fs::read_dir(&products_dir).map_err(|e| ToyStudioError::io(&products_dir, e))?;
 
     for product_file in product_files {
         match product_file {
@@ -319,13 +962,18 @@ pub(crate) fn init_installed_products(app_handle: &AppHandle) -> Result<(), Stri
                 println!("product_file list error:{}", err);
             }
             Ok(product_file) => {
+                // 卸载时保留数据的目录不是git仓库，不视为已安装
+                if !product_file.path().join(".git").exists() {
+                    continue;
+                }
                 let product_name = product_file.file_name();
                 let mut product_id = product_name.to_string_lossy().to_string();
                 product_id.push_str(".toml");
-                println!("--------------------------------APP_INSTALLED insert:{}-----[------------------", product_id);
-                if let Ok(mut child) = APP_INSTALLED.lock() {
-                    child.insert(product_id, None);
-                }
+                println!(
+                    "--------------------------------installed:{}-----------------------",
+                    product_id
+                );
+                app_handle.state::<Supervisor>().mark_installed(&product_id);
             }
         }
     }
@@ -333,26 +981,39 @@ pub(crate) fn init_installed_products(app_handle: &AppHandle) -> Result<(), Stri
 }
 
 /// 初始化产品元数据
-pub(crate) fn init_meta_products(app_handle: &AppHandle) -> Result<(), String> {
+pub(crate) fn init_meta_products(app_handle: &AppHandle) -> ToyResult<()> {
     let app_config = AppConfig::get_app_config(&app_handle)?;
-    let bak_dir = app_config.get_product_bak_path();
+    let bak_dir = app_config.get_product_backup_path(META_PRODUCTS_BACKUP);
     let meta_products_dir = app_config.get_meta_products_dir();
 
     let products_dir = Path::new("products");
-    let canonicalize = products_dir.canonicalize().map_err(|e| e.to_string())?;
+    let canonicalize = products_dir
+        .canonicalize()
+        .map_err(|e| ToyStudioError::io(products_dir, e))?;
     println!("products_dir:{:?}", canonicalize);
 
     let git_url = "https://github.com/shoucuo-ai/toy-studio-products.git";
     let branch = "main";
-    if let Err(_e) = git_clone(git_url, branch, &canonicalize, &bak_dir) {
+    let on_line = |_, line: &str| println!("{}", line);
+    let output = OutputSink::new(&on_line);
+    let clone_options = app_config.clone_options(false);
+    let result = with_mirrors(app_handle, &clone_options, output, |options| {
+        git_clone(
+            git_url,
+            branch,
+            None,
+            &canonicalize,
+            &bak_dir,
+            options,
+            output,
+        )
+    });
+    if let Err(_e) = result {
         println!("git_clone {} error:{}", git_url, _e);
-        let git_proxy = GIT_PROXY.lock().map_err(|e| e.to_string())?;
-        let git_url_proxy = format!("{git_proxy}/{}", git_url);
-        println!("try to use git proxy:{}, url:{}", git_proxy, git_url_proxy);
-        let _ = git_clone(&git_url_proxy, &branch, &canonicalize, &bak_dir);
     }
 
-    let product_files = fs::read_dir(&products_dir).map_err(|e| e.to_string())?;
+    let product_files =
+        fs::read_dir(&products_dir).map_err(|e| ToyStudioError::io(&products_dir, e))?;
 
     for product_file in product_files {
         match product_file {
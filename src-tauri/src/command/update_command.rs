@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::{
+    diff_lock, get_file_name_without_suffix, git_fetch, git_head_commit, git_log, git_show_file,
+    is_git_repository, with_mirrors, AppConfig, JobQueue, OutputSink, Product, ToyResult,
+    ToyStudioError, UpdateCheck, UpdatePreference, LOCK_FILE,
+};
+
+/// 获取已安装产品的安装目录，未安装时返回错误
+fn installed_dir(app_config: &AppConfig, pid: &str) -> ToyResult<PathBuf> {
+    let install_dir = app_config
+        .get_product_install_path()
+        .join(get_file_name_without_suffix(pid));
+    if !is_git_repository(&install_dir) {
+        return Err(ToyStudioError::invalid_argument(format!(
+            "{} is not installed",
+            pid
+        )));
+    }
+    Ok(install_dir)
+}
+
+/// 检查产品更新
+///
+/// 拉取远程分支（产品配置固定了标签或提交时拉取该版本）但不合并，比较已安装的提交与远程的提交、
+/// 安装时与当前产品配置中的版本，返回之间的提交记录和 `uv.lock` 中依赖的变化。
+#[tauri::command]
+pub async fn product_check_update(
+    app_handle: AppHandle,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+) -> ToyResult<String> {
+    println!("product_check_update:{}", pid);
+    if jobs.is_busy(&pid) {
+        return Err(ToyStudioError::ProductBusy { product_id: pid });
+    }
+
+    // 拉取远程仓库耗时较长，放到阻塞线程中执行，避免占用异步运行时
+    let check = tauri::async_runtime::spawn_blocking(move || check_update(&app_handle, pid))
+        .await
+        .map_err(ToyStudioError::internal)??;
+    serde_json::to_string(&check).map_err(ToyStudioError::internal)
+}
+
+fn check_update(app_handle: &AppHandle, pid: String) -> ToyResult<UpdateCheck> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+    let product = Product::parse_product_toml(&app_config.get_meta_product_dir(&pid))?;
+    let install_dir = installed_dir(&app_config, &pid)?;
+    let clone_options = app_config.clone_options(product.download.submodules());
+
+    let revision = product.download.revision().map(str::to_string);
+    let branch = product.download.branch;
+    let current_commit = git_head_commit(&install_dir)?;
+    let on_line = |_, line: &str| println!("{}", line);
+    let mut remote_commit = String::new();
+    let mut remote_lock = None;
+    with_mirrors(
+        app_handle,
+        &clone_options,
+        OutputSink::new(&on_line),
+        |options| {
+            remote_commit = git_fetch(
+                &install_dir,
+                revision.as_deref().unwrap_or(&branch),
+                options,
+            )?;
+            // 部分克隆时锁文件的内容需要下载，与拉取使用相同的镜像
+            remote_lock = git_show_file(&install_dir, &remote_commit, LOCK_FILE, options)?;
+            Ok(())
+        },
+    )?;
+    let commits = git_log(&install_dir, &current_commit, &remote_commit)?;
+    let current_lock = git_show_file(&install_dir, &current_commit, LOCK_FILE, &clone_options)?;
+    let lock_changes = diff_lock(current_lock.as_deref(), remote_lock.as_deref());
+
+    let preference = UpdatePreference::load(app_handle, &pid)?;
+    let version_changed = preference
+        .installed_version
+        .as_deref()
+        .is_some_and(|version| version != product.version);
+    let check = UpdateCheck {
+        product_id: pid,
+        branch,
+        revision,
+        skipped: preference.skipped_commit.as_deref() == Some(remote_commit.as_str()),
+        // 远程的提交可能不是已安装提交的后续提交（例如产品配置固定到更早的提交），此时提交记录为空
+        has_update: current_commit != remote_commit || version_changed,
+        current_commit,
+        remote_commit,
+        installed_version: preference.installed_version,
+        latest_version: product.version,
+        commits,
+        lock_changes,
+        pinned_commit: preference.pinned_commit,
+    };
+    Ok(check)
+}
+
+/// 跳过远程的 `commit`，远程出现更新的提交前不再提示
+#[tauri::command]
+pub fn product_skip_update(app_handle: AppHandle, pid: String, commit: String) -> ToyResult<()> {
+    println!("product_skip_update:{} {}", pid, commit);
+    UpdatePreference::update(&app_handle, &pid, |preference| {
+        preference.skipped_commit = Some(commit);
+    })
+}
+
+/// 固定产品当前安装的提交，取消固定前不提示更新，也不允许升级
+#[tauri::command]
+pub fn product_pin_version(app_handle: AppHandle, pid: String) -> ToyResult<()> {
+    println!("product_pin_version:{}", pid);
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let commit = git_head_commit(installed_dir(&app_config, &pid)?)?;
+    UpdatePreference::update(&app_handle, &pid, |preference| {
+        preference.pinned_commit = Some(commit);
+    })
+}
+
+/// 取消固定产品的版本
+#[tauri::command]
+pub fn product_unpin_version(app_handle: AppHandle, pid: String) -> ToyResult<()> {
+    println!("product_unpin_version:{}", pid);
+    UpdatePreference::update(&app_handle, &pid, |preference| {
+        preference.pinned_commit = None;
+    })
+}
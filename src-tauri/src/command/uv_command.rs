@@ -1,16 +1,50 @@
 use std::path::Path;
+use std::process::{Command, Output};
 
-#[tauri::command]
-pub fn uv_get_cache_dir() -> Result<String, String> {
-    let output = std::process::Command::new("uv")
-        .arg("cache")
-        .arg("dir")
-        .output()
-        .map_err(|e| e.to_string())?;
+use crate::{run_with_output, OutputSink, ToyResult, ToyStudioError};
+
+/// 执行uv命令，失败时返回带退出码和stderr的错误
+fn execute_uv_command(command: &mut Command, args: &[&str]) -> ToyResult<Output> {
+    let output = command.args(args).output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ToyStudioError::UvNotFound,
+        _ => ToyStudioError::spawn("uv", e),
+    })?;
 
     if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+        return Err(ToyStudioError::UvCommandFailed {
+            args: args.join(" "),
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(output)
+}
+
+/// 执行耗时的uv命令（venv、sync 等），输出逐行交给 `output`
+fn execute_uv_command_with_output(
+    command: &mut Command,
+    args: &[&str],
+    output: OutputSink,
+) -> ToyResult<()> {
+    let (status, stderr) =
+        run_with_output(command.args(args), output).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ToyStudioError::UvNotFound,
+            _ => ToyStudioError::spawn("uv", e),
+        })?;
+
+    if !status.success() {
+        return Err(ToyStudioError::UvCommandFailed {
+            args: args.join(" "),
+            exit_code: status.code(),
+            stderr,
+        });
     }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn uv_get_cache_dir() -> ToyResult<String> {
+    let output = execute_uv_command(&mut Command::new("uv"), &["cache", "dir"])?;
 
     let cache_dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
@@ -18,21 +52,19 @@ pub fn uv_get_cache_dir() -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn uv_get_python_envs() -> Result<String, String> {
-    let output = std::process::Command::new("uv")
-        .arg("python")
-        .arg("list")
-        .arg("--output-format")
-        .arg("json")
-        .arg("--python-preference")
-        .arg("only-managed")
-        .arg("--only-installed")
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
-    }
+pub fn uv_get_python_envs() -> ToyResult<String> {
+    let output = execute_uv_command(
+        &mut Command::new("uv"),
+        &[
+            "python",
+            "list",
+            "--output-format",
+            "json",
+            "--python-preference",
+            "only-managed",
+            "--only-installed",
+        ],
+    )?;
 
     let envs = String::from_utf8_lossy(&output.stdout)
         .lines()
@@ -42,31 +74,25 @@ pub fn uv_get_python_envs() -> Result<String, String> {
     Ok(envs)
 }
 
-pub fn uv_venv<P: AsRef<Path>>(install_dir: P, python_version: &str) -> Result<String, String> {
-    let output = std::process::Command::new("uv")
-        .arg("venv")
-        .arg("-p")
-        .arg(python_version)
-        .current_dir(install_dir)
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
-    }
-    Ok(String::from("success"))
+pub fn uv_venv<P: AsRef<Path>>(
+    install_dir: P,
+    python_version: &str,
+    output: OutputSink,
+) -> ToyResult<()> {
+    let mut command = Command::new("uv");
+    command.current_dir(install_dir);
+    // 虚拟环境在临时目录中创建后会被移动到安装目录，需要可重定位
+    execute_uv_command_with_output(
+        &mut command,
+        &["venv", "--relocatable", "-p", python_version],
+        output,
+    )
 }
 
-pub fn uv_sync<P: AsRef<Path>>(install_dir: P) -> Result<String, String> {
-    let output = std::process::Command::new("uv")
-        .arg("sync")
-        .current_dir(install_dir)
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
-    }
-
-    Ok(String::from("success"))
+pub fn uv_sync<P: AsRef<Path>>(install_dir: P, output: OutputSink) -> ToyResult<()> {
+    let mut command = Command::new("uv");
+    command.current_dir(install_dir);
+    // 与 `uv venv --relocatable` 相同，依赖在临时目录中安装后会被移动：
+    // 产品自身不以可编辑模式安装，否则其 `.pth` 文件会指向已删除的临时目录
+    execute_uv_command_with_output(&mut command, &["sync", "--no-editable"], output)
 }
@@ -0,0 +1,137 @@
+use tauri::{AppHandle, State};
+
+use crate::{
+    activate_version, active_version, delete_version, find_version, get_file_name_without_suffix,
+    list_versions, write_version_file, AppConfig, InstalledVersion, JobKind, JobQueue, Product,
+    Supervisor, ToyResult, ToyStudioError, UpdatePreference,
+};
+
+/// 获取产品的所有已安装版本，当前使用的版本在前
+#[tauri::command]
+pub fn product_list_versions(app_handle: AppHandle, pid: String) -> ToyResult<String> {
+    let versions = load_versions(&app_handle, &pid)?;
+    serde_json::to_string(&versions).map_err(ToyStudioError::internal)
+}
+
+fn load_versions(app_handle: &AppHandle, pid: &str) -> ToyResult<Vec<InstalledVersion>> {
+    let app_config = AppConfig::get_app_config(app_handle)?;
+    let product_name = get_file_name_without_suffix(pid);
+    let installed_version = UpdatePreference::load(app_handle, pid)?.installed_version;
+    Ok(list_versions(
+        &app_config.get_product_install_path().join(&product_name),
+        &app_config.get_product_versions_path(&product_name),
+        installed_version.as_deref(),
+    ))
+}
+
+/// 与当前版本并行安装产品配置中的最新版本，加入后台任务队列
+#[tauri::command]
+pub fn product_install_version(jobs: State<'_, JobQueue>, pid: String) -> ToyResult<String> {
+    println!("product_install_version:{}", pid);
+    let job = jobs.enqueue(JobKind::InstallVersion, &pid)?;
+    serde_json::to_string(&job).map_err(ToyStudioError::internal)
+}
+
+/// 将未使用的版本切换为当前使用的版本
+///
+/// 原来的版本移到 `.local/versions/<产品>` 中保留，产品配置中声明的数据路径随之移到新的版本中。
+#[tauri::command]
+pub fn product_activate_version(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    jobs: State<'_, JobQueue>,
+    pid: String,
+    version_id: String,
+) -> ToyResult<()> {
+    println!("product_activate_version:{} {}", pid, version_id);
+    if supervisor.status(&pid).state.is_alive() {
+        return Err(ToyStudioError::ProductAlreadyRunning { product_id: pid });
+    }
+    if jobs.is_busy(&pid) {
+        return Err(ToyStudioError::ProductBusy { product_id: pid });
+    }
+
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let product_name = get_file_name_without_suffix(&pid);
+    let install_dir = app_config.get_product_install_path().join(&product_name);
+    let versions_dir = app_config.get_product_versions_path(&product_name);
+
+    let target = load_versions(&app_handle, &pid)?
+        .into_iter()
+        .find(|version| !version.active && version.id == version_id)
+        .ok_or_else(|| ToyStudioError::VersionNotFound {
+            product_id: pid.clone(),
+            version: version_id.clone(),
+        })?;
+    let installed_version = UpdatePreference::load(&app_handle, &pid)?.installed_version;
+    let active = active_version(&install_dir, installed_version.as_deref());
+    let data_paths = Product::parse_product_toml(&app_config.get_meta_product_dir(&pid))
+        .map(|product| product.data_paths.unwrap_or_default())
+        .unwrap_or_default();
+
+    // 当前版本移到版本目录后，仍能取得其在产品配置中的版本
+    if let Some(active) = &active {
+        write_version_file(&install_dir, &active.version)?;
+    }
+    activate_version(
+        &install_dir,
+        &versions_dir.join(&target.id),
+        &versions_dir,
+        active.as_ref().map(|active| active.id.as_str()),
+        &data_paths,
+    )?;
+
+    UpdatePreference::record_installed(&app_handle, &pid, &target.version);
+    supervisor.mark_installed(&pid);
+    Ok(())
+}
+
+/// 删除未使用的版本，正在运行的版本不能删除
+#[tauri::command]
+pub fn product_delete_version(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    pid: String,
+    version_id: String,
+) -> ToyResult<()> {
+    println!("product_delete_version:{} {}", pid, version_id);
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let versions_dir = app_config.get_product_versions_path(&get_file_name_without_suffix(&pid));
+    let version_dir = find_version(&versions_dir, &version_id).ok_or_else(|| {
+        ToyStudioError::VersionNotFound {
+            product_id: pid.clone(),
+            version: version_id.clone(),
+        }
+    })?;
+    if is_running_version(&supervisor, &pid, &version_id) {
+        return Err(ToyStudioError::ProductAlreadyRunning { product_id: pid });
+    }
+    delete_version(&version_dir)
+}
+
+/// 删除所有未使用的版本，正在运行的版本除外
+#[tauri::command]
+pub fn product_prune_versions(
+    app_handle: AppHandle,
+    supervisor: State<'_, Supervisor>,
+    pid: String,
+) -> ToyResult<()> {
+    println!("product_prune_versions:{}", pid);
+    let app_config = AppConfig::get_app_config(&app_handle)?;
+    let versions_dir = app_config.get_product_versions_path(&get_file_name_without_suffix(&pid));
+    for version in load_versions(&app_handle, &pid)? {
+        if version.active || is_running_version(&supervisor, &pid, &version.id) {
+            continue;
+        }
+        if let Some(version_dir) = find_version(&versions_dir, &version.id) {
+            delete_version(&version_dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// 产品正在运行指定的未使用版本
+fn is_running_version(supervisor: &Supervisor, pid: &str, version_id: &str) -> bool {
+    let status = supervisor.status(pid);
+    status.state.is_alive() && status.version.as_deref() == Some(version_id)
+}
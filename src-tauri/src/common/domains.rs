@@ -1,11 +1,23 @@
-use std::{fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
-use crate::{uv_get_cache_dir, APP_INSTALLED};
+use crate::{
+    get_file_name_without_suffix, latest_backup_time, read_port_file, uv_get_cache_dir,
+    CloneOptions, GitMirror, ProcessState, Supervisor, ToyResult, ToyStudioError,
+};
 
-use super::template_replace_single;
+use super::{template_replace, template_replace_single};
+
+/// 每个产品默认保留的备份数
+const DEFAULT_MAX_BACKUPS: usize = 5;
+/// 克隆、拉取失败后默认的重试次数
+const DEFAULT_CLONE_RETRIES: u32 = 3;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
@@ -14,6 +26,35 @@ pub struct AppConfig {
     pub enable_external_uv: bool,
     pub uv_cache_dir: String,
     pub dev_mode: Option<bool>,
+    /// 关闭 ToyStudio 时如何处理运行中的产品
+    pub exit_policy: Option<ExitPolicy>,
+    /// 同时执行的安装、升级任务数
+    pub max_concurrent_jobs: Option<usize>,
+    /// 每个产品保留的备份数
+    pub max_backups: Option<usize>,
+    /// 克隆时只下载最近的提交数，为空或 0 时下载完整历史
+    pub clone_depth: Option<u32>,
+    /// 是否使用部分克隆（`--filter=blob:none`），默认关闭
+    ///
+    /// 部分克隆的仓库之后读取其他提交的文件时会从 `origin` 补充下载，网络无法直连时可能失败。
+    pub partial_clone: Option<bool>,
+    /// 克隆、拉取失败后的重试次数
+    pub clone_retries: Option<u32>,
+    /// 直连失败时依次尝试的git镜像或代理
+    pub git_mirrors: Option<Vec<GitMirror>>,
+}
+
+/// 关闭 ToyStudio 时如何处理运行中的产品
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitPolicy {
+    /// 停止所有产品
+    #[default]
+    StopAll,
+    /// 询问用户
+    Ask,
+    /// 产品继续运行，下次启动时重新接管
+    LeaveRunning,
 }
 
 impl AppConfig {
@@ -21,6 +62,38 @@ impl AppConfig {
         Some(true) == self.dev_mode
     }
 
+    pub fn exit_policy(&self) -> ExitPolicy {
+        self.exit_policy.unwrap_or_default()
+    }
+
+    /// 同时执行的任务数，至少为 1
+    pub fn max_concurrent_jobs(&self) -> usize {
+        self.max_concurrent_jobs.unwrap_or(1).max(1)
+    }
+
+    /// 每个产品保留的备份数，至少为 1
+    pub fn max_backups(&self) -> usize {
+        self.max_backups.unwrap_or(DEFAULT_MAX_BACKUPS).max(1)
+    }
+
+    /// 克隆产品仓库的方式，`submodules` 来自产品配置
+    pub fn clone_options(&self, submodules: bool) -> CloneOptions {
+        CloneOptions {
+            depth: self.clone_depth.filter(|depth| *depth > 0),
+            partial: self.partial_clone.unwrap_or(false),
+            submodules,
+            retries: self.clone_retries.unwrap_or(DEFAULT_CLONE_RETRIES),
+            mirrors: self.git_mirrors(),
+            mirror: None,
+            max_backups: self.max_backups(),
+        }
+    }
+
+    /// git镜像或代理，未配置时使用默认镜像
+    pub fn git_mirrors(&self) -> Vec<GitMirror> {
+        self.git_mirrors.clone().unwrap_or_else(GitMirror::defaults)
+    }
+
     /// 默认配置，安装后初始化配置文件
     pub fn default(app_handle: &AppHandle) -> Self {
         let dir = app_handle.path().app_data_dir();
@@ -33,6 +106,13 @@ impl AppConfig {
             enable_external_uv: true,
             uv_cache_dir: cache_dir,
             dev_mode: Some(false),
+            exit_policy: Some(ExitPolicy::StopAll),
+            max_concurrent_jobs: Some(1),
+            max_backups: Some(DEFAULT_MAX_BACKUPS),
+            clone_depth: None,
+            partial_clone: Some(false),
+            clone_retries: Some(DEFAULT_CLONE_RETRIES),
+            git_mirrors: Some(GitMirror::defaults()),
         }
     }
 
@@ -65,6 +145,48 @@ impl AppConfig {
         dir
     }
 
+    /// 获取安装临时目录，安装先在这里完成，成功后再移动到安装目录
+    pub fn get_product_temp_path(&self) -> PathBuf {
+        let dir = PathBuf::from(&self.project_root_dir);
+        let dir = dir.join("./.local/temp");
+        dir
+    }
+
+    /// 获取产品的备份目录，每次卸载的备份以时间戳命名
+    pub fn get_product_backup_path(&self, product_name: &str) -> PathBuf {
+        self.get_product_bak_path().join(product_name)
+    }
+
+    /// 获取产品其他已安装版本的目录，当前使用的版本在安装目录中
+    pub fn get_product_versions_path(&self, product_name: &str) -> PathBuf {
+        let dir = PathBuf::from(&self.project_root_dir);
+        let dir = dir.join("./.local/versions");
+        let dir = dir.join(product_name);
+        dir
+    }
+
+    /// 获取产品日志目录
+    pub fn get_product_log_path(&self, product_name: &str) -> PathBuf {
+        let dir = PathBuf::from(&self.project_root_dir);
+        let dir = dir.join("./.local/logs");
+        let dir = dir.join(product_name);
+        dir
+    }
+
+    /// 获取运行中产品的PID文件路径
+    pub fn get_pid_file_path(&self) -> PathBuf {
+        let dir = PathBuf::from(&self.project_root_dir);
+        let dir = dir.join("./.local/run/products.json");
+        dir
+    }
+
+    /// 获取产品端口分配记录的文件路径
+    pub fn get_port_file_path(&self) -> PathBuf {
+        let dir = PathBuf::from(&self.project_root_dir);
+        let dir = dir.join("./.local/run/ports.json");
+        dir
+    }
+
     /// 获取输出目录
     pub fn get_output_path(&self) -> PathBuf {
         let dir = PathBuf::from(&self.project_root_dir);
@@ -82,33 +204,36 @@ impl AppConfig {
     }
 
     /// 获取应用配置
-    pub fn get_app_config(app_handle: &AppHandle) -> Result<AppConfig, String> {
+    pub fn get_app_config(app_handle: &AppHandle) -> ToyResult<AppConfig> {
         let config_path = Self::get_config_file_path(&app_handle);
         if !config_path.exists() {
             let app_config = AppConfig::default(&app_handle);
             let config_str =
-                serde_json::to_string_pretty(&app_config).map_err(|e| e.to_string())?;
-            fs::write(&config_path, &config_str).map_err(|e| e.to_string())?;
+                serde_json::to_string_pretty(&app_config).map_err(ToyStudioError::config)?;
+            fs::write(&config_path, &config_str)
+                .map_err(|e| ToyStudioError::io(&config_path, e))?;
             return Ok(app_config);
         }
         match fs::read_to_string(&config_path) {
             Ok(json) => {
                 let app_config = serde_json::from_str::<AppConfig>(&json);
-                app_config.map_err(|e| e.to_string())
+                app_config.map_err(ToyStudioError::config)
             }
-            Err(err) => return Err(err.to_string()),
+            Err(err) => return Err(ToyStudioError::io(&config_path, err)),
         }
     }
 
     /// 获取产品列表，补充安装状态和运行状态
-    pub fn get_meta_product_list(&self) -> Result<Vec<Product>, String> {
+    pub fn get_meta_product_list(&self, supervisor: &Supervisor) -> ToyResult<Vec<Product>> {
         println!("config:{:?}", self);
 
         let products_dir = self.get_meta_products_dir();
         println!("product dir:{:?}", products_dir);
-        let product_files = fs::read_dir(&products_dir).map_err(|e| e.to_string())?;
+        let product_files =
+            fs::read_dir(&products_dir).map_err(|e| ToyStudioError::io(&products_dir, e))?;
 
         let mut products: Vec<Product> = Vec::new();
+        let ports = read_port_file(&self.get_port_file_path());
 
         for product_file in product_files {
             match product_file {
@@ -123,27 +248,19 @@ impl AppConfig {
                             println!("product_file parse error:{}", err);
                         }
                         Ok(mut product) => {
-                            if let Ok(map) = APP_INSTALLED.lock() {
-                                if map.contains_key(&product.id) {
-                                    product.install = Some(true);
-                                } else {
-                                    product.install = Some(false);
-                                }
-                                product.running = Some(false);
-                                if let Some(child) = map.get(&product.id) {
-                                    if let Some(child) = child {
-                                        product.install = Some(true);
-                                        if let Ok(mut child) = child.lock() {
-                                            if let Ok(None) = child.try_wait() {
-                                                product.running = Some(true);
-                                            }
-                                        }
-                                    }
-                                }
-                            } else {
-                                product.install = Some(false);
-                                product.running = Some(false);
-                            }
+                            let status = supervisor.status(&product.id);
+                            product.install = Some(status.installed);
+                            product.running = Some(status.state.is_alive());
+                            product.state = Some(status.state);
+                            product.ui_url = status.ui_url;
+                            product.assigned_port =
+                                status.port.or_else(|| ports.get(&product.id).copied());
+                            product.crash_count = Some(status.crash_count);
+                            product.last_exit_code = status.exit_code;
+                            product.restart_at = status.restart_at;
+                            product.backup_at = latest_backup_time(&self.get_product_backup_path(
+                                &get_file_name_without_suffix(&product.id),
+                            ));
                             products.push(product);
                         }
                     }
@@ -156,6 +273,84 @@ impl AppConfig {
     }
 }
 
+/// 产品启动配置，由用户按产品定义，启动时选择
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct LaunchProfile {
+    /// 配置名称，同一产品内唯一
+    pub name: String,
+    /// 追加到启动命令后的参数，支持 `${output}` 和 `${port}`
+    pub args: String,
+    /// 环境变量，例如 `CUDA_VISIBLE_DEVICES`、`HF_HOME`
+    pub envs: BTreeMap<String, String>,
+    /// 工作目录，相对路径基于产品安装目录，为空时使用安装目录
+    pub working_dir: Option<String>,
+}
+
+impl LaunchProfile {
+    /// 获取启动配置文件路径，与应用配置文件在同一目录
+    pub fn get_profiles_file_path(app_handle: &AppHandle) -> PathBuf {
+        let config_path = AppConfig::get_config_file_path(app_handle);
+        config_path.with_file_name("launch_profiles.json")
+    }
+
+    /// 获取所有产品的启动配置：产品ID -> 启动配置列表
+    pub fn load_all(app_handle: &AppHandle) -> ToyResult<HashMap<String, Vec<LaunchProfile>>> {
+        let path = Self::get_profiles_file_path(app_handle);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let json = fs::read_to_string(&path).map_err(|e| ToyStudioError::io(&path, e))?;
+        serde_json::from_str(&json).map_err(ToyStudioError::config)
+    }
+
+    /// 获取产品的启动配置
+    pub fn load(app_handle: &AppHandle, product_id: &str) -> ToyResult<Vec<LaunchProfile>> {
+        let mut profiles = Self::load_all(app_handle)?;
+        Ok(profiles.remove(product_id).unwrap_or_default())
+    }
+
+    /// 按名称获取产品的启动配置
+    pub fn find(app_handle: &AppHandle, product_id: &str, name: &str) -> ToyResult<LaunchProfile> {
+        Self::load(app_handle, product_id)?
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| {
+                ToyStudioError::invalid_argument(format!("launch profile {} not found", name))
+            })
+    }
+
+    /// 保存产品的启动配置，名称不能为空或重复
+    pub fn save(
+        app_handle: &AppHandle,
+        product_id: &str,
+        profiles: Vec<LaunchProfile>,
+    ) -> ToyResult<()> {
+        for (index, profile) in profiles.iter().enumerate() {
+            if profile.name.trim().is_empty() {
+                return Err(ToyStudioError::invalid_argument(
+                    "launch profile name is empty",
+                ));
+            }
+            if profiles[..index].iter().any(|p| p.name == profile.name) {
+                return Err(ToyStudioError::invalid_argument(format!(
+                    "duplicate launch profile {}",
+                    profile.name
+                )));
+            }
+        }
+
+        let mut all = Self::load_all(app_handle)?;
+        if profiles.is_empty() {
+            all.remove(product_id);
+        } else {
+            all.insert(product_id.to_string(), profiles);
+        }
+        let path = Self::get_profiles_file_path(app_handle);
+        let json = serde_json::to_string_pretty(&all).map_err(ToyStudioError::config)?;
+        fs::write(&path, json).map_err(|e| ToyStudioError::io(&path, e))
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Product {
     /// 产品ID: 唯一标识, 使用产品配置`*.toml` 文件名作为ID。
@@ -184,6 +379,20 @@ pub struct Product {
     pub install: Option<bool>,
     /// 产品运行状态
     pub running: Option<bool>,
+    /// 产品进程状态
+    pub state: Option<ProcessState>,
+    /// 产品运行时的 Web 界面地址
+    pub ui_url: Option<String>,
+    /// 产品最近一次运行分配的端口
+    pub assigned_port: Option<u16>,
+    /// 用户定义的启动配置名称
+    pub launch_profiles: Option<Vec<String>>,
+    /// ToyStudio 本次运行期间产品异常退出的次数
+    pub crash_count: Option<u32>,
+    /// 最近一次退出码
+    pub last_exit_code: Option<i32>,
+    /// 等待自动重启时，计划重启的时间（毫秒时间戳）
+    pub restart_at: Option<u64>,
     /// 产品创建时间
     pub created_at: String,
     /// 产品更新时间
@@ -200,6 +409,19 @@ pub struct Product {
     pub macos: Macos,
     /// 产品Linux启动命令
     pub linux: Linux,
+    /// 产品就绪检查
+    pub health: Option<Health>,
+    /// 产品 Web 界面地址，例如 `http://127.0.0.1:${port}`；`${port}` 从产品输出中识别，
+    /// 未配置时使用产品输出中的第一个本机地址
+    pub web_url: Option<String>,
+    /// 产品默认端口，被占用时自动分配空闲端口；通过 `${port}` 和环境变量 `PORT` 传给产品
+    pub port: Option<u16>,
+    /// 额外接收端口的环境变量，例如 `GRADIO_SERVER_PORT`
+    pub port_env: Option<String>,
+    /// 卸载、重新安装时保留的用户数据路径（相对安装目录），例如 `models/`、`outputs/`、`config.yaml`
+    pub data_paths: Option<Vec<String>>,
+    /// 最近一次卸载备份的时间（毫秒时间戳），没有备份时为空
+    pub backup_at: Option<u64>,
     /// 产品发布者
     pub publisher: Option<String>,
     /// 产品文件大小
@@ -208,34 +430,123 @@ pub struct Product {
 
 impl Product {
     /// 解析产品配置文件
-    pub fn parse_product_toml(product_file: &PathBuf) -> Result<Product, String> {
+    pub fn parse_product_toml(product_file: &PathBuf) -> ToyResult<Product> {
         let pid = product_file
             .file_name()
-            .ok_or("Product ID is not found".to_string())?
+            .ok_or(ToyStudioError::invalid_argument("Product ID is not found"))?
             .to_string_lossy()
             .to_string();
-        let product_toml = fs::read_to_string(product_file).map_err(|e| e.to_string())?;
-        let mut product: Product = toml::from_str(&product_toml).map_err(|e| e.to_string())?;
+        if !product_file.exists() {
+            return Err(ToyStudioError::ProductNotFound { product_id: pid });
+        }
+        let product_toml =
+            fs::read_to_string(product_file).map_err(|e| ToyStudioError::io(product_file, e))?;
+        let mut product: Product =
+            toml::from_str(&product_toml).map_err(|e| ToyStudioError::ManifestInvalid {
+                path: product_file.to_string_lossy().to_string(),
+                message: e.to_string(),
+            })?;
         product.id = pid;
         Ok(product)
     }
 
-    /// 获取产品启动命令：根据操作系统获取对应的启动命令，并替换输出目录
-    pub fn get_startup_command(&self, output_dir: &PathBuf) -> Result<String, String> {
+    /// 获取产品启动命令：根据操作系统获取对应的启动命令，追加启动配置中的参数，并替换输出目录和端口
+    pub fn get_startup_command(
+        &self,
+        output_dir: &PathBuf,
+        port: u16,
+        profile: Option<&LaunchProfile>,
+    ) -> ToyResult<String> {
         let startup = match std::env::consts::OS {
             "windows" => &self.windows.startup.clone(),
             "macos" => &self.macos.startup.clone(),
             "linux" => &self.linux.startup.clone(),
-            _ => return Err("Unsupported OS".to_string()),
+            os => return Err(ToyStudioError::UnsupportedOs { os: os.to_string() }),
         };
 
-        let startup = template_replace_single(
-            &startup,
-            "output",
-            &output_dir.to_string_lossy().into_owned(),
+        let mut params = HashMap::new();
+        params.insert(
+            "output".to_string(),
+            output_dir.to_string_lossy().into_owned(),
         );
+        params.insert("port".to_string(), port.to_string());
 
-        Ok(startup)
+        let startup = match profile {
+            Some(profile) if !profile.args.trim().is_empty() => {
+                format!("{} {}", startup.trim_end(), profile.args.trim())
+            }
+            _ => startup.to_string(),
+        };
+        Ok(template_replace(&startup, &params))
+    }
+
+    /// 传给产品进程的环境变量：端口，以及启动配置中的环境变量（可覆盖端口变量）
+    pub fn get_launch_envs(
+        &self,
+        port: u16,
+        profile: Option<&LaunchProfile>,
+    ) -> HashMap<String, String> {
+        let mut envs = HashMap::new();
+        envs.insert("PORT".to_string(), port.to_string());
+        if let Some(port_env) = &self.port_env {
+            envs.insert(port_env.clone(), port.to_string());
+        }
+        if let Some(profile) = profile {
+            for (key, value) in &profile.envs {
+                envs.insert(
+                    key.clone(),
+                    template_replace_single(value, "port", &port.to_string()),
+                );
+            }
+        }
+        envs
+    }
+
+    /// 替换 `web_url` 和就绪检查地址中的 `${port}`
+    pub fn resolve_port(&mut self, port: u16) {
+        let port = port.to_string();
+        let replace = |value: &mut Option<String>| {
+            if let Some(value) = value {
+                *value = template_replace_single(value, "port", &port);
+            }
+        };
+        replace(&mut self.web_url);
+        if let Some(health) = &mut self.health {
+            replace(&mut health.http);
+            replace(&mut health.tcp);
+        }
+    }
+
+    /// 获取产品关闭命令：未配置时返回 `None`；支持 `${output}`、`${pid}`（产品进程ID）和 `${port}`
+    pub fn get_shutdown_command(
+        &self,
+        output_dir: &Path,
+        os_pid: Option<u32>,
+        port: Option<u16>,
+    ) -> ToyResult<Option<String>> {
+        let shutdown = match std::env::consts::OS {
+            "windows" => &self.windows.shutdown,
+            "macos" => &self.macos.shutdown,
+            "linux" => &self.linux.shutdown,
+            os => return Err(ToyStudioError::UnsupportedOs { os: os.to_string() }),
+        };
+        if shutdown.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let mut params = HashMap::new();
+        params.insert(
+            "output".to_string(),
+            output_dir.to_string_lossy().into_owned(),
+        );
+        if let Some(os_pid) = os_pid {
+            params.insert("pid".to_string(), os_pid.to_string());
+        }
+        if let Some(port) = port {
+            params.insert("port".to_string(), port.to_string());
+        }
+
+        Ok(Some(template_replace(shutdown, &params)))
     }
 }
 
@@ -263,10 +574,89 @@ pub struct Download {
     pub git_url: String,
     /// 产品git分支
     pub branch: String,
+    /// 固定的git标签，例如 `v1.2.0`
+    pub tag: Option<String>,
+    /// 固定的git提交（完整哈希），与 `tag` 同时配置时以 `commit` 为准
+    pub commit: Option<String>,
+    /// 仓库包含子模块时设为 `true`，安装时一并克隆
+    pub submodules: Option<bool>,
+    /// 是否下载 Git LFS 文件，未配置时根据仓库的 `.gitattributes` 判断
+    pub lfs: Option<LfsMode>,
     /// 产品python版本
     pub python_version: String,
 }
 
+impl Download {
+    pub fn submodules(&self) -> bool {
+        self.submodules.unwrap_or(false)
+    }
+
+    /// 产品配置中固定的版本：`commit` 优先，其次是 `tag`，都未配置时跟随分支
+    pub fn revision(&self) -> Option<&str> {
+        [&self.commit, &self.tag]
+            .into_iter()
+            .flatten()
+            .map(|revision| revision.trim())
+            .find(|revision| !revision.is_empty())
+    }
+}
+
+/// 产品配置中的 `lfs`：`true` 安装时下载 Git LFS 文件，`false` 不下载，`"lazy"` 首次启动时才下载
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "LfsValue", into = "LfsValue")]
+pub enum LfsMode {
+    Enabled,
+    Disabled,
+    Lazy,
+}
+
+/// `LfsMode` 在产品配置中的写法
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LfsValue {
+    Bool(bool),
+    Mode(String),
+}
+
+impl TryFrom<LfsValue> for LfsMode {
+    type Error = String;
+
+    fn try_from(value: LfsValue) -> Result<Self, Self::Error> {
+        match value {
+            LfsValue::Bool(true) => Ok(LfsMode::Enabled),
+            LfsValue::Bool(false) => Ok(LfsMode::Disabled),
+            LfsValue::Mode(mode) if mode == "lazy" => Ok(LfsMode::Lazy),
+            LfsValue::Mode(mode) => Err(format!(
+                "invalid lfs value \"{}\", expected true, false or \"lazy\"",
+                mode
+            )),
+        }
+    }
+}
+
+impl From<LfsMode> for LfsValue {
+    fn from(mode: LfsMode) -> Self {
+        match mode {
+            LfsMode::Enabled => LfsValue::Bool(true),
+            LfsMode::Disabled => LfsValue::Bool(false),
+            LfsMode::Lazy => LfsValue::Mode("lazy".to_string()),
+        }
+    }
+}
+
+/// 产品就绪检查：配置多项时全部通过才视为就绪
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Health {
+    /// HTTP地址，返回 2xx/3xx 视为就绪，例如 `http://127.0.0.1:7860`
+    pub http: Option<String>,
+    /// TCP地址或端口，能建立连接视为就绪，例如 `127.0.0.1:7860`
+    pub tcp: Option<String>,
+    /// 产品输出中出现匹配的行视为就绪，例如 `Running on local URL`
+    pub stdout_regex: Option<String>,
+    /// 超时时间（秒），默认120秒
+    pub timeout: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Windows {
     /// 产品Windows启动命令
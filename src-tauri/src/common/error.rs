@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// ToyStudio 统一错误类型
+///
+/// 序列化为 `{"code": "GIT_NOT_FOUND", ...}` 形式，`code` 为稳定的错误码，
+/// 前端 `common::invoke` 中有对应的反序列化定义，新增或修改变体时需同步。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "code", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ToyStudioError {
+    /// 未找到 git 可执行文件
+    #[error("git is not installed or not in PATH")]
+    GitNotFound,
+    /// git 命令执行失败
+    #[error("git {args} failed (exit code {exit_code:?}): {stderr}")]
+    GitCommandFailed {
+        args: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    /// 产品仓库使用 Git LFS，但未安装 git-lfs
+    #[error("git-lfs is not installed; it is required to download the model files")]
+    GitLfsNotFound,
+    /// 目标目录已存在 git 仓库，但远程地址不匹配
+    #[error("remote url mismatch at {path}: current {current_url}, expected {expected_url}")]
+    RemoteUrlMismatch {
+        path: String,
+        current_url: String,
+        expected_url: String,
+    },
+    /// 未找到 uv 可执行文件
+    #[error("uv is not installed or not in PATH")]
+    UvNotFound,
+    /// uv 命令执行失败
+    #[error("uv {args} failed (exit code {exit_code:?}): {stderr}")]
+    UvCommandFailed {
+        args: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    /// 产品不存在
+    #[error("product {product_id} not found")]
+    ProductNotFound { product_id: String },
+    /// 产品已在运行
+    #[error("product {product_id} is already running")]
+    ProductAlreadyRunning { product_id: String },
+    /// 产品正在安装、升级或重新安装
+    #[error("product {product_id} is busy with another operation")]
+    ProductBusy { product_id: String },
+    /// 产品没有可恢复的备份
+    #[error("no backup found for product {product_id}")]
+    BackupNotFound { product_id: String },
+    /// 产品没有指定的已安装版本
+    #[error("version {version} of product {product_id} not found")]
+    VersionNotFound { product_id: String, version: String },
+    /// 用户固定了产品的版本，不允许升级
+    #[error("product {product_id} is pinned to commit {commit}")]
+    ProductPinned { product_id: String, commit: String },
+    /// 产品的 Git LFS 文件尚未下载，已加入后台任务队列
+    #[error("model files of product {product_id} are being downloaded")]
+    LfsDownloadPending { product_id: String },
+    /// 产品配置文件无效
+    #[error("invalid product manifest {path}: {message}")]
+    ManifestInvalid { path: String, message: String },
+    /// 应用配置无效
+    #[error("invalid config: {message}")]
+    ConfigInvalid { message: String },
+    /// 操作被用户取消
+    #[error("operation cancelled")]
+    Cancelled,
+    /// 进程启动失败
+    #[error("failed to spawn {program}: {message}")]
+    ProcessSpawnFailed { program: String, message: String },
+    /// 文件读写失败
+    #[error("io error at {path}: {message}")]
+    Io { path: String, message: String },
+    /// 不支持的操作系统
+    #[error("unsupported os: {os}")]
+    UnsupportedOs { os: String },
+    /// 参数无效
+    #[error("invalid argument: {message}")]
+    InvalidArgument { message: String },
+    /// 内部错误
+    #[error("internal error: {message}")]
+    Internal { message: String },
+}
+
+pub type ToyResult<T> = Result<T, ToyStudioError>;
+
+impl ToyStudioError {
+    /// 文件读写错误，记录出错路径
+    pub fn io<P: AsRef<Path>>(path: P, err: std::io::Error) -> Self {
+        ToyStudioError::Io {
+            path: path.as_ref().to_string_lossy().to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    /// 进程启动错误
+    pub fn spawn(program: &str, err: std::io::Error) -> Self {
+        ToyStudioError::ProcessSpawnFailed {
+            program: program.to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn config<E: ToString>(err: E) -> Self {
+        ToyStudioError::ConfigInvalid {
+            message: err.to_string(),
+        }
+    }
+
+    pub fn internal<E: ToString>(err: E) -> Self {
+        ToyStudioError::Internal {
+            message: err.to_string(),
+        }
+    }
+
+    pub fn invalid_argument<E: ToString>(err: E) -> Self {
+        ToyStudioError::InvalidArgument {
+            message: err.to_string(),
+        }
+    }
+}
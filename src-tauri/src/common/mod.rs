@@ -1,7 +1,10 @@
 pub mod domains;
+pub mod error;
 pub mod os_utils;
 pub mod template;
+pub mod time_utils;
 pub use domains::*;
+pub use error::*;
 pub use os_utils::*;
 pub use template::*;
-
+pub use time_utils::*;
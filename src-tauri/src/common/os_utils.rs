@@ -1,9 +1,56 @@
 use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, BufRead, BufReader, Read},
     path::Path,
-    process::{Child, Command, Stdio},
-    sync::{Arc, Mutex},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
 };
 
+use crate::{LogStream, ToyResult, ToyStudioError};
+
+/// 命令执行失败时保留的 stderr 行数
+const STDERR_TAIL_LINES: usize = 20;
+/// 检查取消标志的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 耗时命令的输出回调，以及可选的取消标志
+#[derive(Clone, Copy)]
+pub struct OutputSink<'a> {
+    on_line: &'a (dyn Fn(LogStream, &str) + Sync),
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> OutputSink<'a> {
+    pub fn new(on_line: &'a (dyn Fn(LogStream, &str) + Sync)) -> Self {
+        Self {
+            on_line,
+            cancel: None,
+        }
+    }
+
+    /// 取消标志被设置后结束正在执行的命令
+    pub fn with_cancel(self, cancel: &'a AtomicBool) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    pub fn line(&self, stream: LogStream, line: &str) {
+        (self.on_line)(stream, line)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+    }
+}
+
 pub fn is_wsl() -> bool {
     match std::env::consts::OS {
         "linux" => {
@@ -34,22 +81,32 @@ pub fn run_command_common<P: AsRef<Path>>(
     current_dir: P,
     program: &str,
     args: &Vec<String>,
+    envs: &HashMap<String, String>,
     _name: &str,
     _pid: &str,
-) -> Result<Arc<Mutex<Child>>, String> {
+) -> ToyResult<Arc<Mutex<Child>>> {
     let current_dir = current_dir
         .as_ref()
         .canonicalize()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| ToyStudioError::io(&current_dir, e))?;
 
-    let child = Command::new(program)
+    let mut command = Command::new(program);
+    command
         .current_dir(current_dir)
         .args(args)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
+        .envs(envs)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // 产品作为新进程组的组长运行，停止时向整个进程组发送信号，避免遗留子进程
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let child = command
         .spawn()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| ToyStudioError::spawn(program, e))?;
 
     let sub_process_id = child.id();
     println!("sub_process_id:{}", sub_process_id);
@@ -59,6 +116,173 @@ pub fn run_command_common<P: AsRef<Path>>(
     Ok(child)
 }
 
+/// 执行命令直到退出，stdout/stderr 逐行交给 `output`，返回退出状态和 stderr 的最后若干行
+///
+/// `\r` 也视为换行，以便获取 git 等工具刷新同一行的进度输出。
+pub fn run_with_output(
+    command: &mut Command,
+    output: OutputSink,
+) -> io::Result<(ExitStatus, String)> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // 命令作为进程组组长运行，取消时结束整个进程组
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let mut child = command.spawn()?;
+    wait_with_output(&mut child, output)
+}
+
+/// 读取子进程输出直到退出，子进程需以 `Stdio::piped()` 创建，返回值同 `run_with_output`
+///
+/// 取消标志被设置时结束子进程的进程树。
+pub fn wait_with_output(child: &mut Child, output: OutputSink) -> io::Result<(ExitStatus, String)> {
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let os_pid = child.id();
+
+    let stderr_tail = Mutex::new(VecDeque::new());
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        if output.cancel.is_some() {
+            let done = &done;
+            scope.spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    if output.is_cancelled() {
+                        crate::kill_process_tree(os_pid);
+                        break;
+                    }
+                    thread::sleep(CANCEL_POLL_INTERVAL);
+                }
+            });
+        }
+        let stderr_reader = stderr.map(|stderr| {
+            let stderr_tail = &stderr_tail;
+            scope.spawn(move || {
+                for_each_line(stderr, |line| {
+                    output.line(LogStream::Stderr, line);
+                    if let Ok(mut tail) = stderr_tail.lock() {
+                        if tail.len() >= STDERR_TAIL_LINES {
+                            tail.pop_front();
+                        }
+                        tail.push_back(line.to_string());
+                    }
+                });
+            })
+        });
+        if let Some(stdout) = stdout {
+            for_each_line(stdout, |line| output.line(LogStream::Stdout, line));
+        }
+        if let Some(stderr_reader) = stderr_reader {
+            let _ = stderr_reader.join();
+        }
+        done.store(true, Ordering::SeqCst);
+    });
+
+    let status = child.wait()?;
+    let stderr = stderr_tail
+        .into_inner()
+        .map(|tail| Vec::from(tail).join("\n"))
+        .unwrap_or_default();
+    Ok((status, stderr))
+}
+
+/// 逐行读取输出直到管道关闭，`\r` 和 `\n` 都作为行结束，忽略空行
+fn for_each_line<R: Read>(reader: R, mut f: impl FnMut(&str)) {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    let mut flush = |line: &mut Vec<u8>| {
+        if !line.is_empty() {
+            f(String::from_utf8_lossy(line).trim_end());
+            line.clear();
+        }
+    };
+    loop {
+        let chunk = match reader.fill_buf() {
+            Ok([]) => break,
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                println!("read output error:{}", e);
+                break;
+            }
+        };
+        let (consumed, end_of_line) = match chunk.iter().position(|b| *b == b'\n' || *b == b'\r') {
+            Some(pos) => {
+                line.extend_from_slice(&chunk[..pos]);
+                (pos + 1, true)
+            }
+            None => {
+                line.extend_from_slice(chunk);
+                (chunk.len(), false)
+            }
+        };
+        reader.consume(consumed);
+        if end_of_line {
+            flush(&mut line);
+        }
+    }
+    flush(&mut line);
+}
+
+/// 向进程发送信号；进程是进程组组长时发送给整个进程组
+#[cfg(unix)]
+pub fn signal_process_tree(pid: u32, signal: i32) {
+    let pid = pid as libc::pid_t;
+    // SAFETY: 只是向指定进程（组）发送信号，不涉及内存操作
+    unsafe {
+        let target = if libc::getpgid(pid) == pid { -pid } else { pid };
+        if libc::kill(target, signal) != 0 {
+            println!(
+                "kill {} signal {} error:{}",
+                target,
+                signal,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// 强制结束进程组中剩余的进程，用于产品主进程退出后清理遗留的子进程
+///
+/// 进程组中还有进程时，内核不会把组长的进程ID分配给新进程；如果仍有进程使用该ID，
+/// 说明进程组已不存在且ID被复用（或组长尚未被回收），此时不发送信号，避免结束其他程序的进程组。
+#[cfg(unix)]
+pub fn kill_process_group(pgid: u32) {
+    let pgid = pgid as libc::pid_t;
+    // SAFETY: 只是查询进程和向指定进程组发送信号；进程（组）不存在时返回 ESRCH
+    unsafe {
+        if libc::getpgid(pgid) != -1 {
+            println!(
+                "process {} still exists, skip killing its process group",
+                pgid
+            );
+            return;
+        }
+        libc::kill(-pgid, libc::SIGKILL);
+    }
+}
+
+/// 获取进程启动时间（秒级时间戳），进程不存在或已成为僵尸进程时返回 `None`
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        .filter(|process| process.status() != sysinfo::ProcessStatus::Zombie)
+        .map(|process| process.start_time())
+}
+
 pub fn get_file_name_without_suffix(file_path: &str) -> String {
     let name = Path::new(file_path).file_stem();
     match name {
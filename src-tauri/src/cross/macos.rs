@@ -3,8 +3,31 @@ pub fn run_command<P: AsRef<std::path::Path>>(
     current_dir: P,
     program: &str,
     args: &Vec<String>,
+    envs: &std::collections::HashMap<String, String>,
     name: &str,
     product_id: &str,
-) -> Result<std::sync::Arc<std::sync::Mutex<std::process::Child>>, String> {
-    crate::run_command_common(current_dir, program, args, name, product_id)
+) -> crate::ToyResult<std::sync::Arc<std::sync::Mutex<std::process::Child>>> {
+    crate::run_command_common(current_dir, program, args, envs, name, product_id)
 }
+
+/// 请求进程树退出（SIGTERM）
+#[cfg(target_os = "macos")]
+pub fn terminate_process_tree(pid: u32) {
+    crate::signal_process_tree(pid, libc::SIGTERM)
+}
+
+/// 强制结束进程树（SIGKILL）
+#[cfg(target_os = "macos")]
+pub fn kill_process_tree(pid: u32) {
+    crate::signal_process_tree(pid, libc::SIGKILL)
+}
+
+/// 产品主进程退出后，结束进程组中遗留的子进程
+#[cfg(target_os = "macos")]
+pub fn release_process_tree(pid: u32) {
+    crate::kill_process_group(pid)
+}
+
+/// ToyStudio 退出后让产品继续运行；进程组不受父进程退出影响，无需处理
+#[cfg(target_os = "macos")]
+pub fn detach_process_tree(_pid: u32) {}
@@ -1,17 +1,25 @@
 #[cfg(target_os = "windows")]
 extern crate winapi;
 
+#[cfg(target_os = "windows")]
+lazy_static! {
+    /// 产品进程ID -> Job Object 句柄
+    static ref JOBS: std::sync::Mutex<std::collections::HashMap<u32, usize>> =
+        std::sync::Mutex::new(std::collections::HashMap::new());
+}
+
 #[cfg(target_os = "windows")]
 pub fn run_command<P: AsRef<std::path::Path>>(
     current_dir: P,
     program: &str,
     args: &Vec<String>,
+    envs: &std::collections::HashMap<String, String>,
     _name: &str,
     _pid: &str,
-) -> Result<std::sync::Arc<std::sync::Mutex<std::process::Child>>, String> {
+) -> crate::ToyResult<std::sync::Arc<std::sync::Mutex<std::process::Child>>> {
     match current_dir.as_ref().canonicalize() {
         Err(e) => {
-            return Err(e.to_string());
+            return Err(crate::ToyStudioError::io(&current_dir, e));
         }
         Ok(current_dir) => {
             let abs_dir = current_dir.to_string_lossy().to_string();
@@ -24,12 +32,6 @@ pub fn run_command<P: AsRef<std::path::Path>>(
             println!("abs_dir:{}", abs_dir);
             let args = args.join(" ");
             let cmd = format!("cd {abs_dir} && {program} {args}");
-            // 添加pause防止窗口自动关闭
-            let cmd = if !cmd.contains("pause") {
-                format!("{cmd} && pause")
-            } else {
-                cmd
-            };
             println!("cmd:{}", cmd);
 
             use std::os::windows::process::CommandExt;
@@ -41,13 +43,139 @@ pub fn run_command<P: AsRef<std::path::Path>>(
 
             let child = std::process::Command::new("cmd.exe")
                 .current_dir(std::path::Path::new(abs_dir))
-                .args(&["/V:ON", "/K", &echo_cmd])
-                .creation_flags(0x00000010) // 创建新控制台 CREATE_NEW_CONSOLE
-                .stdout(std::process::Stdio::inherit())
-                .stderr(std::process::Stdio::inherit())
+                .args(&["/V:ON", "/C", &echo_cmd])
+                .envs(envs)
+                .creation_flags(0x08000000) // 不创建控制台窗口 CREATE_NO_WINDOW，输出写入日志
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
                 .spawn()
-                .expect("无法启动cmd进程");
+                .map_err(|e| crate::ToyStudioError::spawn("cmd.exe", e))?;
+            assign_job(&child);
             Ok(std::sync::Arc::new(std::sync::Mutex::new(child)))
         }
     }
 }
+
+/// 请求进程树退出（taskkill /T）
+#[cfg(target_os = "windows")]
+pub fn terminate_process_tree(pid: u32) {
+    taskkill(pid, false)
+}
+
+/// 强制结束进程树：结束 Job Object 中的所有进程，再用 taskkill /T /F 兜底
+#[cfg(target_os = "windows")]
+pub fn kill_process_tree(pid: u32) {
+    if let Some(job) = take_job(pid) {
+        // SAFETY: 句柄由 `assign_job` 创建，从 JOBS 中取出后只在这里关闭一次
+        unsafe {
+            winapi::um::jobapi2::TerminateJobObject(job, 1);
+            winapi::um::handleapi::CloseHandle(job);
+        }
+    }
+    taskkill(pid, true)
+}
+
+/// 产品主进程退出后关闭其 Job Object，遗留的子进程随之结束
+#[cfg(target_os = "windows")]
+pub fn release_process_tree(pid: u32) {
+    if let Some(job) = take_job(pid) {
+        // SAFETY: 同 `kill_process_tree`
+        unsafe {
+            winapi::um::handleapi::CloseHandle(job);
+        }
+    }
+}
+
+/// ToyStudio 退出后让产品继续运行：取消 Job Object 的 KILL_ON_JOB_CLOSE 后关闭句柄
+#[cfg(target_os = "windows")]
+pub fn detach_process_tree(pid: u32) {
+    use winapi::um::{
+        handleapi::CloseHandle,
+        jobapi2::SetInformationJobObject,
+        winnt::{JobObjectExtendedLimitInformation, JOBOBJECT_EXTENDED_LIMIT_INFORMATION},
+    };
+
+    if let Some(job) = take_job(pid) {
+        // SAFETY: 同 `kill_process_tree`，`info` 为有效的局部变量
+        unsafe {
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &mut info as *mut _ as *mut winapi::ctypes::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            CloseHandle(job);
+        }
+    }
+}
+
+/// 创建设置了 KILL_ON_JOB_CLOSE 的 Job Object 并将子进程加入其中，
+/// 子进程之后创建的进程会自动加入同一个 Job；ToyStudio 退出时句柄关闭，所有进程随之结束
+#[cfg(target_os = "windows")]
+fn assign_job(child: &std::process::Child) {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::{
+        handleapi::CloseHandle,
+        jobapi2::{AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject},
+        winnt::{
+            JobObjectExtendedLimitInformation, HANDLE, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        },
+    };
+
+    // SAFETY: 所有指针参数均指向有效的局部变量，失败时关闭已创建的句柄
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null_mut(), std::ptr::null());
+        if job.is_null() {
+            println!("CreateJobObjectW error:{}", std::io::Error::last_os_error());
+            return;
+        }
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        let ok = SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &mut info as *mut _ as *mut winapi::ctypes::c_void,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) != 0
+            && AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) != 0;
+        if !ok {
+            println!("assign job error:{}", std::io::Error::last_os_error());
+            CloseHandle(job);
+            return;
+        }
+        if let Ok(mut jobs) = JOBS.lock() {
+            if let Some(old) = jobs.insert(child.id(), job as usize) {
+                CloseHandle(old as HANDLE);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn take_job(pid: u32) -> Option<winapi::um::winnt::HANDLE> {
+    JOBS.lock()
+        .ok()
+        .and_then(|mut jobs| jobs.remove(&pid))
+        .map(|job| job as winapi::um::winnt::HANDLE)
+}
+
+#[cfg(target_os = "windows")]
+fn taskkill(pid: u32, force: bool) {
+    use std::os::windows::process::CommandExt;
+
+    let pid = pid.to_string();
+    let mut args = vec!["/PID", pid.as_str(), "/T"];
+    if force {
+        args.push("/F");
+    }
+    let result = std::process::Command::new("taskkill")
+        .args(&args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    println!("taskkill {:?}: {:?}", args, result);
+}
@@ -2,18 +2,25 @@
 mod command;
 mod common;
 mod cross;
+mod service;
 pub use command::*;
 pub use common::*;
 pub use cross::*;
+pub use service::*;
 
 #[macro_use]
 extern crate lazy_static;
 
 use crate::init_installed_products;
+use tauri::Manager;
 use tauri_plugin_log::{Target, TargetKind};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // ToyStudio 退出后为继续运行的产品转发输出
+    if run_log_relay() {
+        return;
+    }
     tauri::Builder::default()
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_fs::init())
@@ -32,8 +39,14 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             let app_handle = app.handle();
+            app.manage(Supervisor::new(app_handle.clone()));
+            app.manage(MetricsSampler::new());
+            app.manage(JobQueue::new(app_handle.clone()));
             let _ = init_installed_products(&app_handle);
             let _ = init_meta_products(&app_handle);
+            recover_products(&app_handle);
+            Supervisor::start_reaper(app_handle.clone());
+            MetricsSampler::start(app_handle.clone());
 
             Ok(())
         })
@@ -42,6 +55,12 @@ pub fn run() {
             command::uv_get_python_envs,
             command::set_config,
             command::get_config,
+            command::get_launch_profiles,
+            command::set_launch_profiles,
+            command::get_restart_policy,
+            command::set_restart_policy,
+            command::git_mirror_stats,
+            command::git_probe_mirrors,
             command::get_meta_product_list,
             command::get_installed_product_list,
             command::select_directory,
@@ -51,11 +70,34 @@ pub fn run() {
             command::product_install,
             command::product_reinstall,
             command::product_uninstall,
+            command::product_restore,
+            command::product_list_backups,
+            command::product_restore_backup,
+            command::product_delete_backup,
+            command::product_check_update,
+            command::product_skip_update,
+            command::product_pin_version,
+            command::product_unpin_version,
+            command::product_list_versions,
+            command::product_install_version,
+            command::product_activate_version,
+            command::product_delete_version,
+            command::product_prune_versions,
             command::product_startup,
             command::product_shutdown,
+            command::product_open_ui,
             command::product_upgrade,
+            command::product_status,
+            command::product_metrics,
+            command::product_get_logs,
+            command::product_list_log_sessions,
+            command::product_export_logs,
+            command::job_list,
+            command::job_cancel,
+            command::job_clear_finished,
+            command::app_exit,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(handle_run_event);
 }
-
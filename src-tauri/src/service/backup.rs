@@ -0,0 +1,268 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{now_millis, ToyResult, ToyStudioError};
+
+/// 卸载方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UninstallMode {
+    /// 保留产品配置中声明的数据路径，其余文件移入备份目录
+    #[default]
+    KeepData,
+    /// 整个安装目录移入备份目录
+    RemoveAll,
+}
+
+/// 产品的一个备份，`product_list_backups` 命令的返回值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backup {
+    /// 备份ID，即备份目录名（创建时的毫秒时间戳）
+    pub id: String,
+    /// 创建时间（毫秒时间戳）
+    pub created_at: u64,
+    /// 占用空间（字节）
+    pub size_bytes: u64,
+}
+
+/// 将目录整体移动到 `backup_root/<时间戳>`，返回备份目录
+///
+/// 同一毫秒内的多次备份依次顺延时间戳，不会覆盖已有备份。
+pub fn backup_install_dir(install_dir: &Path, backup_root: &Path) -> ToyResult<PathBuf> {
+    fs::create_dir_all(backup_root).map_err(|e| ToyStudioError::io(backup_root, e))?;
+    let mut timestamp = now_millis();
+    while backup_root.join(timestamp.to_string()).exists() {
+        timestamp += 1;
+    }
+    let backup_dir = backup_root.join(timestamp.to_string());
+    println!("backup {:?} to {:?}", install_dir, backup_dir);
+    fs::rename(install_dir, &backup_dir).map_err(|e| ToyStudioError::io(install_dir, e))?;
+    Ok(backup_dir)
+}
+
+/// 备份目录按时间戳从新到旧排列，忽略名称不是时间戳的目录
+fn backup_dirs(backup_root: &Path) -> Vec<(u64, PathBuf)> {
+    let Ok(entries) = fs::read_dir(backup_root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<(u64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let timestamp = entry.file_name().to_string_lossy().parse::<u64>().ok()?;
+            Some((timestamp, entry.path()))
+        })
+        .collect();
+    dirs.sort_by_key(|(created_at, _)| Reverse(*created_at));
+    dirs
+}
+
+/// 产品的所有备份，从新到旧排列
+pub fn list_backups(backup_root: &Path) -> Vec<Backup> {
+    backup_dirs(backup_root)
+        .into_iter()
+        .map(|(created_at, path)| Backup {
+            id: created_at.to_string(),
+            created_at,
+            size_bytes: dir_size(&path),
+        })
+        .collect()
+}
+
+/// 指定ID的备份目录
+pub fn find_backup(backup_root: &Path, backup_id: &str) -> Option<PathBuf> {
+    backup_dirs(backup_root)
+        .into_iter()
+        .find(|(created_at, _)| created_at.to_string() == backup_id)
+        .map(|(_, path)| path)
+}
+
+/// 最近一次的备份目录
+pub fn latest_backup(backup_root: &Path) -> Option<PathBuf> {
+    backup_dirs(backup_root)
+        .into_iter()
+        .next()
+        .map(|(_, path)| path)
+}
+
+/// 最近一次备份的时间（毫秒时间戳）
+pub fn latest_backup_time(backup_root: &Path) -> Option<u64> {
+    backup_dirs(backup_root)
+        .first()
+        .map(|(created_at, _)| *created_at)
+}
+
+/// 删除超出保留数量的最早的备份
+pub fn prune_backups(backup_root: &Path, keep: usize) {
+    for (_, path) in backup_dirs(backup_root).into_iter().skip(keep) {
+        println!("remove expired backup:{:?}", path);
+        if let Err(e) = fs::remove_dir_all(&path) {
+            println!("remove backup {:?} error:{}", path, e);
+        }
+    }
+}
+
+/// 删除备份
+pub fn delete_backup(backup_dir: &Path) -> ToyResult<()> {
+    println!("delete backup:{:?}", backup_dir);
+    fs::remove_dir_all(backup_dir).map_err(|e| ToyStudioError::io(backup_dir, e))
+}
+
+/// 目录占用空间（字节），不跟随符号链接
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => {
+                entry.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+/// 数据路径必须是安装目录内的相对路径
+fn is_safe_data_path(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// 将 `from` 中存在的数据路径移动到 `to` 中，`to` 中已存在的路径先删除
+pub fn move_data_paths(from: &Path, to: &Path, data_paths: &[String]) -> ToyResult<()> {
+    for data_path in data_paths {
+        let relative = Path::new(data_path.trim_end_matches(['/', '\\']));
+        if relative.as_os_str().is_empty() || !is_safe_data_path(relative) {
+            println!("skip invalid data path:{}", data_path);
+            continue;
+        }
+        let source = from.join(relative);
+        if !source.exists() {
+            continue;
+        }
+        let target = to.join(relative);
+        if target.is_dir() {
+            fs::remove_dir_all(&target).map_err(|e| ToyStudioError::io(&target, e))?;
+        } else if target.exists() {
+            fs::remove_file(&target).map_err(|e| ToyStudioError::io(&target, e))?;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| ToyStudioError::io(parent, e))?;
+        }
+        println!("move data {:?} to {:?}", source, target);
+        fs::rename(&source, &target).map_err(|e| ToyStudioError::io(&source, e))?;
+    }
+    Ok(())
+}
+
+/// 从备份恢复安装目录
+///
+/// 安装目录不存在时直接移回；卸载时保留了数据则逐级合并目录，只跳过安装目录中已存在的文件，
+/// 保留的数据优先。任一文件移动失败时返回错误并保留备份。
+pub fn restore_backup(backup_dir: &Path, install_dir: &Path) -> ToyResult<()> {
+    println!("restore {:?} to {:?}", backup_dir, install_dir);
+    if !install_dir.exists() {
+        if let Some(parent) = install_dir.parent() {
+            fs::create_dir_all(parent).map_err(|e| ToyStudioError::io(parent, e))?;
+        }
+        return fs::rename(backup_dir, install_dir).map_err(|e| ToyStudioError::io(backup_dir, e));
+    }
+
+    merge_dir(backup_dir, install_dir)?;
+    fs::remove_dir_all(backup_dir).map_err(|e| ToyStudioError::io(backup_dir, e))
+}
+
+/// 将 `from` 中的文件移到 `to` 中：`to` 中没有的直接移动，两边都是目录时逐级合并，
+/// `to` 中已存在的文件保持不变
+fn merge_dir(from: &Path, to: &Path) -> ToyResult<()> {
+    let entries = fs::read_dir(from).map_err(|e| ToyStudioError::io(from, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| ToyStudioError::io(from, e))?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if fs::symlink_metadata(&target).is_err() {
+            fs::rename(&source, &target).map_err(|e| ToyStudioError::io(&source, e))?;
+        } else if is_dir && target.is_dir() && !target.is_symlink() {
+            merge_dir(&source, &target)?;
+        } else {
+            println!("keep {:?}, skip {:?}", target, source);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn restore_merges_backup_with_nested_kept_data() {
+        let root =
+            std::env::temp_dir().join(format!("toy-studio-backup-test-{}", std::process::id()));
+        let install_dir = root.join("apps").join("demo");
+        let backup_root = root.join("bak").join("demo");
+        write(&install_dir.join("main.py"), "app");
+        write(&install_dir.join("models/weights/model.bin"), "old weights");
+        write(&install_dir.join("models/config/model.json"), "config");
+        write(&install_dir.join("models/README.md"), "readme");
+
+        // 卸载时保留 `models/weights`
+        let backup_dir = backup_install_dir(&install_dir, &backup_root).unwrap();
+        fs::create_dir_all(&install_dir).unwrap();
+        move_data_paths(&backup_dir, &install_dir, &["models/weights/".to_string()]).unwrap();
+        write(&install_dir.join("models/weights/model.bin"), "new weights");
+
+        restore_backup(&backup_dir, &install_dir).unwrap();
+
+        assert_eq!(read(&install_dir.join("main.py")), "app");
+        assert_eq!(
+            read(&install_dir.join("models/weights/model.bin")),
+            "new weights"
+        );
+        assert_eq!(
+            read(&install_dir.join("models/config/model.json")),
+            "config"
+        );
+        assert_eq!(read(&install_dir.join("models/README.md")), "readme");
+        assert!(!backup_dir.exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn restore_moves_backup_back_when_install_dir_is_missing() {
+        let root =
+            std::env::temp_dir().join(format!("toy-studio-restore-test-{}", std::process::id()));
+        let install_dir = root.join("apps").join("demo");
+        let backup_dir = root.join("bak").join("demo").join("1");
+        write(&backup_dir.join("models/weights/model.bin"), "weights");
+
+        restore_backup(&backup_dir, &install_dir).unwrap();
+
+        assert_eq!(
+            read(&install_dir.join("models/weights/model.bin")),
+            "weights"
+        );
+        assert!(!backup_dir.exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
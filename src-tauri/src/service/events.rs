@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::{ProcessState, ToyStudioError};
+
+/// 产品状态变化（进程状态或安装状态），负载为 `ProductStatus`
+pub const PRODUCT_STATE_CHANGED: &str = "product://state-changed";
+/// 产品进程退出，负载为 `ProductExitedEvent`
+pub const PRODUCT_EXITED: &str = "product://exited";
+/// 产品安装进度，负载为 `InstallProgressEvent`
+pub const PRODUCT_INSTALL_PROGRESS: &str = "product://install-progress";
+/// 产品安装结束，负载为 `InstallFinishedEvent`
+pub const PRODUCT_INSTALL_FINISHED: &str = "product://install-finished";
+/// 产品输出的一行日志，负载为 `LogLine`
+pub const PRODUCT_LOG_LINE: &str = "product://log-line";
+/// 产品资源占用采样，负载为 `ProductMetrics`
+pub const PRODUCT_METRICS: &str = "product://metrics";
+/// 后台任务状态变化，负载为 `Job`
+pub const JOB_UPDATED: &str = "job://updated";
+/// 关闭窗口时有产品在运行且退出策略为询问，负载为运行中的产品ID列表
+pub const APP_EXIT_REQUESTED: &str = "app://exit-requested";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductExitedEvent {
+    pub product_id: String,
+    pub state: ProcessState,
+    pub exit_code: Option<i32>,
+}
+
+/// 安装步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStage {
+    Clone,
+    /// 下载 Git LFS 文件
+    Lfs,
+    CreateVenv,
+    Sync,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStageStatus {
+    Started,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallProgressEvent {
+    pub product_id: String,
+    pub stage: InstallStage,
+    pub status: InstallStageStatus,
+    /// 步骤失败时的错误信息
+    pub message: Option<String>,
+    /// 步骤结束时的耗时（毫秒）
+    pub duration_ms: Option<u64>,
+    /// 步骤输出所在的日志会话
+    pub log_session: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallFinishedEvent {
+    pub product_id: String,
+    pub log_session: String,
+    /// 安装失败时的错误，成功时为空
+    pub error: Option<ToyStudioError>,
+}
+
+/// 向前端发送事件，发送失败只记录日志
+pub fn emit_event<S: Serialize + Clone>(app_handle: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app_handle.emit(event, payload) {
+        println!("emit {} error:{}", event, e);
+    }
+}
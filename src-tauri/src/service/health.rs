@@ -0,0 +1,172 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+use tauri::{AppHandle, Manager};
+use url::Url;
+
+use crate::{
+    stop_product, Health, LineWatcher, ProcessState, Supervisor, ToyResult, ToyStudioError,
+};
+
+/// 默认就绪检查超时时间
+const DEFAULT_HEALTH_TIMEOUT_SECS: u64 = 120;
+/// 两次检查之间的间隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// 单次 HTTP/TCP 探测的超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 产品就绪检查，所有配置的检查项都通过才视为就绪
+pub struct HealthCheck {
+    http: Option<String>,
+    tcp: Option<String>,
+    stdout_regex: Option<Regex>,
+    /// 产品输出中是否已出现匹配 `stdout_regex` 的行
+    matched: Arc<AtomicBool>,
+    timeout: Duration,
+}
+
+impl HealthCheck {
+    /// 根据产品配置创建就绪检查，未配置任何检查项时返回 `None`
+    pub fn new(product_id: &str, health: &Health) -> ToyResult<Option<Self>> {
+        let stdout_regex = match &health.stdout_regex {
+            Some(pattern) => {
+                Some(
+                    Regex::new(pattern).map_err(|e| ToyStudioError::ManifestInvalid {
+                        path: product_id.to_string(),
+                        message: format!("health.stdout_regex: {}", e),
+                    })?,
+                )
+            }
+            None => None,
+        };
+        if health.http.is_none() && health.tcp.is_none() && stdout_regex.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            http: health.http.clone(),
+            tcp: health.tcp.clone(),
+            stdout_regex,
+            matched: Arc::new(AtomicBool::new(false)),
+            timeout: Duration::from_secs(health.timeout.unwrap_or(DEFAULT_HEALTH_TIMEOUT_SECS)),
+        }))
+    }
+
+    /// 配置了 `stdout_regex` 时返回需要接入输出捕获的行监听器，stdout 和 stderr 都会匹配
+    pub fn line_watcher(&self) -> Option<LineWatcher> {
+        let regex = self.stdout_regex.clone()?;
+        let matched = self.matched.clone();
+        Some(Arc::new(move |line| {
+            if !matched.load(Ordering::Relaxed) && regex.is_match(&line.line) {
+                matched.store(true, Ordering::Relaxed);
+            }
+        }))
+    }
+
+    fn is_ready(&self) -> bool {
+        (self.stdout_regex.is_none() || self.matched.load(Ordering::Relaxed))
+            && self.tcp.as_deref().is_none_or(probe_tcp)
+            && self.http.as_deref().is_none_or(probe_http)
+    }
+
+    /// 在后台线程中轮询，直到产品就绪、超时或不再处于 `Starting`
+    ///
+    /// 就绪时状态变为 `Ready`；超时时停止产品，状态变为 `Failed`。
+    pub fn spawn(self, app_handle: AppHandle, product_id: String) {
+        thread::spawn(move || {
+            let deadline = Instant::now() + self.timeout;
+            loop {
+                let supervisor = app_handle.state::<Supervisor>();
+                // 进程已退出或被用户停止
+                if supervisor.status(&product_id).state != ProcessState::Starting {
+                    return;
+                }
+                if self.is_ready() {
+                    println!("product ready:{}", product_id);
+                    supervisor.mark_ready(&product_id);
+                    return;
+                }
+                if Instant::now() >= deadline {
+                    println!("product health check timeout:{}", product_id);
+                    supervisor.fail_health(&product_id);
+                    if let Err(e) = stop_product(&app_handle, &supervisor, &product_id) {
+                        println!("stop product {} error:{}", product_id, e);
+                    }
+                    return;
+                }
+                thread::sleep(HEALTH_CHECK_INTERVAL);
+            }
+        });
+    }
+}
+
+/// 解析地址，只有端口时使用本机地址
+fn resolve(addr: &str) -> Vec<SocketAddr> {
+    let addr = match addr.parse::<u16>() {
+        Ok(port) => format!("127.0.0.1:{}", port),
+        Err(_) => addr.to_string(),
+    };
+    addr.to_socket_addrs()
+        .map(|addrs| addrs.collect())
+        .unwrap_or_default()
+}
+
+/// 能建立 TCP 连接视为就绪
+fn probe_tcp(addr: &str) -> bool {
+    resolve(addr)
+        .iter()
+        .any(|addr| TcpStream::connect_timeout(addr, PROBE_TIMEOUT).is_ok())
+}
+
+/// 发送 HTTP GET，返回 2xx/3xx 视为就绪；只支持 `http://`
+fn probe_http(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    if url.scheme() != "http" {
+        return false;
+    }
+    let port = url.port_or_known_default().unwrap_or(80);
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    resolve(&format!("{}:{}", host, port)).iter().any(|addr| {
+        let Ok(mut stream) = TcpStream::connect_timeout(addr, PROBE_TIMEOUT) else {
+            return false;
+        };
+        let _ = stream.set_read_timeout(Some(PROBE_TIMEOUT));
+        let _ = stream.set_write_timeout(Some(PROBE_TIMEOUT));
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\n\r\n",
+            path, host, port
+        );
+        if stream.write_all(request.as_bytes()).is_err() {
+            return false;
+        }
+
+        // 只看状态行，例如 `HTTP/1.1 200 OK`
+        let mut status_line = String::new();
+        if BufReader::new(stream).read_line(&mut status_line).is_err() {
+            return false;
+        }
+        status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .is_some_and(|code| (200..400).contains(&code))
+    })
+}
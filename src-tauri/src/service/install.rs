@@ -0,0 +1,282 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use tauri::AppHandle;
+
+use crate::{
+    emit_event, move_data_paths, new_log_session, now_millis, with_mirrors, CloneOptions,
+    InstallFinishedEvent, InstallProgressEvent, InstallStage, InstallStageStatus, LogStream,
+    LogWriter, OutputSink, ToyResult, ToyStudioError, PRODUCT_INSTALL_FINISHED,
+    PRODUCT_INSTALL_PROGRESS,
+};
+
+/// 一次安装（或重新安装、升级）的执行过程，由任务队列创建
+///
+/// 按步骤执行，每个步骤开始和结束时发送 `product://install-progress` 事件，
+/// 步骤中命令的输出写入 `<时间>-install` 日志会话，并以 `product://log-line` 事件实时发送。
+/// 取消标志被设置后，正在执行的命令会被结束，之后的步骤不再执行。
+pub struct InstallPipeline {
+    app_handle: AppHandle,
+    product_id: String,
+    log: Arc<LogWriter>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl InstallPipeline {
+    pub fn new(
+        app_handle: &AppHandle,
+        product_id: &str,
+        log_dir: &Path,
+        cancel: Arc<AtomicBool>,
+    ) -> ToyResult<Self> {
+        let session = format!("{}-install", new_log_session());
+        let log = LogWriter::open(app_handle, product_id, log_dir, &session, Vec::new())?;
+        Ok(Self {
+            app_handle: app_handle.clone(),
+            product_id: product_id.to_string(),
+            log,
+            cancel,
+        })
+    }
+
+    pub fn log_session(&self) -> &str {
+        self.log.session()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    /// 写入一行安装日志
+    pub fn output(&self, stream: LogStream, line: &str) {
+        self.log.write(stream, line.to_string());
+    }
+
+    /// 以写入安装日志、响应取消的 `OutputSink` 执行 `f`
+    pub fn with_output<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(OutputSink) -> T,
+    {
+        let on_line = |stream, line: &str| self.output(stream, line);
+        f(OutputSink::new(&on_line).with_cancel(&self.cancel))
+    }
+
+    /// 执行一个安装步骤，`f` 中命令的输出应交给传入的 `OutputSink`
+    pub fn run_stage<T, F>(&self, stage: InstallStage, f: F) -> ToyResult<T>
+    where
+        F: FnOnce(OutputSink) -> ToyResult<T>,
+    {
+        if self.is_cancelled() {
+            return Err(ToyStudioError::Cancelled);
+        }
+        let emit = |status, message, duration_ms| {
+            emit_event(
+                &self.app_handle,
+                PRODUCT_INSTALL_PROGRESS,
+                InstallProgressEvent {
+                    product_id: self.product_id.clone(),
+                    stage,
+                    status,
+                    message,
+                    duration_ms,
+                    log_session: self.log_session().to_string(),
+                },
+            )
+        };
+
+        emit(InstallStageStatus::Started, None, None);
+        let started = Instant::now();
+        let result = match self.with_output(f) {
+            // 被取消的命令以失败退出，统一为取消
+            Err(_) if self.is_cancelled() => Err(ToyStudioError::Cancelled),
+            result => result,
+        };
+        let duration_ms = Some(started.elapsed().as_millis() as u64);
+        match &result {
+            Ok(_) => emit(InstallStageStatus::Finished, None, duration_ms),
+            Err(e) => {
+                self.output(LogStream::Stderr, &e.to_string());
+                emit(InstallStageStatus::Failed, Some(e.to_string()), duration_ms);
+            }
+        }
+        result
+    }
+
+    /// 执行访问远程仓库的安装步骤，直连失败时依次通过 `options.mirrors` 中的镜像执行 `f`
+    pub fn run_remote_stage<F>(
+        &self,
+        stage: InstallStage,
+        options: &CloneOptions,
+        mut f: F,
+    ) -> ToyResult<()>
+    where
+        F: FnMut(&CloneOptions, OutputSink) -> ToyResult<()>,
+    {
+        self.run_stage(stage, |output| {
+            with_mirrors(&self.app_handle, options, output, |options| {
+                f(options, output)
+            })
+        })
+    }
+
+    /// 全部步骤成功后用临时目录替换安装目录，已取消时放弃安装
+    pub fn commit(&self, staged: StagedInstall) -> ToyResult<()> {
+        if self.is_cancelled() {
+            return Err(ToyStudioError::Cancelled);
+        }
+        self.output(
+            LogStream::Stdout,
+            &format!(
+                "move {} to {}",
+                staged.dir().display(),
+                staged.install_dir.display()
+            ),
+        );
+        staged.commit()
+    }
+
+    /// 执行失败后的回滚操作，输出写入安装日志，不响应取消
+    pub fn rollback<F>(&self, f: F)
+    where
+        F: FnOnce(OutputSink) -> ToyResult<()>,
+    {
+        self.output(LogStream::Stderr, "rolling back");
+        let on_line = |stream, line: &str| self.output(stream, line);
+        if let Err(e) = f(OutputSink::new(&on_line)) {
+            self.output(LogStream::Stderr, &format!("rollback failed: {}", e));
+        }
+    }
+
+    /// 发送安装结束事件
+    pub fn finish(&self, result: &ToyResult<()>) {
+        emit_event(
+            &self.app_handle,
+            PRODUCT_INSTALL_FINISHED,
+            InstallFinishedEvent {
+                product_id: self.product_id.clone(),
+                log_session: self.log_session().to_string(),
+                error: result.as_ref().err().cloned(),
+            },
+        );
+    }
+}
+
+/// 在 `.local/temp` 下的临时目录中完成的安装
+///
+/// 全部步骤成功后通过 `InstallPipeline::commit` 替换安装目录，原安装目录中的数据路径保留到新的安装中；
+/// 未提交就被丢弃（步骤失败或被取消）时删除临时目录，安装目录保持原样。
+pub struct StagedInstall {
+    staging_dir: PathBuf,
+    install_dir: PathBuf,
+    data_paths: Vec<String>,
+    committed: bool,
+}
+
+impl StagedInstall {
+    /// 在 `temp_dir` 下为 `install_dir` 分配临时目录，临时目录本身不创建，由 git clone 创建
+    pub fn new(temp_dir: &Path, install_dir: &Path, data_paths: Vec<String>) -> ToyResult<Self> {
+        fs::create_dir_all(temp_dir).map_err(|e| ToyStudioError::io(temp_dir, e))?;
+        let name = install_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let staging_dir = temp_dir.join(format!("{}-{}", name, now_millis()));
+        println!("staging_dir:{:?}", staging_dir);
+        Ok(Self {
+            staging_dir,
+            install_dir: install_dir.to_path_buf(),
+            data_paths,
+            committed: false,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.staging_dir
+    }
+
+    /// 修改提交的目标目录，用于克隆完成后才能确定目录的并行安装版本
+    pub fn set_install_dir(&mut self, install_dir: &Path) {
+        self.install_dir = install_dir.to_path_buf();
+    }
+
+    /// 原安装目录先移到临时目录下，再将临时目录移动到安装目录；移动失败时恢复原安装目录
+    fn commit(mut self) -> ToyResult<()> {
+        if let Some(parent) = self.install_dir.parent() {
+            fs::create_dir_all(parent).map_err(|e| ToyStudioError::io(parent, e))?;
+        }
+        let previous = if self.install_dir.exists() {
+            if let Err(e) = move_data_paths(&self.install_dir, &self.staging_dir, &self.data_paths)
+            {
+                self.restore_data();
+                return Err(e);
+            }
+            let previous = self.staging_dir.with_extension("previous");
+            if let Err(e) = fs::rename(&self.install_dir, &previous) {
+                self.restore_data();
+                return Err(ToyStudioError::io(&self.install_dir, e));
+            }
+            Some(previous)
+        } else {
+            None
+        };
+        if let Err(e) = fs::rename(&self.staging_dir, &self.install_dir) {
+            if let Some(previous) = &previous {
+                let _ = fs::rename(previous, &self.install_dir);
+                self.restore_data();
+            }
+            return Err(ToyStudioError::io(&self.install_dir, e));
+        }
+        self.committed = true;
+        if let Some(previous) = previous {
+            if let Err(e) = fs::remove_dir_all(&previous) {
+                println!("remove previous install {:?} error:{}", previous, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 提交失败时将已移入临时目录的数据移回原安装目录
+    fn restore_data(&self) {
+        if let Err(e) = move_data_paths(&self.staging_dir, &self.install_dir, &self.data_paths) {
+            println!("restore data to {:?} error:{}", self.install_dir, e);
+        }
+    }
+}
+
+impl Drop for StagedInstall {
+    fn drop(&mut self) {
+        if self.committed || !self.staging_dir.exists() {
+            return;
+        }
+        println!("rollback staged install:{:?}", self.staging_dir);
+        if let Err(e) = fs::remove_dir_all(&self.staging_dir) {
+            println!("remove staging dir {:?} error:{}", self.staging_dir, e);
+        }
+    }
+}
+
+/// 删除上次异常退出时遗留的临时安装目录
+pub fn clean_staging_dir(temp_dir: &Path) {
+    let Ok(entries) = fs::read_dir(temp_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        println!("remove leftover staging dir:{:?}", path);
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        if let Err(e) = result {
+            println!("remove {:?} error:{}", path, e);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    emit_event, get_file_name_without_suffix, now_millis, AppConfig, InstallPipeline, ToyResult,
+    ToyStudioError, JOB_UPDATED,
+};
+
+/// 保留的已结束任务数
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// 后台任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Install,
+    Reinstall,
+    Upgrade,
+    /// 与当前版本并行安装产品配置中的最新版本
+    InstallVersion,
+    /// 下载 `lfs = "lazy"` 的产品的 Git LFS 文件
+    PullLfs,
+}
+
+/// 后台任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// 等待执行
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Succeeded | JobState::Failed | JobState::Cancelled
+        )
+    }
+}
+
+/// 后台任务，`job_list` 命令和 `job://updated` 事件的负载
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub product_id: String,
+    pub kind: JobKind,
+    pub state: JobState,
+    /// 加入队列的时间（毫秒时间戳）
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    /// 任务输出所在的日志会话
+    pub log_session: Option<String>,
+    /// 任务失败时的错误
+    pub error: Option<ToyStudioError>,
+}
+
+struct QueuedJob {
+    job: Job,
+    cancel: Arc<AtomicBool>,
+}
+
+/// 安装、重新安装、升级的后台任务队列，由 Tauri managed state 持有
+///
+/// 任务按加入顺序执行，同时执行的任务数由 `AppConfig::max_concurrent_jobs` 决定；
+/// 同一产品同时只能有一个未结束的任务。任务的每次状态变化都会发送 `job://updated` 事件。
+pub struct JobQueue {
+    app_handle: AppHandle,
+    next_id: AtomicU64,
+    jobs: Mutex<Vec<QueuedJob>>,
+}
+
+impl JobQueue {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            next_id: AtomicU64::new(1),
+            jobs: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn publish(&self, job: Job) {
+        emit_event(&self.app_handle, JOB_UPDATED, job);
+    }
+
+    /// 加入任务，产品已有未结束的任务时返回错误
+    pub fn enqueue(&self, kind: JobKind, product_id: &str) -> ToyResult<Job> {
+        let job = {
+            let mut jobs = self.jobs.lock().map_err(ToyStudioError::internal)?;
+            if jobs.iter().any(|queued| {
+                queued.job.product_id == product_id && !queued.job.state.is_finished()
+            }) {
+                return Err(ToyStudioError::ProductBusy {
+                    product_id: product_id.to_string(),
+                });
+            }
+            let job = Job {
+                id: self.next_id.fetch_add(1, Ordering::SeqCst),
+                product_id: product_id.to_string(),
+                kind,
+                state: JobState::Queued,
+                created_at: now_millis(),
+                started_at: None,
+                finished_at: None,
+                log_session: None,
+                error: None,
+            };
+            jobs.push(QueuedJob {
+                job: job.clone(),
+                cancel: Arc::new(AtomicBool::new(false)),
+            });
+            prune_finished(&mut jobs);
+            job
+        };
+        println!("job {} queued: {:?} {}", job.id, job.kind, job.product_id);
+        self.publish(job.clone());
+        self.schedule();
+        Ok(job)
+    }
+
+    /// 所有任务，按加入顺序排列
+    pub fn list(&self) -> Vec<Job> {
+        self.jobs
+            .lock()
+            .map(|jobs| jobs.iter().map(|queued| queued.job.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 产品是否有未结束的任务
+    pub fn is_busy(&self, product_id: &str) -> bool {
+        self.jobs
+            .lock()
+            .map(|jobs| {
+                jobs.iter().any(|queued| {
+                    queued.job.product_id == product_id && !queued.job.state.is_finished()
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    /// 产品没有未结束的任务时执行 `f`，用于卸载等不经过队列、很快完成的同步操作；
+    /// 执行期间持有队列的锁，新任务要等 `f` 结束后才能加入
+    pub fn run_exclusive<T, F>(&self, product_id: &str, f: F) -> ToyResult<T>
+    where
+        F: FnOnce() -> ToyResult<T>,
+    {
+        let jobs = self.jobs.lock().map_err(ToyStudioError::internal)?;
+        if jobs
+            .iter()
+            .any(|queued| queued.job.product_id == product_id && !queued.job.state.is_finished())
+        {
+            return Err(ToyStudioError::ProductBusy {
+                product_id: product_id.to_string(),
+            });
+        }
+        f()
+    }
+
+    /// 取消任务：等待中的任务直接取消，执行中的任务结束当前命令后取消
+    pub fn cancel(&self, job_id: u64) -> ToyResult<()> {
+        let cancelled = {
+            let mut jobs = self.jobs.lock().map_err(ToyStudioError::internal)?;
+            let queued = jobs
+                .iter_mut()
+                .find(|queued| queued.job.id == job_id)
+                .ok_or_else(|| {
+                    ToyStudioError::invalid_argument(format!("job {} not found", job_id))
+                })?;
+            queued.cancel.store(true, Ordering::SeqCst);
+            if queued.job.state == JobState::Queued {
+                queued.job.state = JobState::Cancelled;
+                queued.job.finished_at = Some(now_millis());
+                Some(queued.job.clone())
+            } else {
+                None
+            }
+        };
+        if let Some(job) = cancelled {
+            self.publish(job);
+        }
+        Ok(())
+    }
+
+    /// 清除已结束的任务
+    pub fn clear_finished(&self) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.retain(|queued| !queued.job.state.is_finished());
+        }
+    }
+
+    /// 在并发数允许的范围内开始等待中的任务
+    fn schedule(&self) {
+        let concurrency = AppConfig::get_app_config(&self.app_handle)
+            .map(|config| config.max_concurrent_jobs())
+            .unwrap_or(1);
+        let mut started = Vec::new();
+        {
+            let Ok(mut jobs) = self.jobs.lock() else {
+                return;
+            };
+            let mut running = jobs
+                .iter()
+                .filter(|queued| queued.job.state == JobState::Running)
+                .count();
+            for queued in jobs.iter_mut() {
+                if running >= concurrency {
+                    break;
+                }
+                if queued.job.state == JobState::Queued {
+                    queued.job.state = JobState::Running;
+                    queued.job.started_at = Some(now_millis());
+                    running += 1;
+                    started.push((queued.job.clone(), queued.cancel.clone()));
+                }
+            }
+        }
+        for (job, cancel) in started {
+            self.publish(job.clone());
+            let app_handle = self.app_handle.clone();
+            thread::spawn(move || app_handle.state::<JobQueue>().run(job, cancel));
+        }
+    }
+
+    /// 在后台线程中执行任务，结束后开始下一个任务；任务 panic 时记为失败，不会一直占用并发数
+    fn run(&self, job: Job, cancel: Arc<AtomicBool>) {
+        println!("job {} started: {:?} {}", job.id, job.kind, job.product_id);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| self.execute(&job, cancel)))
+            .unwrap_or_else(|payload| Err(ToyStudioError::internal(panic_message(&payload))));
+        let state = match &result {
+            Ok(()) => JobState::Succeeded,
+            Err(ToyStudioError::Cancelled) => JobState::Cancelled,
+            Err(_) => JobState::Failed,
+        };
+        println!("job {} finished: {:?}", job.id, state);
+        self.update(job.id, |job| {
+            job.state = state;
+            job.finished_at = Some(now_millis());
+            job.error = result.err();
+        });
+        self.schedule();
+    }
+
+    fn execute(&self, job: &Job, cancel: Arc<AtomicBool>) -> ToyResult<()> {
+        let app_config = AppConfig::get_app_config(&self.app_handle)?;
+        let log_dir =
+            app_config.get_product_log_path(&get_file_name_without_suffix(&job.product_id));
+        let pipeline = InstallPipeline::new(&self.app_handle, &job.product_id, &log_dir, cancel)?;
+        let log_session = pipeline.log_session().to_string();
+        self.update(job.id, |job| job.log_session = Some(log_session));
+
+        let result = crate::run_product_job(&self.app_handle, &pipeline, job.kind, &job.product_id);
+        pipeline.finish(&result);
+        result
+    }
+
+    fn update<F: FnOnce(&mut Job)>(&self, job_id: u64, f: F) {
+        let job = self.jobs.lock().ok().and_then(|mut jobs| {
+            let queued = jobs.iter_mut().find(|queued| queued.job.id == job_id)?;
+            f(&mut queued.job);
+            Some(queued.job.clone())
+        });
+        if let Some(job) = job {
+            self.publish(job);
+        }
+    }
+}
+
+/// panic 的消息，`panic!` 的参数不是字符串时返回通用描述
+fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "job panicked".to_string())
+}
+
+/// 删除超出保留数量的最早的已结束任务
+fn prune_finished(jobs: &mut Vec<QueuedJob>) {
+    let finished = jobs
+        .iter()
+        .filter(|queued| queued.job.state.is_finished())
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+    jobs.retain(|queued| {
+        if excess > 0 && queued.job.state.is_finished() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
@@ -0,0 +1,99 @@
+use std::thread;
+
+use tauri::{AppHandle, Manager, RunEvent, WindowEvent};
+
+use crate::{emit_event, stop_product, AppConfig, ExitPolicy, Supervisor, APP_EXIT_REQUESTED};
+
+/// 当前配置的退出策略，配置读取失败时使用默认策略
+fn exit_policy(app_handle: &AppHandle) -> ExitPolicy {
+    AppConfig::get_app_config(app_handle)
+        .map(|app_config| app_config.exit_policy())
+        .unwrap_or_default()
+}
+
+/// 启动时处理上次运行遗留的产品进程
+pub(crate) fn recover_products(app_handle: &AppHandle) {
+    let policy = exit_policy(app_handle);
+    app_handle.state::<Supervisor>().recover(policy);
+}
+
+/// 退出 ToyStudio：停止所有产品或让其继续运行，完成后退出应用
+pub fn exit_app(app_handle: &AppHandle, stop_products: bool) {
+    let supervisor = app_handle.state::<Supervisor>();
+    if !supervisor.begin_exit() {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let supervisor = app_handle.state::<Supervisor>();
+        if stop_products {
+            // 并行停止，避免每个产品依次等待超时
+            thread::scope(|scope| {
+                for product_id in supervisor.running_products() {
+                    let app_handle = &app_handle;
+                    let supervisor = &supervisor;
+                    scope.spawn(move || {
+                        if let Err(e) = stop_product(app_handle, supervisor, &product_id) {
+                            println!("stop {} on exit error:{}", product_id, e);
+                        }
+                    });
+                }
+            });
+        } else {
+            supervisor.detach_all();
+        }
+        app_handle.exit(0);
+    });
+}
+
+/// 处理应用生命周期事件：关闭窗口或退出时按退出策略处理运行中的产品
+pub fn handle_run_event(app_handle: &AppHandle, event: RunEvent) {
+    let supervisor = app_handle.state::<Supervisor>();
+    match event {
+        RunEvent::WindowEvent {
+            event: WindowEvent::CloseRequested { api, .. },
+            ..
+        } => {
+            if supervisor.is_exiting() || supervisor.running_products().is_empty() {
+                return;
+            }
+            match exit_policy(app_handle) {
+                ExitPolicy::StopAll => {
+                    api.prevent_close();
+                    exit_app(app_handle, true);
+                }
+                ExitPolicy::Ask => {
+                    api.prevent_close();
+                    emit_event(
+                        app_handle,
+                        APP_EXIT_REQUESTED,
+                        supervisor.running_products(),
+                    );
+                }
+                // 窗口关闭后会收到 `ExitRequested`
+                ExitPolicy::LeaveRunning => {}
+            }
+        }
+        RunEvent::ExitRequested { api, .. } => {
+            if supervisor.is_exiting() || supervisor.running_products().is_empty() {
+                return;
+            }
+            match exit_policy(app_handle) {
+                ExitPolicy::LeaveRunning => {
+                    supervisor.begin_exit();
+                    supervisor.detach_all();
+                }
+                // 没有窗口可以询问用户时按停止所有产品处理
+                ExitPolicy::StopAll | ExitPolicy::Ask => {
+                    api.prevent_exit();
+                    exit_app(app_handle, true);
+                }
+            }
+        }
+        RunEvent::Exit => {
+            // 兜底：结束仍由 ToyStudio 管理的产品进程树
+            supervisor.kill_all();
+        }
+        _ => {}
+    }
+}
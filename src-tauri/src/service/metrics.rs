@@ -0,0 +1,257 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager};
+
+use crate::{emit_event, now_millis, Supervisor, PRODUCT_METRICS};
+
+/// 资源占用采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+/// 每个产品保留的采样数，约两分钟
+const MAX_SAMPLES: usize = 60;
+
+/// 产品进程树的一次资源占用采样，`product_metrics` 命令和 `product://metrics` 事件的负载
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductMetrics {
+    pub product_id: String,
+    /// 采样时间（毫秒时间戳）
+    pub timestamp: u64,
+    /// CPU 占用，按核心数归一化到 0~100
+    pub cpu_percent: f32,
+    /// 常驻内存（字节）
+    pub memory_bytes: u64,
+    /// 显存（字节），没有 NVIDIA 显卡或无法获取时为 `None`
+    pub vram_bytes: Option<u64>,
+    /// 进程树中的进程数
+    pub process_count: usize,
+}
+
+/// 一次运行的采样记录，主进程变化（重新启动）后清空
+struct MetricsHistory {
+    os_pid: u32,
+    samples: VecDeque<ProductMetrics>,
+}
+
+/// 运行中产品的资源占用采样器，由 Tauri managed state 持有
+///
+/// 后台线程定期统计每个产品主进程及其所有子进程的 CPU、内存，
+/// 以及通过 `nvidia-smi` 获取的显存，并向前端发送 `product://metrics` 事件。
+pub struct MetricsSampler {
+    system: Mutex<System>,
+    history: Mutex<HashMap<String, MetricsHistory>>,
+    /// `nvidia-smi` 不可用时不再尝试
+    gpu_available: AtomicBool,
+}
+
+impl Default for MetricsSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSampler {
+    pub fn new() -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+            history: Mutex::new(HashMap::new()),
+            gpu_available: AtomicBool::new(true),
+        }
+    }
+
+    /// 启动后台采样线程
+    pub fn start(app_handle: AppHandle) {
+        thread::spawn(move || loop {
+            app_handle.state::<MetricsSampler>().sample(&app_handle);
+            thread::sleep(SAMPLE_INTERVAL);
+        });
+    }
+
+    /// 获取产品本次运行的采样记录，按时间先后排列
+    pub fn history(&self, product_id: &str) -> Vec<ProductMetrics> {
+        self.history
+            .lock()
+            .ok()
+            .and_then(|history| {
+                history
+                    .get(product_id)
+                    .map(|history| history.samples.iter().cloned().collect())
+            })
+            .unwrap_or_default()
+    }
+
+    /// 对所有运行中的产品采样一次
+    fn sample(&self, app_handle: &AppHandle) {
+        let supervisor = app_handle.state::<Supervisor>();
+        let roots: Vec<(String, u32)> = supervisor
+            .running_products()
+            .into_iter()
+            .filter_map(|product_id| {
+                let os_pid = supervisor.status(&product_id).os_pid?;
+                Some((product_id, os_pid))
+            })
+            .collect();
+
+        if let Ok(mut history) = self.history.lock() {
+            history.retain(|product_id, _| roots.iter().any(|(id, _)| id == product_id));
+        }
+        if roots.is_empty() {
+            return;
+        }
+
+        let vram = self.query_vram();
+        let timestamp = now_millis();
+        let cpu_count = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1) as f32;
+
+        let samples: Vec<ProductMetrics> = {
+            let Ok(mut system) = self.system.lock() else {
+                return;
+            };
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::nothing()
+                    .with_cpu()
+                    .with_memory()
+                    .without_tasks(),
+            );
+
+            // Linux 上线程也会作为进程列出，其内存为整个进程的内存，不计入进程树
+            let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+            for (pid, process) in system.processes() {
+                if process.thread_kind().is_some() {
+                    continue;
+                }
+                if let Some(parent) = process.parent() {
+                    children.entry(parent).or_default().push(*pid);
+                }
+            }
+
+            roots
+                .iter()
+                .map(|(product_id, os_pid)| {
+                    let tree = process_tree(&children, Pid::from_u32(*os_pid));
+                    let mut cpu_percent = 0.0;
+                    let mut memory_bytes = 0;
+                    for pid in &tree {
+                        if let Some(process) = system.process(*pid) {
+                            cpu_percent += process.cpu_usage();
+                            memory_bytes += process.memory();
+                        }
+                    }
+                    let vram_bytes = vram
+                        .as_ref()
+                        .map(|vram| tree.iter().filter_map(|pid| vram.get(&pid.as_u32())).sum());
+                    ProductMetrics {
+                        product_id: product_id.clone(),
+                        timestamp,
+                        cpu_percent: cpu_percent / cpu_count,
+                        memory_bytes,
+                        vram_bytes,
+                        process_count: tree.len(),
+                    }
+                })
+                .collect()
+        };
+
+        if let Ok(mut history) = self.history.lock() {
+            for (metrics, (_, os_pid)) in samples.iter().zip(&roots) {
+                let entry = history
+                    .entry(metrics.product_id.clone())
+                    .or_insert_with(|| MetricsHistory {
+                        os_pid: *os_pid,
+                        samples: VecDeque::new(),
+                    });
+                if entry.os_pid != *os_pid {
+                    entry.os_pid = *os_pid;
+                    entry.samples.clear();
+                }
+                if entry.samples.len() >= MAX_SAMPLES {
+                    entry.samples.pop_front();
+                }
+                entry.samples.push_back(metrics.clone());
+            }
+        }
+        for metrics in samples {
+            emit_event(app_handle, PRODUCT_METRICS, metrics);
+        }
+    }
+
+    /// 通过 `nvidia-smi` 获取每个进程占用的显存（字节）
+    ///
+    /// 没有 NVIDIA 显卡或驱动时返回 `None`，之后不再尝试；
+    /// 个别进程无法获取显存（如 Windows WDDM 模式下显示 `[N/A]`）时忽略该进程。
+    fn query_vram(&self) -> Option<HashMap<u32, u64>> {
+        if !self.gpu_available.load(Ordering::Relaxed) {
+            return None;
+        }
+        let mut command = Command::new("nvidia-smi");
+        command
+            .args([
+                "--query-compute-apps=pid,used_memory",
+                "--format=csv,noheader,nounits",
+            ])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null());
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+
+        let output = match command.output() {
+            Ok(output) if output.status.success() => output,
+            Ok(output) => {
+                println!("nvidia-smi exited with {}, vram disabled", output.status);
+                self.gpu_available.store(false, Ordering::Relaxed);
+                return None;
+            }
+            Err(e) => {
+                println!("nvidia-smi not available, vram disabled:{}", e);
+                self.gpu_available.store(false, Ordering::Relaxed);
+                return None;
+            }
+        };
+
+        // 每行形如 `1234, 512`，显存单位为 MiB
+        let mut vram: HashMap<u32, u64> = HashMap::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Some((pid, used)) = line.split_once(',') else {
+                continue;
+            };
+            if let (Ok(pid), Ok(used)) = (pid.trim().parse::<u32>(), used.trim().parse::<u64>()) {
+                *vram.entry(pid).or_default() += used * 1024 * 1024;
+            }
+        }
+        Some(vram)
+    }
+}
+
+/// 进程及其所有子孙进程
+fn process_tree(children: &HashMap<Pid, Vec<Pid>>, root: Pid) -> Vec<Pid> {
+    let mut tree = vec![root];
+    let mut index = 0;
+    while index < tree.len() {
+        if let Some(pids) = children.get(&tree[index]) {
+            let new: Vec<Pid> = pids
+                .iter()
+                .copied()
+                .filter(|pid| !tree.contains(pid))
+                .collect();
+            tree.extend(new);
+        }
+        index += 1;
+    }
+    tree
+}
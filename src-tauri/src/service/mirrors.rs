@@ -0,0 +1,299 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use url::Url;
+
+use crate::{
+    now_millis, AppConfig, CloneOptions, LogStream, OutputSink, ToyResult, ToyStudioError,
+};
+
+/// 镜像替换的网站
+const MIRRORED_SITE: &str = "https://github.com/";
+/// 测速时建立连接的超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// git 镜像或代理的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorKind {
+    /// 在仓库地址前加上镜像地址，例如 `https://ghfast.top/https://github.com/...`
+    Prefix,
+    /// 将仓库地址中的网站替换为镜像地址，例如 `https://kkgithub.com/...`
+    HostRewrite,
+    /// 通过 HTTP 代理访问，例如 `http://127.0.0.1:7890`
+    HttpProxy,
+}
+
+/// 直连访问产品git仓库失败时依次尝试的镜像或代理
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitMirror {
+    pub name: String,
+    pub kind: MirrorKind,
+    /// 镜像或代理地址
+    pub url: String,
+    pub enabled: bool,
+}
+
+impl GitMirror {
+    /// 未配置镜像时使用的默认镜像
+    pub fn defaults() -> Vec<GitMirror> {
+        vec![GitMirror {
+            name: "ghfast.top".to_string(),
+            kind: MirrorKind::Prefix,
+            url: "https://ghfast.top".to_string(),
+            enabled: true,
+        }]
+    }
+
+    /// 已启用且填写了地址的镜像才会被尝试
+    pub fn is_usable(&self) -> bool {
+        self.enabled && !self.url.trim().is_empty()
+    }
+
+    /// 使用镜像时加在git命令前的 `-c` 参数
+    ///
+    /// 镜像的配置不写入仓库，仓库的远程地址始终是产品配置中的地址。
+    pub fn git_config(&self) -> Vec<String> {
+        let url = self.url.trim().trim_end_matches('/');
+        let config = match self.kind {
+            MirrorKind::Prefix => {
+                format!("url.{}/{}.insteadOf={}", url, MIRRORED_SITE, MIRRORED_SITE)
+            }
+            MirrorKind::HostRewrite => format!("url.{}/.insteadOf={}", url, MIRRORED_SITE),
+            MirrorKind::HttpProxy => format!("http.proxy={}", url),
+        };
+        vec!["-c".to_string(), config]
+    }
+}
+
+/// 先直连执行 `run`，失败后依次通过 `options.mirrors` 中启用的镜像执行，直到成功；
+/// 记录每个镜像的成功、失败次数
+///
+/// 只有git命令执行失败（网络错误等）时才尝试镜像，任务被取消后不再尝试；未填写地址的镜像被跳过。
+/// 镜像按配置中的顺序尝试，不按测得的延迟排序。
+pub fn with_mirrors<F>(
+    app_handle: &AppHandle,
+    options: &CloneOptions,
+    output: OutputSink,
+    mut run: F,
+) -> ToyResult<()>
+where
+    F: FnMut(&CloneOptions) -> ToyResult<()>,
+{
+    let mut result = run(options);
+    for mirror in options.mirrors.iter().filter(|mirror| mirror.is_usable()) {
+        let Err(e @ ToyStudioError::GitCommandFailed { .. }) = &result else {
+            break;
+        };
+        if output.is_cancelled() {
+            break;
+        }
+        println!("try git mirror {}:{}", mirror.name, mirror.url);
+        output.line(
+            LogStream::Stderr,
+            &format!("{}; trying mirror {}", e, mirror.name),
+        );
+        let options = CloneOptions {
+            mirror: Some(mirror.clone()),
+            ..options.clone()
+        };
+        result = run(&options);
+        MirrorStats::record(app_handle, &mirror.url, result.is_ok());
+    }
+    result
+}
+
+/// 镜像的使用统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct MirrorStats {
+    pub successes: u32,
+    pub failures: u32,
+    /// 最近一次使用的时间（毫秒时间戳）
+    pub last_used_at: Option<u64>,
+    /// 最近一次测速的耗时（毫秒），无法连接时为空
+    pub latency_ms: Option<u64>,
+}
+
+impl MirrorStats {
+    /// 获取镜像统计文件路径，与应用配置文件在同一目录
+    pub fn get_stats_file_path(app_handle: &AppHandle) -> PathBuf {
+        let config_path = AppConfig::get_config_file_path(app_handle);
+        config_path.with_file_name("git_mirror_stats.json")
+    }
+
+    /// 获取所有镜像的统计：镜像地址 -> 统计
+    pub fn load_all(app_handle: &AppHandle) -> ToyResult<HashMap<String, MirrorStats>> {
+        let path = Self::get_stats_file_path(app_handle);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let json = fs::read_to_string(&path).map_err(|e| ToyStudioError::io(&path, e))?;
+        serde_json::from_str(&json).map_err(ToyStudioError::config)
+    }
+
+    /// 修改并保存镜像的统计
+    pub fn update<F>(app_handle: &AppHandle, f: F) -> ToyResult<()>
+    where
+        F: FnOnce(&mut HashMap<String, MirrorStats>),
+    {
+        let mut stats = Self::load_all(app_handle)?;
+        f(&mut stats);
+        let path = Self::get_stats_file_path(app_handle);
+        let json = serde_json::to_string_pretty(&stats).map_err(ToyStudioError::config)?;
+        fs::write(&path, json).map_err(|e| ToyStudioError::io(&path, e))
+    }
+
+    /// 记录一次使用镜像的结果
+    pub fn record(app_handle: &AppHandle, url: &str, success: bool) {
+        let result = Self::update(app_handle, |stats| {
+            let stats = stats.entry(url.to_string()).or_default();
+            if success {
+                stats.successes += 1;
+            } else {
+                stats.failures += 1;
+            }
+            stats.last_used_at = Some(now_millis());
+        });
+        if let Err(e) = result {
+            println!("record git mirror {} error:{}", url, e);
+        }
+    }
+}
+
+/// 镜像的测速结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorProbe {
+    pub url: String,
+    /// 建立 TCP 连接的耗时（毫秒），无法连接时为空
+    pub latency_ms: Option<u64>,
+}
+
+/// 测试与镜像或代理建立 TCP 连接的耗时，无法连接时返回 `None`
+fn probe_mirror(mirror: &GitMirror) -> Option<u64> {
+    let url = Url::parse(mirror.url.trim()).ok()?;
+    let host = url.host_str()?;
+    let port = url.port_or_known_default()?;
+    (host, port)
+        .to_socket_addrs()
+        .ok()?
+        .filter_map(|addr| {
+            let started = Instant::now();
+            TcpStream::connect_timeout(&addr, PROBE_TIMEOUT)
+                .ok()
+                .map(|_| started.elapsed().as_millis() as u64)
+        })
+        .min()
+}
+
+/// 同时测试所有镜像，结果顺序与 `mirrors` 一致，并记录到镜像统计中
+pub fn probe_mirrors(app_handle: &AppHandle, mirrors: &[GitMirror]) -> Vec<MirrorProbe> {
+    let probes: Vec<MirrorProbe> = thread::scope(|scope| {
+        let handles: Vec<_> = mirrors
+            .iter()
+            .map(|mirror| scope.spawn(move || probe_mirror(mirror)))
+            .collect();
+        mirrors
+            .iter()
+            .zip(handles)
+            .map(|(mirror, handle)| MirrorProbe {
+                url: mirror.url.clone(),
+                latency_ms: handle.join().unwrap_or(None),
+            })
+            .collect()
+    });
+    let result = MirrorStats::update(app_handle, |stats| {
+        for probe in &probes {
+            stats.entry(probe.url.clone()).or_default().latency_ms = probe.latency_ms;
+        }
+    });
+    if let Err(e) = result {
+        println!("record git mirror latency error:{}", e);
+    }
+    probes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirror(kind: MirrorKind, url: &str) -> GitMirror {
+        GitMirror {
+            name: "test".to_string(),
+            kind,
+            url: url.to_string(),
+            enabled: true,
+        }
+    }
+
+    fn config(kind: MirrorKind, url: &str) -> String {
+        let args = mirror(kind, url).git_config();
+        assert_eq!(args[0], "-c");
+        args[1].clone()
+    }
+
+    #[test]
+    fn prefix_mirror_is_put_in_front_of_github_url() {
+        assert_eq!(
+            config(MirrorKind::Prefix, "https://ghfast.top"),
+            "url.https://ghfast.top/https://github.com/.insteadOf=https://github.com/"
+        );
+    }
+
+    #[test]
+    fn host_rewrite_mirror_replaces_github() {
+        assert_eq!(
+            config(MirrorKind::HostRewrite, "https://kkgithub.com"),
+            "url.https://kkgithub.com/.insteadOf=https://github.com/"
+        );
+    }
+
+    #[test]
+    fn http_proxy_is_set_as_git_proxy() {
+        assert_eq!(
+            config(MirrorKind::HttpProxy, "http://127.0.0.1:7890"),
+            "http.proxy=http://127.0.0.1:7890"
+        );
+    }
+
+    #[test]
+    fn trailing_slashes_and_spaces_are_ignored() {
+        for kind in [
+            MirrorKind::Prefix,
+            MirrorKind::HostRewrite,
+            MirrorKind::HttpProxy,
+        ] {
+            let expected = config(kind, "https://mirror.example");
+            assert_eq!(config(kind, "https://mirror.example/"), expected);
+            assert_eq!(config(kind, " https://mirror.example// "), expected);
+        }
+    }
+
+    #[test]
+    fn disabled_or_empty_mirrors_are_not_used() {
+        assert!(mirror(MirrorKind::Prefix, "https://ghfast.top").is_usable());
+        assert!(!mirror(MirrorKind::Prefix, "").is_usable());
+        assert!(!mirror(MirrorKind::Prefix, "  ").is_usable());
+        let mut disabled = mirror(MirrorKind::Prefix, "https://ghfast.top");
+        disabled.enabled = false;
+        assert!(!disabled.is_usable());
+    }
+
+    #[test]
+    fn mirror_kind_uses_snake_case_names() {
+        let json = serde_json::to_string(&[
+            MirrorKind::Prefix,
+            MirrorKind::HostRewrite,
+            MirrorKind::HttpProxy,
+        ])
+        .unwrap();
+        assert_eq!(json, r#"["prefix","host_rewrite","http_proxy"]"#);
+    }
+}
@@ -0,0 +1,32 @@
+pub mod backup;
+pub mod events;
+pub mod health;
+pub mod install;
+pub mod jobs;
+pub mod lifecycle;
+pub mod metrics;
+pub mod mirrors;
+pub mod pid_file;
+pub mod ports;
+pub mod product_log;
+pub mod restart;
+pub mod supervisor;
+pub mod updates;
+pub mod versions;
+pub mod web_ui;
+pub use backup::*;
+pub use events::*;
+pub use health::*;
+pub use install::*;
+pub use jobs::*;
+pub use lifecycle::*;
+pub use metrics::*;
+pub use mirrors::*;
+pub use pid_file::*;
+pub use ports::*;
+pub use product_log::*;
+pub use restart::*;
+pub use supervisor::*;
+pub use updates::*;
+pub use versions::*;
+pub use web_ui::*;
@@ -0,0 +1,56 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ToyResult, ToyStudioError};
+
+/// PID文件中的一条记录，用于 ToyStudio 重启后重新接管仍在运行的产品
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PidRecord {
+    pub product_id: String,
+    /// 操作系统进程ID
+    pub os_pid: u32,
+    /// 启动时间（毫秒时间戳），用于判断进程ID是否已被复用
+    pub started_at: u64,
+    pub log_session: Option<String>,
+    /// 产品 Web 界面地址
+    pub ui_url: Option<String>,
+    /// 产品端口
+    pub port: Option<u16>,
+    /// 启动配置
+    pub profile: Option<String>,
+    /// 运行的未使用版本的ID
+    pub version: Option<String>,
+}
+
+/// 读取PID文件，文件不存在或格式错误时返回空列表
+pub fn read_pid_file(path: &Path) -> Vec<PidRecord> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            println!("pid file {} parse error:{}", path.display(), e);
+            Vec::new()
+        }),
+        Err(e) => {
+            println!("pid file {} read error:{}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// 写入PID文件，没有运行中的产品时删除文件
+pub fn write_pid_file(path: &Path, records: &[PidRecord]) -> ToyResult<()> {
+    if records.is_empty() {
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| ToyStudioError::io(path, e))?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| ToyStudioError::io(dir, e))?;
+    }
+    let json = serde_json::to_string_pretty(records).map_err(ToyStudioError::internal)?;
+    fs::write(path, json).map_err(|e| ToyStudioError::io(path, e))
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::{Ipv4Addr, TcpListener},
+    path::Path,
+};
+
+use crate::{ToyResult, ToyStudioError};
+
+/// 由系统分配空闲端口时的最大尝试次数
+const MAX_ALLOCATE_ATTEMPTS: usize = 16;
+
+/// 读取端口文件：产品ID -> 上次分配的端口，文件不存在或格式错误时返回空表
+pub fn read_port_file(path: &Path) -> HashMap<String, u16> {
+    if !path.exists() {
+        return HashMap::new();
+    }
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            println!("port file {} parse error:{}", path.display(), e);
+            HashMap::new()
+        }),
+        Err(e) => {
+            println!("port file {} read error:{}", path.display(), e);
+            HashMap::new()
+        }
+    }
+}
+
+/// 记录产品本次分配的端口，下次启动时优先使用
+pub fn save_port(path: &Path, product_id: &str, port: u16) -> ToyResult<()> {
+    let mut ports = read_port_file(path);
+    if ports.get(product_id) == Some(&port) {
+        return Ok(());
+    }
+    ports.insert(product_id.to_string(), port);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| ToyStudioError::io(dir, e))?;
+    }
+    let json = serde_json::to_string_pretty(&ports).map_err(ToyStudioError::internal)?;
+    fs::write(path, json).map_err(|e| ToyStudioError::io(path, e))
+}
+
+/// 端口在本机没有被监听
+fn is_port_free(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+        && TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok()
+}
+
+/// 为产品分配端口
+///
+/// 依次尝试上次分配的端口、产品配置的默认端口，都被占用时由系统分配空闲端口。
+/// `reserved` 为其他运行中产品的端口，即使暂未监听也不会分配。
+pub fn allocate_port(
+    path: &Path,
+    product_id: &str,
+    preferred: Option<u16>,
+    reserved: &[u16],
+) -> ToyResult<u16> {
+    let last = read_port_file(path).get(product_id).copied();
+    let candidates = last.into_iter().chain(preferred);
+    for port in candidates {
+        if !reserved.contains(&port) && is_port_free(port) {
+            return Ok(port);
+        }
+        println!("port {} of {} is in use", port, product_id);
+    }
+
+    for _ in 0..MAX_ALLOCATE_ATTEMPTS {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .map_err(|e| ToyStudioError::internal(format!("allocate port: {}", e)))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| ToyStudioError::internal(format!("allocate port: {}", e)))?
+            .port();
+        drop(listener);
+        if !reserved.contains(&port) && is_port_free(port) {
+            return Ok(port);
+        }
+    }
+    Err(ToyStudioError::internal(format!(
+        "no free port for {}",
+        product_id
+    )))
+}
@@ -0,0 +1,507 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Component, Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{emit_event, now_millis, ToyResult, ToyStudioError, PRODUCT_LOG_LINE};
+
+/// 单个日志文件的最大字节数，超过后轮转
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// 每个会话最多保留的轮转文件数（不含当前文件）
+const MAX_ROTATED_FILES: usize = 5;
+/// 每个产品最多保留的会话数
+const MAX_SESSIONS: usize = 20;
+/// 日志行的时间格式，例如 `2025-01-01 12:00:00.000`
+const LOG_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+const LOG_TIME_LEN: usize = 23;
+const LOG_SUFFIX: &str = ".log";
+/// 作为日志转发进程运行的命令行参数
+const RELAY_ARG: &str = "--log-relay";
+/// 转发进程写入的日志文件大小上限，超过后丢弃输出
+const MAX_RELAY_FILE_BYTES: u64 = MAX_LOG_FILE_BYTES * (MAX_ROTATED_FILES as u64 + 1);
+
+/// 产品输出管道的读取端
+#[cfg(unix)]
+type PipeHandle = std::os::fd::OwnedFd;
+#[cfg(target_os = "windows")]
+type PipeHandle = std::os::windows::io::OwnedHandle;
+
+/// 输出流
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "stdout" => Some(LogStream::Stdout),
+            "stderr" => Some(LogStream::Stderr),
+            _ => None,
+        }
+    }
+}
+
+/// 一行产品输出，同时是 `product://log-line` 事件的负载
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub product_id: String,
+    pub session: String,
+    /// 毫秒时间戳
+    pub timestamp: u64,
+    pub stream: LogStream,
+    pub line: String,
+}
+
+impl LogLine {
+    /// 格式化为日志文件中的一行：`<时间> [<stream>] <内容>`
+    fn format(&self) -> String {
+        let time = Local
+            .timestamp_millis_opt(self.timestamp as i64)
+            .single()
+            .unwrap_or_else(Local::now);
+        format!(
+            "{} [{}] {}",
+            time.format(LOG_TIME_FORMAT),
+            self.stream.as_str(),
+            self.line
+        )
+    }
+
+    /// 解析日志文件中的一行，格式不符时返回 `None`
+    fn parse(product_id: &str, session: &str, text: &str) -> Option<Self> {
+        let time = text.get(..LOG_TIME_LEN)?;
+        let time = NaiveDateTime::parse_from_str(time, LOG_TIME_FORMAT).ok()?;
+        let timestamp = time
+            .and_local_timezone(Local)
+            .earliest()?
+            .timestamp_millis();
+
+        let rest = text.get(LOG_TIME_LEN..)?.strip_prefix(" [")?;
+        let (stream, line) = rest.split_once(']')?;
+        let stream = LogStream::parse(stream)?;
+        let line = line.strip_prefix(' ').unwrap_or(line);
+
+        Some(Self {
+            product_id: product_id.to_string(),
+            session: session.to_string(),
+            timestamp: timestamp.max(0) as u64,
+            stream,
+            line: line.to_string(),
+        })
+    }
+}
+
+/// 产品输出的行监听器，例如就绪检查中的 `stdout_regex`
+pub type LineWatcher = Arc<dyn Fn(&LogLine) + Send + Sync>;
+
+/// 生成新的日志会话ID，按时间排序
+pub fn new_log_session() -> String {
+    Local::now().format("%Y%m%d-%H%M%S-%3f").to_string()
+}
+
+/// 检查前端传入的会话ID，只接受文件名，避免访问日志目录之外的路径
+fn check_session(session: &str) -> ToyResult<()> {
+    let mut components = Path::new(session).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(()),
+        _ => Err(ToyStudioError::invalid_argument(format!(
+            "invalid log session: {session}"
+        ))),
+    }
+}
+
+/// 会话的第 `index` 个日志文件，0 为当前文件，数字越大越旧
+fn session_file(log_dir: &Path, session: &str, index: usize) -> PathBuf {
+    if index == 0 {
+        log_dir.join(format!("{session}{LOG_SUFFIX}"))
+    } else {
+        log_dir.join(format!("{session}.{index}{LOG_SUFFIX}"))
+    }
+}
+
+/// 按大小轮转的日志文件
+struct RotatingLogFile {
+    log_dir: PathBuf,
+    session: String,
+    file: File,
+    size: u64,
+}
+
+impl RotatingLogFile {
+    fn open(log_dir: &Path, session: &str) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(session_file(log_dir, session, 0))?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            log_dir: log_dir.to_path_buf(),
+            session: session.to_string(),
+            file,
+            size,
+        })
+    }
+
+    fn write_line(&mut self, text: &str) -> io::Result<()> {
+        let len = text.len() as u64 + 1;
+        if self.size > 0 && self.size + len > MAX_LOG_FILE_BYTES {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", text)?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// `<session>.log` -> `<session>.1.log` -> ... ，超出数量的最旧文件被覆盖
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for index in (0..MAX_ROTATED_FILES).rev() {
+            let from = session_file(&self.log_dir, &self.session, index);
+            if from.exists() {
+                fs::rename(&from, session_file(&self.log_dir, &self.session, index + 1))?;
+            }
+        }
+        *self = Self::open(&self.log_dir, &self.session)?;
+        Ok(())
+    }
+}
+
+/// 日志会话的写入器：写入日志文件、通知监听器并发送 `product://log-line` 事件
+pub struct LogWriter {
+    app_handle: AppHandle,
+    product_id: String,
+    session: String,
+    file: Mutex<RotatingLogFile>,
+    watchers: Vec<LineWatcher>,
+}
+
+impl LogWriter {
+    /// 打开（或追加到）产品的日志会话
+    pub fn open(
+        app_handle: &AppHandle,
+        product_id: &str,
+        log_dir: &Path,
+        session: &str,
+        watchers: Vec<LineWatcher>,
+    ) -> ToyResult<Arc<Self>> {
+        fs::create_dir_all(log_dir).map_err(|e| ToyStudioError::io(log_dir, e))?;
+        prune_log_sessions(log_dir);
+
+        let file = RotatingLogFile::open(log_dir, session)
+            .map_err(|e| ToyStudioError::io(session_file(log_dir, session, 0), e))?;
+        Ok(Arc::new(LogWriter {
+            app_handle: app_handle.clone(),
+            product_id: product_id.to_string(),
+            session: session.to_string(),
+            file: Mutex::new(file),
+            watchers,
+        }))
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    pub fn write(&self, stream: LogStream, line: String) {
+        let log_line = LogLine {
+            product_id: self.product_id.clone(),
+            session: self.session.clone(),
+            timestamp: now_millis(),
+            stream,
+            line,
+        };
+        if let Ok(mut file) = self.file.lock() {
+            if let Err(e) = file.write_line(&log_line.format()) {
+                println!("write log {} error:{}", self.product_id, e);
+            }
+        }
+        for watcher in &self.watchers {
+            watcher(&log_line);
+        }
+        emit_event(&self.app_handle, PRODUCT_LOG_LINE, log_line);
+    }
+
+    /// 逐行读取输出直到管道关闭
+    fn pump<R: Read>(&self, stream: LogStream, reader: R) {
+        if let Err(e) = read_lines(reader, |line| self.write(stream, line)) {
+            println!("read {} {} error:{}", self.product_id, stream.as_str(), e);
+        }
+    }
+}
+
+/// 逐行读取直到管道关闭，去掉行尾的 `\r\n`
+fn read_lines<R: Read>(reader: R, mut f: impl FnMut(String)) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&buf);
+        f(text.trim_end_matches(['\r', '\n']).to_string());
+    }
+}
+
+/// 产品输出管道读取端的副本
+///
+/// ToyStudio 退出而产品继续运行时，管道交给转发进程继续读取；
+/// 否则产品之后写输出时会因管道关闭收到 SIGPIPE / EPIPE 而退出或出错。
+pub struct OutputRelay {
+    log_dir: PathBuf,
+    session: String,
+    pipes: Vec<(LogStream, PipeHandle)>,
+}
+
+impl OutputRelay {
+    /// 为每个输出流启动一个转发进程（ToyStudio 自身，参数为 `--log-relay`），
+    /// 将产品输出追加到当前日志会话；转发进程在产品退出、管道关闭后结束
+    pub fn spawn(self) {
+        let exe = match env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                println!("get current exe for log relay error:{}", e);
+                return;
+            }
+        };
+        for (stream, pipe) in self.pipes {
+            let mut command = Command::new(&exe);
+            command
+                .arg(RELAY_ARG)
+                .arg(&self.log_dir)
+                .arg(&self.session)
+                .arg(stream.as_str())
+                .stdin(Stdio::from(pipe))
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            // 转发进程不随 ToyStudio 的进程组或控制台一起结束
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                command.process_group(0);
+            }
+            #[cfg(target_os = "windows")]
+            {
+                use std::os::windows::process::CommandExt;
+                command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+            }
+            match command.spawn() {
+                Ok(child) => println!(
+                    "log relay {} of session {} started:{}",
+                    stream.as_str(),
+                    self.session,
+                    child.id()
+                ),
+                Err(e) => println!("spawn log relay error:{}", e),
+            }
+        }
+    }
+}
+
+/// 以 `--log-relay <log_dir> <session> <stream>` 参数启动时作为日志转发进程运行并返回 `true`
+///
+/// 从标准输入逐行读取产品输出，按日志格式追加到会话的当前日志文件，直到管道关闭。
+/// 转发进程不轮转日志，文件超过 `MAX_RELAY_FILE_BYTES` 后丢弃输出，但仍继续读取，避免产品阻塞。
+pub fn run_log_relay() -> bool {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [flag, log_dir, session, stream] = args.as_slice() else {
+        return false;
+    };
+    if flag != RELAY_ARG {
+        return false;
+    }
+    let Some(stream) = LogStream::parse(stream) else {
+        return true;
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(session_file(Path::new(log_dir), session, 0))
+        .ok();
+    let _ = read_lines(io::stdin().lock(), |line| {
+        let Some(out) = file.as_mut() else {
+            return;
+        };
+        let full = out
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() >= MAX_RELAY_FILE_BYTES);
+        if full {
+            return;
+        }
+        let log_line = LogLine {
+            product_id: String::new(),
+            session: session.clone(),
+            timestamp: now_millis(),
+            stream,
+            line,
+        };
+        if writeln!(out, "{}", log_line.format()).is_err() {
+            file = None;
+        }
+    });
+    true
+}
+
+/// 接管子进程的 stdout/stderr，写入日志文件并发送 `product://log-line` 事件
+///
+/// 子进程需以 `Stdio::piped()` 创建；每个输出流由一个后台线程读取，进程退出后线程自动结束。
+/// `watchers` 会收到每一行输出。返回管道读取端的副本，用于 ToyStudio 退出后继续转发输出。
+pub fn capture_output(
+    app_handle: &AppHandle,
+    product_id: &str,
+    log_dir: &Path,
+    session: &str,
+    child: &Arc<Mutex<Child>>,
+    watchers: Vec<LineWatcher>,
+) -> ToyResult<OutputRelay> {
+    let writer = LogWriter::open(app_handle, product_id, log_dir, session, watchers)?;
+
+    let (stdout, stderr) = {
+        let mut child = child.lock().map_err(ToyStudioError::internal)?;
+        (child.stdout.take(), child.stderr.take())
+    };
+    let mut relay = OutputRelay {
+        log_dir: log_dir.to_path_buf(),
+        session: session.to_string(),
+        pipes: Vec::new(),
+    };
+    let pipes = [
+        (LogStream::Stdout, stdout.map(PipeHandle::from)),
+        (LogStream::Stderr, stderr.map(PipeHandle::from)),
+    ];
+    for (stream, pipe) in pipes {
+        let Some(pipe) = pipe else {
+            continue;
+        };
+        match pipe.try_clone() {
+            Ok(copy) => relay.pipes.push((stream, copy)),
+            Err(e) => println!("clone {} {} pipe error:{}", product_id, stream.as_str(), e),
+        }
+        let writer = writer.clone();
+        thread::spawn(move || writer.pump(stream, File::from(pipe)));
+    }
+    Ok(relay)
+}
+
+/// 获取产品的日志会话，最新的在前
+pub fn list_log_sessions(log_dir: &Path) -> ToyResult<Vec<String>> {
+    if !log_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(log_dir).map_err(|e| ToyStudioError::io(log_dir, e))?;
+
+    let mut sessions: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let session = name.strip_suffix(LOG_SUFFIX)?;
+            // 跳过轮转文件 `<session>.<n>.log`
+            if session.contains('.') {
+                None
+            } else {
+                Some(session.to_string())
+            }
+        })
+        .collect();
+    sessions.sort_by(|a, b| b.cmp(a));
+    Ok(sessions)
+}
+
+/// 删除超出保留数量的旧会话
+fn prune_log_sessions(log_dir: &Path) {
+    let Ok(sessions) = list_log_sessions(log_dir) else {
+        return;
+    };
+    for session in sessions.iter().skip(MAX_SESSIONS.saturating_sub(1)) {
+        for index in 0..=MAX_ROTATED_FILES {
+            let path = session_file(log_dir, session, index);
+            if path.exists() {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// 读取产品日志
+///
+/// - `session`：日志会话，为空时读取最新会话
+/// - `tail`：只返回最后 N 行
+/// - `since`：只返回该毫秒时间戳之后的行
+pub fn read_logs(
+    log_dir: &Path,
+    product_id: &str,
+    session: Option<&str>,
+    tail: Option<usize>,
+    since: Option<u64>,
+) -> ToyResult<Vec<LogLine>> {
+    let session = match session {
+        Some(session) => {
+            check_session(session)?;
+            session.to_string()
+        }
+        None => match list_log_sessions(log_dir)?.into_iter().next() {
+            Some(session) => session,
+            None => return Ok(Vec::new()),
+        },
+    };
+
+    let mut lines = Vec::new();
+    for index in (0..=MAX_ROTATED_FILES).rev() {
+        let path = session_file(log_dir, &session, index);
+        if !path.exists() {
+            continue;
+        }
+        let file = File::open(&path).map_err(|e| ToyStudioError::io(&path, e))?;
+        for text in BufReader::new(file).split(b'\n') {
+            let text = text.map_err(|e| ToyStudioError::io(&path, e))?;
+            let text = String::from_utf8_lossy(&text);
+            let Some(line) = LogLine::parse(product_id, &session, text.trim_end_matches('\r'))
+            else {
+                continue;
+            };
+            if since.is_none_or(|since| line.timestamp > since) {
+                lines.push(line);
+            }
+        }
+    }
+
+    if let Some(tail) = tail {
+        let skip = lines.len().saturating_sub(tail);
+        lines.drain(..skip);
+    }
+    Ok(lines)
+}
+
+/// 将一个会话的所有日志文件按时间顺序合并导出到 `dest`
+pub fn export_logs(log_dir: &Path, session: &str, dest: &Path) -> ToyResult<()> {
+    check_session(session)?;
+    let mut out = File::create(dest).map_err(|e| ToyStudioError::io(dest, e))?;
+    for index in (0..=MAX_ROTATED_FILES).rev() {
+        let path = session_file(log_dir, session, index);
+        if !path.exists() {
+            continue;
+        }
+        let mut file = File::open(&path).map_err(|e| ToyStudioError::io(&path, e))?;
+        io::copy(&mut file, &mut out).map_err(|e| ToyStudioError::io(dest, e))?;
+    }
+    Ok(())
+}
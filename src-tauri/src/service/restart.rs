@@ -0,0 +1,102 @@
+use std::{collections::HashMap, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{AppConfig, ProcessState, ToyResult, ToyStudioError};
+
+/// 自动重启前的最长等待时间
+const MAX_RESTART_BACKOFF_SECS: u64 = 300;
+/// 产品运行超过该时间后退出，连续重启次数重新计算
+pub const RESTART_STABLE_SECS: u64 = 60;
+
+/// 产品退出后是否自动重启
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    /// 不重启
+    #[default]
+    Never,
+    /// 异常退出（崩溃或就绪检查失败）时重启，最多 `max_retries` 次
+    OnFailure,
+    /// 除用户停止外，退出后总是重启
+    Always,
+}
+
+/// 产品的自动重启策略，由用户按产品配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    /// `on_failure` 时连续重启的最大次数，0 表示不限制
+    pub max_retries: u32,
+    /// 第一次重启前等待的秒数，之后每次翻倍
+    pub backoff_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            max_retries: 3,
+            backoff_secs: 2,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// 获取重启策略文件路径，与应用配置文件在同一目录
+    pub fn get_policies_file_path(app_handle: &AppHandle) -> PathBuf {
+        let config_path = AppConfig::get_config_file_path(app_handle);
+        config_path.with_file_name("restart_policies.json")
+    }
+
+    /// 获取所有产品的重启策略：产品ID -> 重启策略
+    pub fn load_all(app_handle: &AppHandle) -> ToyResult<HashMap<String, RestartPolicy>> {
+        let path = Self::get_policies_file_path(app_handle);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let json = fs::read_to_string(&path).map_err(|e| ToyStudioError::io(&path, e))?;
+        serde_json::from_str(&json).map_err(ToyStudioError::config)
+    }
+
+    /// 获取产品的重启策略，未配置时不重启
+    pub fn load(app_handle: &AppHandle, product_id: &str) -> ToyResult<RestartPolicy> {
+        let mut policies = Self::load_all(app_handle)?;
+        Ok(policies.remove(product_id).unwrap_or_default())
+    }
+
+    /// 保存产品的重启策略
+    pub fn save(app_handle: &AppHandle, product_id: &str, policy: RestartPolicy) -> ToyResult<()> {
+        let mut policies = Self::load_all(app_handle)?;
+        if policy == RestartPolicy::default() {
+            policies.remove(product_id);
+        } else {
+            policies.insert(product_id.to_string(), policy);
+        }
+        let path = Self::get_policies_file_path(app_handle);
+        let json = serde_json::to_string_pretty(&policies).map_err(ToyStudioError::config)?;
+        fs::write(&path, json).map_err(|e| ToyStudioError::io(&path, e))
+    }
+
+    /// 第 `attempt` 次（从 1 开始）连续重启前等待的时间，不应重启时返回 `None`
+    pub fn restart_delay(&self, state: ProcessState, attempt: u32) -> Option<Duration> {
+        let failed = matches!(state, ProcessState::Crashed | ProcessState::Failed);
+        let retry = match self.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => {
+                failed && (self.max_retries == 0 || attempt <= self.max_retries)
+            }
+            RestartMode::Always => failed || matches!(state, ProcessState::Exited(_)),
+        };
+        if !retry {
+            return None;
+        }
+        let exponent = attempt.saturating_sub(1).min(16);
+        let secs = self
+            .backoff_secs
+            .saturating_mul(1 << exponent)
+            .min(MAX_RESTART_BACKOFF_SECS);
+        Some(Duration::from_secs(secs))
+    }
+}
@@ -0,0 +1,859 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    emit_event, now_millis, process_start_time, read_pid_file, start_product, write_pid_file,
+    AppConfig, ExitPolicy, OutputRelay, PidRecord, ProductExitedEvent, RestartMode, RestartPolicy,
+    ToyResult, ToyStudioError, PRODUCT_EXITED, PRODUCT_STATE_CHANGED, RESTART_STABLE_SECS,
+};
+
+/// 后台回收子进程的轮询间隔
+const REAP_INTERVAL: Duration = Duration::from_millis(500);
+/// 停止产品时检查进程是否退出的间隔
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// 关闭命令本身的最长执行时间
+const SHUTDOWN_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+/// 关闭命令执行后等待产品退出的时间
+const SHUTDOWN_COMMAND_GRACE: Duration = Duration::from_secs(5);
+/// 请求进程树退出后等待的时间，超时后强制结束
+const TERMINATE_GRACE: Duration = Duration::from_secs(10);
+/// PID文件中的启动时间与实际进程启动时间允许的误差（秒），超出视为进程ID已被复用
+const PID_REUSE_TOLERANCE_SECS: u64 = 10;
+
+/// 产品进程状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", content = "code")]
+pub enum ProcessState {
+    /// 未运行（未启动过，或已被用户停止）
+    Stopped,
+    /// 进程已创建，尚未确认存活
+    Starting,
+    /// 运行中（未配置就绪检查）
+    Running,
+    /// 就绪检查通过
+    Ready,
+    /// 就绪检查超时，进程已被停止
+    Failed,
+    /// 正在停止
+    Stopping,
+    /// 异常退出：非零退出码或被信号终止
+    Crashed,
+    /// 自行退出，附带退出码
+    Exited(i32),
+}
+
+impl ProcessState {
+    /// 进程是否仍然存活
+    pub fn is_alive(&self) -> bool {
+        matches!(
+            self,
+            ProcessState::Starting
+                | ProcessState::Running
+                | ProcessState::Ready
+                | ProcessState::Stopping
+        )
+    }
+}
+
+/// 产品状态，`product_status` 命令的返回值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductStatus {
+    pub product_id: String,
+    pub installed: bool,
+    pub state: ProcessState,
+    /// 操作系统进程ID
+    pub os_pid: Option<u32>,
+    /// 启动时间（毫秒时间戳）
+    pub started_at: Option<u64>,
+    /// 退出时间（毫秒时间戳）
+    pub exited_at: Option<u64>,
+    /// 最近一次退出码
+    pub exit_code: Option<i32>,
+    /// 当前（或最近一次）运行的日志会话
+    pub log_session: Option<String>,
+    /// 产品 Web 界面地址，来自产品配置或产品输出
+    pub ui_url: Option<String>,
+    /// 本次（或最近一次）运行分配的端口
+    pub port: Option<u16>,
+    /// 本次（或最近一次）运行使用的启动配置
+    pub profile: Option<String>,
+    /// 本次（或最近一次）运行的未使用版本的ID，为空时运行当前使用的版本
+    pub version: Option<String>,
+    /// ToyStudio 本次运行期间产品异常退出的次数
+    pub crash_count: u32,
+    /// 等待自动重启时，计划重启的时间（毫秒时间戳）
+    pub restart_at: Option<u64>,
+}
+
+impl ProductStatus {
+    fn new(product_id: &str) -> Self {
+        Self {
+            product_id: product_id.to_string(),
+            installed: false,
+            state: ProcessState::Stopped,
+            os_pid: None,
+            started_at: None,
+            exited_at: None,
+            exit_code: None,
+            log_session: None,
+            ui_url: None,
+            port: None,
+            profile: None,
+            version: None,
+            crash_count: 0,
+            restart_at: None,
+        }
+    }
+}
+
+/// 产品关闭命令，来自产品配置中对应操作系统的 `shutdown`
+#[derive(Debug, Clone)]
+pub struct ShutdownCommand {
+    pub current_dir: PathBuf,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+/// 停止产品时最终使其退出的步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownOutcome {
+    /// 产品未在运行
+    NotRunning,
+    /// 产品的关闭命令
+    ShutdownCommand,
+    /// 请求进程树退出（SIGTERM / taskkill）
+    Terminated,
+    /// 强制结束进程树（SIGKILL / taskkill /F）
+    Killed,
+}
+
+/// 被管理的进程
+#[derive(Clone)]
+enum ProcessHandle {
+    /// 本次运行启动的子进程
+    Child(Arc<Mutex<Child>>),
+    /// 从PID文件恢复的进程，只有进程ID和启动时间（秒），无法获取退出码和输出
+    Adopted { os_pid: u32, start_time: u64 },
+}
+
+impl ProcessHandle {
+    fn id(&self) -> Option<u32> {
+        match self {
+            ProcessHandle::Child(child) => child.lock().map(|child| child.id()).ok(),
+            ProcessHandle::Adopted { os_pid, .. } => Some(*os_pid),
+        }
+    }
+
+    /// `Ok(None)` 表示仍在运行，`Ok(Some(code))` 表示已退出
+    fn try_wait(&self) -> io::Result<Option<Option<i32>>> {
+        match self {
+            ProcessHandle::Child(child) => {
+                let mut child = child.lock().map_err(|e| io::Error::other(e.to_string()))?;
+                Ok(child.try_wait()?.map(|status| status.code()))
+            }
+            ProcessHandle::Adopted { os_pid, start_time } => {
+                if process_start_time(*os_pid) == Some(*start_time) {
+                    Ok(None)
+                } else {
+                    Ok(Some(None))
+                }
+            }
+        }
+    }
+
+    /// 强制结束进程树
+    fn kill(&self) {
+        if let Some(os_pid) = self.id() {
+            crate::kill_process_tree(os_pid);
+        }
+        if let ProcessHandle::Child(child) = self {
+            if let Ok(mut child) = child.lock() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
+
+/// 等待进程退出，超时返回 `false`；等待期间不长时间持有子进程锁
+fn wait_exit(handle: &ProcessHandle, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !matches!(handle.try_wait(), Ok(None)) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(STOP_POLL_INTERVAL);
+    }
+}
+
+struct SupervisedProcess {
+    handle: Option<ProcessHandle>,
+    /// 产品输出管道的副本，ToyStudio 退出而产品继续运行时交给转发进程
+    relay: Option<OutputRelay>,
+    status: ProductStatus,
+    /// 等待就绪检查结果，期间保持 `Starting`
+    health_pending: bool,
+    /// 就绪检查失败，进程退出后状态为 `Failed`
+    health_failed: bool,
+}
+
+/// 产品进程管理器，由 Tauri managed state 持有
+///
+/// 已安装与运行状态分开记录；子进程由后台线程定期回收，
+/// 进程退出后状态会自动变为 `Stopped` / `Exited` / `Crashed`，并按产品的重启策略自动重启。
+/// 每次状态变化都会向前端发送 `product://state-changed` 事件；
+/// 运行中的进程记录在PID文件中，ToyStudio 重启后可以重新接管。
+pub struct Supervisor {
+    app_handle: AppHandle,
+    installed: Mutex<HashSet<String>>,
+    processes: Mutex<HashMap<String, SupervisedProcess>>,
+    /// ToyStudio 正在退出
+    exiting: AtomicBool,
+    /// 产品连续自动重启的次数
+    restart_attempts: Mutex<HashMap<String, u32>>,
+}
+
+impl Supervisor {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            installed: Mutex::new(HashSet::new()),
+            processes: Mutex::new(HashMap::new()),
+            exiting: AtomicBool::new(false),
+            restart_attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 发送状态变化事件，调用时不能持有 `processes` 锁
+    fn publish(&self, mut status: ProductStatus) {
+        status.installed = self.is_installed(&status.product_id);
+        emit_event(&self.app_handle, PRODUCT_STATE_CHANGED, status);
+    }
+
+    /// 启动后台回收线程
+    pub fn start_reaper(app_handle: AppHandle) {
+        thread::spawn(move || loop {
+            app_handle.state::<Supervisor>().reap();
+            thread::sleep(REAP_INTERVAL);
+        });
+    }
+
+    pub fn mark_installed(&self, product_id: &str) {
+        if let Ok(mut installed) = self.installed.lock() {
+            installed.insert(product_id.to_string());
+        }
+        self.publish(self.status(product_id));
+    }
+
+    pub fn mark_uninstalled(&self, product_id: &str) {
+        if let Ok(mut installed) = self.installed.lock() {
+            installed.remove(product_id);
+        }
+        self.publish(self.status(product_id));
+    }
+
+    pub fn is_installed(&self, product_id: &str) -> bool {
+        self.installed
+            .lock()
+            .map(|installed| installed.contains(product_id))
+            .unwrap_or(false)
+    }
+
+    /// 获取产品状态，未记录的产品视为 `Stopped`
+    pub fn status(&self, product_id: &str) -> ProductStatus {
+        let mut status = self
+            .processes
+            .lock()
+            .ok()
+            .and_then(|processes| processes.get(product_id).map(|p| p.status.clone()))
+            .unwrap_or_else(|| ProductStatus::new(product_id));
+        status.installed = self.is_installed(product_id);
+        status
+    }
+
+    /// 标记产品开始启动，产品已在运行时返回错误
+    ///
+    /// `health_check` 为 `true` 时保持 `Starting`，直到 `mark_ready` 或 `fail_health`。
+    pub fn begin_start(
+        &self,
+        product_id: &str,
+        health_check: bool,
+        port: u16,
+        profile: Option<&str>,
+        version: Option<&str>,
+    ) -> ToyResult<()> {
+        let mut processes = self.processes.lock().map_err(ToyStudioError::internal)?;
+        let mut crash_count = 0;
+        if let Some(process) = processes.get(product_id) {
+            if process.status.state.is_alive() {
+                return Err(ToyStudioError::ProductAlreadyRunning {
+                    product_id: product_id.to_string(),
+                });
+            }
+            crash_count = process.status.crash_count;
+        }
+        let mut status = ProductStatus::new(product_id);
+        status.state = ProcessState::Starting;
+        status.started_at = Some(now_millis());
+        status.port = Some(port);
+        status.profile = profile.map(|profile| profile.to_string());
+        status.version = version.map(|version| version.to_string());
+        status.crash_count = crash_count;
+        processes.insert(
+            product_id.to_string(),
+            SupervisedProcess {
+                handle: None,
+                relay: None,
+                status: status.clone(),
+                health_pending: health_check,
+                health_failed: false,
+            },
+        );
+        drop(processes);
+        self.publish(status);
+        Ok(())
+    }
+
+    /// 关联已创建的子进程及其日志会话
+    pub fn attach(
+        &self,
+        product_id: &str,
+        child: Arc<Mutex<Child>>,
+        log_session: &str,
+        relay: OutputRelay,
+    ) {
+        let os_pid = child.lock().map(|c| c.id()).ok();
+        if let Ok(mut processes) = self.processes.lock() {
+            if let Some(process) = processes.get_mut(product_id) {
+                process.status.os_pid = os_pid;
+                process.status.log_session = Some(log_session.to_string());
+                process.handle = Some(ProcessHandle::Child(child));
+                process.relay = Some(relay);
+            }
+        }
+        self.save_pid_file();
+    }
+
+    /// 运行中产品占用的端口
+    pub fn used_ports(&self) -> Vec<u16> {
+        self.processes
+            .lock()
+            .map(|processes| {
+                processes
+                    .values()
+                    .filter(|process| process.status.state.is_alive())
+                    .filter_map(|process| process.status.port)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 更新运行中产品的 Web 界面地址
+    pub fn set_ui_url(&self, product_id: &str, ui_url: &str) {
+        let status = self.processes.lock().ok().and_then(|mut processes| {
+            let process = processes.get_mut(product_id)?;
+            if !process.status.state.is_alive() || process.status.ui_url.as_deref() == Some(ui_url)
+            {
+                return None;
+            }
+            process.status.ui_url = Some(ui_url.to_string());
+            Some(process.status.clone())
+        });
+        if let Some(status) = status {
+            self.save_pid_file();
+            self.publish(status);
+        }
+    }
+
+    /// 就绪检查通过，`Starting` 变为 `Ready`
+    pub fn mark_ready(&self, product_id: &str) {
+        let status = self.processes.lock().ok().and_then(|mut processes| {
+            let process = processes.get_mut(product_id)?;
+            if process.status.state != ProcessState::Starting {
+                return None;
+            }
+            process.health_pending = false;
+            process.status.state = ProcessState::Ready;
+            Some(process.status.clone())
+        });
+        if let Some(status) = status {
+            self.publish(status);
+        }
+    }
+
+    /// 就绪检查失败，之后停止产品时状态变为 `Failed`
+    pub fn fail_health(&self, product_id: &str) {
+        if let Ok(mut processes) = self.processes.lock() {
+            if let Some(process) = processes.get_mut(product_id) {
+                process.health_pending = false;
+                process.health_failed = true;
+            }
+        }
+    }
+
+    /// 启动失败，恢复为 `Stopped`
+    pub fn fail_start(&self, product_id: &str) {
+        if let Ok(mut processes) = self.processes.lock() {
+            processes.remove(product_id);
+        }
+        self.publish(ProductStatus::new(product_id));
+    }
+
+    /// 用户手动启动产品，重新计算连续重启次数
+    pub fn reset_restart_attempts(&self, product_id: &str) {
+        if let Ok(mut attempts) = self.restart_attempts.lock() {
+            attempts.remove(product_id);
+        }
+    }
+
+    /// 根据产品的重启策略安排自动重启
+    fn schedule_restart(&self, product_id: &str, state: ProcessState, started_at: Option<u64>) {
+        if self.is_exiting() {
+            return;
+        }
+        // 用户已停止或重新启动产品时不重启，也不计入连续重启次数
+        let current = self
+            .processes
+            .lock()
+            .ok()
+            .and_then(|processes| Some(processes.get(product_id)?.status.state));
+        match current {
+            Some(current) if current != ProcessState::Stopped && !current.is_alive() => {}
+            _ => return,
+        }
+        let policy = match RestartPolicy::load(&self.app_handle, product_id) {
+            Ok(policy) => policy,
+            Err(e) => {
+                println!("load restart policy of {} error:{}", product_id, e);
+                return;
+            }
+        };
+        let Ok(mut attempts) = self.restart_attempts.lock() else {
+            return;
+        };
+        let attempt = attempts.entry(product_id.to_string()).or_insert(0);
+        // 稳定运行一段时间后才退出，不算连续重启
+        let uptime = started_at.map(|started_at| now_millis().saturating_sub(started_at));
+        if uptime.is_some_and(|uptime| uptime > RESTART_STABLE_SECS * 1000) {
+            *attempt = 0;
+        }
+        *attempt += 1;
+        let attempt = *attempt;
+        drop(attempts);
+
+        let Some(delay) = policy.restart_delay(state, attempt) else {
+            if policy.mode != RestartMode::Never {
+                println!("product {} will not be restarted ({:?})", product_id, state);
+            }
+            return;
+        };
+        let restart_at = now_millis() + delay.as_millis() as u64;
+        let status = self.processes.lock().ok().and_then(|mut processes| {
+            let process = processes.get_mut(product_id)?;
+            if process.status.state.is_alive() {
+                return None;
+            }
+            process.status.restart_at = Some(restart_at);
+            Some(process.status.clone())
+        });
+        let Some(status) = status else {
+            return;
+        };
+        println!(
+            "restart product {} in {:?} (attempt {})",
+            product_id, delay, attempt
+        );
+        let profile = status.profile.clone();
+        let version = status.version.clone();
+        self.publish(status);
+
+        let app_handle = self.app_handle.clone();
+        let product_id = product_id.to_string();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            let supervisor = app_handle.state::<Supervisor>();
+            if !supervisor.take_restart(&product_id, restart_at) {
+                return;
+            }
+            if let Err(e) = start_product(&app_handle, &supervisor, &product_id, profile, version) {
+                println!("restart product {} error:{}", product_id, e);
+            }
+        });
+    }
+
+    /// 到达计划的重启时间，期间产品未被手动启动或停止时返回 `true`
+    fn take_restart(&self, product_id: &str, restart_at: u64) -> bool {
+        if self.is_exiting() {
+            return false;
+        }
+        let Ok(mut processes) = self.processes.lock() else {
+            return false;
+        };
+        match processes.get_mut(product_id) {
+            Some(process)
+                if process.status.restart_at == Some(restart_at)
+                    && !process.status.state.is_alive() =>
+            {
+                process.status.restart_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 取消等待中的自动重启
+    fn cancel_restart(&self, product_id: &str) {
+        let status = self.processes.lock().ok().and_then(|mut processes| {
+            let process = processes.get_mut(product_id)?;
+            process.status.restart_at.take()?;
+            Some(process.status.clone())
+        });
+        if let Some(status) = status {
+            println!("cancel restart of {}", product_id);
+            self.publish(status);
+        }
+    }
+
+    /// 停止产品进程，返回最终使产品退出的步骤
+    ///
+    /// 依次尝试：产品的关闭命令 -> 请求进程树退出 -> 强制结束进程树，每一步等待一段时间。
+    /// 产品正在等待自动重启时，取消重启。
+    pub fn stop(
+        &self,
+        product_id: &str,
+        shutdown: Option<ShutdownCommand>,
+    ) -> ToyResult<ShutdownOutcome> {
+        self.cancel_restart(product_id);
+        // 进程尚未关联到产品时（启动中）不修改状态，否则没有进程可回收，状态会一直停留在 `Stopping`
+        let stopping = {
+            let mut processes = self.processes.lock().map_err(ToyStudioError::internal)?;
+            match processes.get_mut(product_id) {
+                Some(process) if process.status.state.is_alive() => {
+                    let handle = process
+                        .handle
+                        .clone()
+                        .and_then(|handle| handle.id().map(|id| (handle, id)));
+                    handle.map(|handle| {
+                        process.status.state = ProcessState::Stopping;
+                        (handle, process.status.clone())
+                    })
+                }
+                _ => None,
+            }
+        };
+        let Some(((handle, os_pid), status)) = stopping else {
+            self.reap();
+            return Ok(ShutdownOutcome::NotRunning);
+        };
+        self.publish(status);
+
+        let outcome = if Self::run_shutdown_command(product_id, shutdown)
+            && wait_exit(&handle, SHUTDOWN_COMMAND_GRACE)
+        {
+            ShutdownOutcome::ShutdownCommand
+        } else {
+            crate::terminate_process_tree(os_pid);
+            if wait_exit(&handle, TERMINATE_GRACE) {
+                ShutdownOutcome::Terminated
+            } else {
+                handle.kill();
+                ShutdownOutcome::Killed
+            }
+        };
+        println!("product {} stopped by {:?}", product_id, outcome);
+
+        self.reap();
+        Ok(outcome)
+    }
+
+    /// 执行产品的关闭命令，返回命令是否执行成功
+    fn run_shutdown_command(product_id: &str, shutdown: Option<ShutdownCommand>) -> bool {
+        let Some(shutdown) = shutdown else {
+            return false;
+        };
+        println!("run shutdown command for {}:{:?}", product_id, shutdown);
+
+        let mut command = Command::new(&shutdown.program);
+        command
+            .current_dir(&shutdown.current_dir)
+            .args(&shutdown.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+
+        let child = match command.spawn() {
+            Ok(child) => Arc::new(Mutex::new(child)),
+            Err(e) => {
+                println!("shutdown command {} error:{}", product_id, e);
+                return false;
+            }
+        };
+        if !wait_exit(
+            &ProcessHandle::Child(child.clone()),
+            SHUTDOWN_COMMAND_TIMEOUT,
+        ) {
+            println!("shutdown command {} timeout", product_id);
+            if let Ok(mut child) = child.lock() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            return false;
+        }
+        child
+            .lock()
+            .ok()
+            .and_then(|mut child| child.try_wait().ok().flatten())
+            .is_some_and(|status| status.success())
+    }
+
+    /// 强制结束所有产品的进程树，ToyStudio 退出时调用
+    pub fn kill_all(&self) {
+        let handles: Vec<ProcessHandle> = match self.processes.lock() {
+            Ok(processes) => processes
+                .values()
+                .filter_map(|process| process.handle.clone())
+                .collect(),
+            Err(_) => return,
+        };
+        for handle in handles {
+            if let Ok(None) = handle.try_wait() {
+                handle.kill();
+            }
+        }
+        self.reap();
+    }
+
+    /// 运行中的产品ID
+    pub fn running_products(&self) -> Vec<String> {
+        self.processes
+            .lock()
+            .map(|processes| {
+                processes
+                    .values()
+                    .filter(|process| process.status.state.is_alive())
+                    .map(|process| process.status.product_id.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn is_exiting(&self) -> bool {
+        self.exiting.load(Ordering::SeqCst)
+    }
+
+    /// 标记 ToyStudio 开始退出，已在退出时返回 `false`
+    pub fn begin_exit(&self) -> bool {
+        !self.exiting.swap(true, Ordering::SeqCst)
+    }
+
+    /// 放弃管理运行中的产品，ToyStudio 退出后产品继续运行
+    ///
+    /// PID文件保持不变，下次启动时由 `recover` 重新接管。产品的输出管道交给转发进程，
+    /// 之后的输出继续追加到当前日志会话（不再轮转，也不再触发输出监听和事件）；
+    /// 转发进程启动失败时，产品之后写输出可能因管道关闭而出错。
+    pub fn detach_all(&self) {
+        let Ok(mut processes) = self.processes.lock() else {
+            return;
+        };
+        for process in processes.values_mut() {
+            if let Some(os_pid) = process.handle.take().and_then(|handle| handle.id()) {
+                crate::detach_process_tree(os_pid);
+            }
+            if let Some(relay) = process.relay.take() {
+                relay.spawn();
+            }
+        }
+    }
+
+    /// 根据PID文件处理上次运行遗留的产品进程
+    ///
+    /// 进程仍在运行时：退出策略为 `StopAll` 则结束进程树，否则重新接管为 `Running`。
+    pub fn recover(&self, policy: ExitPolicy) {
+        let Some(path) = self.pid_file_path() else {
+            return;
+        };
+        let mut adopted = Vec::new();
+        for record in read_pid_file(&path) {
+            let Some(start_time) = process_start_time(record.os_pid) else {
+                continue;
+            };
+            if start_time.abs_diff(record.started_at / 1000) > PID_REUSE_TOLERANCE_SECS {
+                println!(
+                    "pid {} of {} has been reused, skip",
+                    record.os_pid, record.product_id
+                );
+                continue;
+            }
+            if policy == ExitPolicy::StopAll {
+                println!(
+                    "kill leftover product {} pid {}",
+                    record.product_id, record.os_pid
+                );
+                crate::kill_process_tree(record.os_pid);
+                continue;
+            }
+
+            println!("adopt product {} pid {}", record.product_id, record.os_pid);
+            let mut status = ProductStatus::new(&record.product_id);
+            status.state = ProcessState::Running;
+            status.os_pid = Some(record.os_pid);
+            status.started_at = Some(record.started_at);
+            status.log_session = record.log_session;
+            status.ui_url = record.ui_url;
+            status.port = record.port;
+            status.profile = record.profile;
+            status.version = record.version;
+            adopted.push(status.clone());
+            if let Ok(mut processes) = self.processes.lock() {
+                processes.insert(
+                    record.product_id,
+                    SupervisedProcess {
+                        handle: Some(ProcessHandle::Adopted {
+                            os_pid: record.os_pid,
+                            start_time,
+                        }),
+                        relay: None,
+                        status,
+                        health_pending: false,
+                        health_failed: false,
+                    },
+                );
+            }
+        }
+        self.save_pid_file();
+        for status in adopted {
+            self.publish(status);
+        }
+    }
+
+    fn pid_file_path(&self) -> Option<PathBuf> {
+        AppConfig::get_app_config(&self.app_handle)
+            .map(|app_config| app_config.get_pid_file_path())
+            .ok()
+    }
+
+    /// 将运行中的进程写入PID文件
+    fn save_pid_file(&self) {
+        let Some(path) = self.pid_file_path() else {
+            return;
+        };
+        let records: Vec<PidRecord> = match self.processes.lock() {
+            Ok(processes) => processes
+                .values()
+                .filter(|process| process.status.state.is_alive())
+                .filter_map(|process| {
+                    Some(PidRecord {
+                        product_id: process.status.product_id.clone(),
+                        os_pid: process.status.os_pid?,
+                        started_at: process.status.started_at?,
+                        log_session: process.status.log_session.clone(),
+                        ui_url: process.status.ui_url.clone(),
+                        port: process.status.port,
+                        profile: process.status.profile.clone(),
+                        version: process.status.version.clone(),
+                    })
+                })
+                .collect(),
+            Err(_) => return,
+        };
+        if let Err(e) = write_pid_file(&path, &records) {
+            println!("write pid file error:{}", e);
+        }
+    }
+
+    /// 回收已退出的子进程，并更新状态
+    pub fn reap(&self) {
+        let mut changed = Vec::new();
+        let mut exited = Vec::new();
+        let mut released = Vec::new();
+        let Ok(mut processes) = self.processes.lock() else {
+            return;
+        };
+        for process in processes.values_mut() {
+            let Some(handle) = process.handle.clone() else {
+                continue;
+            };
+            match handle.try_wait() {
+                Ok(None) => {
+                    if process.status.state == ProcessState::Starting && !process.health_pending {
+                        process.status.state = ProcessState::Running;
+                        changed.push(process.status.clone());
+                    }
+                }
+                Ok(Some(code)) => {
+                    let adopted = matches!(handle, ProcessHandle::Adopted { .. });
+                    process.status.state = match (process.status.state, code) {
+                        _ if process.health_failed => ProcessState::Failed,
+                        (ProcessState::Stopping, _) => ProcessState::Stopped,
+                        (_, Some(0)) => ProcessState::Exited(0),
+                        // 接管的进程无法获取退出码
+                        (_, None) if adopted => ProcessState::Stopped,
+                        _ => ProcessState::Crashed,
+                    };
+                    if matches!(
+                        process.status.state,
+                        ProcessState::Crashed | ProcessState::Failed
+                    ) {
+                        process.status.crash_count += 1;
+                    }
+                    process.status.exit_code = code;
+                    process.status.exited_at = Some(now_millis());
+                    process.handle = None;
+                    process.relay = None;
+                    released.extend(process.status.os_pid);
+                    changed.push(process.status.clone());
+                    exited.push((
+                        ProductExitedEvent {
+                            product_id: process.status.product_id.clone(),
+                            state: process.status.state,
+                            exit_code: code,
+                        },
+                        process.status.started_at,
+                    ));
+                }
+                Err(e) => {
+                    println!("reap {} error:{}", process.status.product_id, e);
+                }
+            }
+        }
+        drop(processes);
+
+        // 主进程已退出，清理进程组 / Job Object 中遗留的子进程
+        if !released.is_empty() {
+            for os_pid in released {
+                crate::release_process_tree(os_pid);
+            }
+            self.save_pid_file();
+        }
+        for status in changed {
+            self.publish(status);
+        }
+        for (event, started_at) in exited {
+            let (product_id, state) = (event.product_id.clone(), event.state);
+            emit_event(&self.app_handle, PRODUCT_EXITED, event);
+            self.schedule_restart(&product_id, state, started_at);
+        }
+    }
+}
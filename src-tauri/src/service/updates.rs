@@ -0,0 +1,335 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::{AppConfig, Product, ToyResult, ToyStudioError};
+
+/// 产品的依赖锁文件
+pub const LOCK_FILE: &str = "uv.lock";
+
+/// 用户对产品更新的选择，以及安装时的版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct UpdatePreference {
+    /// 安装或最近一次升级时产品配置中的版本
+    pub installed_version: Option<String>,
+    /// 跳过的远程提交，远程出现更新的提交时再次提示
+    pub skipped_commit: Option<String>,
+    /// 固定的提交，固定期间不提示更新，也不允许升级
+    pub pinned_commit: Option<String>,
+}
+
+impl UpdatePreference {
+    /// 获取更新选择文件路径，与应用配置文件在同一目录
+    pub fn get_preferences_file_path(app_handle: &AppHandle) -> PathBuf {
+        let config_path = AppConfig::get_config_file_path(app_handle);
+        config_path.with_file_name("update_preferences.json")
+    }
+
+    /// 获取所有产品的更新选择：产品ID -> 更新选择
+    pub fn load_all(app_handle: &AppHandle) -> ToyResult<HashMap<String, UpdatePreference>> {
+        let path = Self::get_preferences_file_path(app_handle);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let json = fs::read_to_string(&path).map_err(|e| ToyStudioError::io(&path, e))?;
+        serde_json::from_str(&json).map_err(ToyStudioError::config)
+    }
+
+    /// 获取产品的更新选择
+    pub fn load(app_handle: &AppHandle, product_id: &str) -> ToyResult<UpdatePreference> {
+        let mut preferences = Self::load_all(app_handle)?;
+        Ok(preferences.remove(product_id).unwrap_or_default())
+    }
+
+    /// 保存产品的更新选择
+    pub fn save(
+        app_handle: &AppHandle,
+        product_id: &str,
+        preference: UpdatePreference,
+    ) -> ToyResult<()> {
+        let mut preferences = Self::load_all(app_handle)?;
+        if preference == UpdatePreference::default() {
+            preferences.remove(product_id);
+        } else {
+            preferences.insert(product_id.to_string(), preference);
+        }
+        let path = Self::get_preferences_file_path(app_handle);
+        let json = serde_json::to_string_pretty(&preferences).map_err(ToyStudioError::config)?;
+        fs::write(&path, json).map_err(|e| ToyStudioError::io(&path, e))
+    }
+
+    /// 修改并保存产品的更新选择
+    pub fn update<F>(app_handle: &AppHandle, product_id: &str, f: F) -> ToyResult<()>
+    where
+        F: FnOnce(&mut UpdatePreference),
+    {
+        let mut preference = Self::load(app_handle, product_id)?;
+        f(&mut preference);
+        Self::save(app_handle, product_id, preference)
+    }
+
+    /// 安装、升级成功后记录产品配置中的版本，清除跳过的提交
+    pub fn record_installed(app_handle: &AppHandle, product_id: &str, version: &str) {
+        let result = Self::update(app_handle, product_id, |preference| {
+            preference.installed_version = Some(version.to_string());
+            preference.skipped_commit = None;
+        });
+        if let Err(e) = result {
+            println!("record installed version of {} error:{}", product_id, e);
+        }
+    }
+}
+
+/// 安装、重新安装时检出的版本：用户固定的提交优先，其次是产品配置中的 `commit`、`tag`
+pub fn install_revision(app_handle: &AppHandle, product: &Product) -> Option<String> {
+    let pinned = UpdatePreference::load(app_handle, &product.id)
+        .map(|preference| preference.pinned_commit)
+        .unwrap_or_else(|e| {
+            println!("load update preference of {} error:{}", product.id, e);
+            None
+        });
+    pinned.or_else(|| product.download.revision().map(str::to_string))
+}
+
+/// git 提交摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub author: String,
+    /// 提交时间（毫秒时间戳）
+    pub committed_at: u64,
+    pub summary: String,
+}
+
+/// 锁文件中一个依赖的版本变化，新增的依赖没有 `from`，移除的依赖没有 `to`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockChange {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// 检查更新的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    pub product_id: String,
+    pub branch: String,
+    /// 产品配置中固定的标签或提交，为空时跟随分支
+    pub revision: Option<String>,
+    /// 已安装的提交
+    pub current_commit: String,
+    /// 远程分支最新的提交，产品配置固定了版本时为该版本的提交
+    pub remote_commit: String,
+    /// 安装时产品配置中的版本，未记录时为空
+    pub installed_version: Option<String>,
+    /// 产品配置中当前的版本
+    pub latest_version: String,
+    /// 已安装提交之后的远程提交，最新的在前
+    pub commits: Vec<GitCommit>,
+    /// 依赖锁文件的变化
+    pub lock_changes: Vec<LockChange>,
+    /// 远程的提交与已安装的提交不同，或产品配置中的版本发生变化
+    pub has_update: bool,
+    /// 用户跳过了当前的远程提交
+    pub skipped: bool,
+    /// 用户固定的提交，固定期间不提示更新
+    pub pinned_commit: Option<String>,
+}
+
+/// 解析 uv.lock 中的依赖：名称 -> 版本
+///
+/// 同一依赖按平台或 Python 版本锁定了多个版本时，版本按顺序以 `, ` 连接；
+/// 没有版本的依赖（例如动态版本的产品自身）版本为空。
+fn parse_lock(content: &str) -> BTreeMap<String, String> {
+    #[derive(Deserialize)]
+    struct Lock {
+        #[serde(default)]
+        package: Vec<LockPackage>,
+    }
+    #[derive(Deserialize)]
+    struct LockPackage {
+        name: String,
+        version: Option<String>,
+    }
+
+    match toml::from_str::<Lock>(content) {
+        Ok(lock) => {
+            let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for package in lock.package {
+                versions
+                    .entry(package.name)
+                    .or_default()
+                    .extend(package.version);
+            }
+            versions
+                .into_iter()
+                .map(|(name, mut versions)| {
+                    versions.sort();
+                    versions.dedup();
+                    (name, versions.join(", "))
+                })
+                .collect()
+        }
+        Err(e) => {
+            println!("parse {} error:{}", LOCK_FILE, e);
+            BTreeMap::new()
+        }
+    }
+}
+
+/// 比较两个版本的锁文件，返回版本发生变化、新增和移除的依赖，按名称排列
+pub fn diff_lock(old: Option<&str>, new: Option<&str>) -> Vec<LockChange> {
+    let old = old.map(parse_lock).unwrap_or_default();
+    let new = new.map(parse_lock).unwrap_or_default();
+
+    let mut changes = Vec::new();
+    for (name, version) in &old {
+        match new.get(name) {
+            Some(to) if to == version => {}
+            to => changes.push(LockChange {
+                name: name.clone(),
+                from: Some(version.clone()),
+                to: to.cloned(),
+            }),
+        }
+    }
+    for (name, version) in &new {
+        if !old.contains_key(name) {
+            changes.push(LockChange {
+                name: name.clone(),
+                from: None,
+                to: Some(version.clone()),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(name: &str, from: Option<&str>, to: Option<&str>) -> LockChange {
+        LockChange {
+            name: name.to_string(),
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+        }
+    }
+
+    const OLD_LOCK: &str = r#"
+version = 1
+requires-python = ">=3.10"
+
+[[package]]
+name = "torch"
+version = "2.3.0"
+
+[[package]]
+name = "numpy"
+version = "1.26.4"
+
+[[package]]
+name = "pillow"
+version = "10.3.0"
+
+[[package]]
+name = "demo"
+source = { editable = "." }
+"#;
+
+    const NEW_LOCK: &str = r#"
+version = 1
+requires-python = ">=3.10"
+
+[[package]]
+name = "torch"
+version = "2.4.1"
+
+[[package]]
+name = "numpy"
+version = "1.26.4"
+
+[[package]]
+name = "safetensors"
+version = "0.4.5"
+
+[[package]]
+name = "demo"
+source = { editable = "." }
+"#;
+
+    #[test]
+    fn diff_lock_reports_changed_added_and_removed_packages_by_name() {
+        assert_eq!(
+            diff_lock(Some(OLD_LOCK), Some(NEW_LOCK)),
+            vec![
+                change("pillow", Some("10.3.0"), None),
+                change("safetensors", None, Some("0.4.5")),
+                change("torch", Some("2.3.0"), Some("2.4.1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_lock_ignores_unchanged_lock() {
+        assert!(diff_lock(Some(OLD_LOCK), Some(OLD_LOCK)).is_empty());
+    }
+
+    #[test]
+    fn package_without_version_has_empty_version() {
+        let lock = parse_lock(OLD_LOCK);
+        assert_eq!(lock.get("demo").map(String::as_str), Some(""));
+        assert_eq!(
+            diff_lock(None, Some(OLD_LOCK)).first(),
+            Some(&change("demo", None, Some("")))
+        );
+    }
+
+    #[test]
+    fn missing_lock_file_reports_all_packages() {
+        let changes = diff_lock(Some(OLD_LOCK), None);
+        assert_eq!(changes.len(), 4);
+        assert!(changes.iter().all(|change| change.to.is_none()));
+    }
+
+    #[test]
+    fn invalid_lock_file_has_no_packages() {
+        assert!(parse_lock("[[package]\nname =").is_empty());
+        assert!(parse_lock("").is_empty());
+    }
+
+    #[test]
+    fn package_locked_to_several_versions_keeps_all_versions() {
+        let lock = r#"
+[[package]]
+name = "numpy"
+version = "2.1.0"
+resolution-markers = ["python_full_version >= '3.10'"]
+
+[[package]]
+name = "numpy"
+version = "1.26.4"
+resolution-markers = ["python_full_version < '3.10'"]
+"#;
+        assert_eq!(
+            parse_lock(lock).get("numpy").map(String::as_str),
+            Some("1.26.4, 2.1.0")
+        );
+        let upgraded = lock.replace("2.1.0", "2.2.0");
+        assert_eq!(
+            diff_lock(Some(lock), Some(&upgraded)),
+            vec![change(
+                "numpy",
+                Some("1.26.4, 2.1.0"),
+                Some("1.26.4, 2.2.0")
+            )]
+        );
+    }
+}
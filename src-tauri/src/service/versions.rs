@@ -0,0 +1,235 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{git_head_commit, is_git_repository, move_data_paths, ToyResult, ToyStudioError};
+
+/// 产品的一个已安装版本，`product_list_versions` 命令的返回值
+///
+/// 当前使用的版本（active）位于 `apps/<产品>`，其他版本位于 `.local/versions/<产品>/<版本ID>`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledVersion {
+    /// 版本ID：`<产品配置中的版本>-<短提交>`，例如 `1.2.0-a1b2c3d`
+    pub id: String,
+    /// 安装时产品配置中的版本
+    pub version: String,
+    /// 安装的提交
+    pub commit: String,
+    /// 是否为当前使用的版本
+    pub active: bool,
+}
+
+/// 由产品配置中的版本和提交生成版本ID，版本中字母、数字、`.`、`_` 以外的字符替换为 `_`
+pub fn version_id(version: &str, commit: &str) -> String {
+    let version: String = version
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let version = if version.is_empty() {
+        "unknown".to_string()
+    } else {
+        version
+    };
+    format!("{}-{}", version, &commit[..commit.len().min(7)])
+}
+
+/// 从版本ID中取出版本，版本中被替换的字符无法还原，只用于没有版本记录的目录
+fn version_of(id: &str) -> &str {
+    id.rsplit_once('-')
+        .map(|(version, _)| version)
+        .unwrap_or(id)
+}
+
+/// 版本目录中记录产品配置中版本的文件，位于 `.git` 中，不影响工作区，随版本目录一起移动
+fn version_file(dir: &Path) -> PathBuf {
+    dir.join(".git").join("toy-studio-version")
+}
+
+/// 记录版本目录安装时产品配置中的版本
+pub fn write_version_file(dir: &Path, version: &str) -> ToyResult<()> {
+    let path = version_file(dir);
+    fs::write(&path, version).map_err(|e| ToyStudioError::io(&path, e))
+}
+
+/// 版本目录安装时产品配置中的版本，没有记录时从版本ID中取出
+fn read_version(dir: &Path, id: &str) -> String {
+    fs::read_to_string(version_file(dir))
+        .map(|version| version.trim().to_string())
+        .ok()
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| version_of(id).to_string())
+}
+
+/// 当前使用的版本，安装目录不是git仓库时返回 `None`
+pub fn active_version(
+    install_dir: &Path,
+    installed_version: Option<&str>,
+) -> Option<InstalledVersion> {
+    if !is_git_repository(install_dir) {
+        return None;
+    }
+    let commit = git_head_commit(install_dir).ok()?;
+    let version = installed_version.unwrap_or("unknown").to_string();
+    Some(InstalledVersion {
+        id: version_id(&version, &commit),
+        version,
+        commit,
+        active: true,
+    })
+}
+
+/// 产品的所有已安装版本，当前使用的版本在前，其余按版本ID倒序排列
+pub fn list_versions(
+    install_dir: &Path,
+    versions_dir: &Path,
+    installed_version: Option<&str>,
+) -> Vec<InstalledVersion> {
+    let mut versions: Vec<InstalledVersion> = fs::read_dir(versions_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| is_git_repository(entry.path()))
+                .map(|entry| {
+                    let id = entry.file_name().to_string_lossy().to_string();
+                    InstalledVersion {
+                        version: read_version(&entry.path(), &id),
+                        commit: git_head_commit(entry.path()).unwrap_or_default(),
+                        active: false,
+                        id,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort_by(|a, b| b.id.cmp(&a.id));
+    if let Some(active) = active_version(install_dir, installed_version) {
+        versions.insert(0, active);
+    }
+    versions
+}
+
+/// 指定ID的未使用版本的目录
+pub fn find_version(versions_dir: &Path, id: &str) -> Option<PathBuf> {
+    // 只接受目录名，避免访问版本目录之外的路径
+    if id.is_empty() || Path::new(id).file_name() != Some(id.as_ref()) {
+        return None;
+    }
+    let dir = versions_dir.join(id);
+    is_git_repository(&dir).then_some(dir)
+}
+
+/// 切换当前使用的版本
+///
+/// 当前版本移到 `versions_dir/<active_id>`，数据路径移到新的版本中；任一步失败时恢复原状。
+pub fn activate_version(
+    install_dir: &Path,
+    version_dir: &Path,
+    versions_dir: &Path,
+    active_id: Option<&str>,
+    data_paths: &[String],
+) -> ToyResult<()> {
+    println!("activate version {:?}", version_dir);
+    let parked = match active_id {
+        Some(active_id) if install_dir.exists() => {
+            let parked = versions_dir.join(active_id);
+            if parked.exists() {
+                return Err(ToyStudioError::invalid_argument(format!(
+                    "version {} already exists",
+                    active_id
+                )));
+            }
+            fs::create_dir_all(versions_dir).map_err(|e| ToyStudioError::io(versions_dir, e))?;
+            move_data_paths(install_dir, version_dir, data_paths)?;
+            if let Err(e) = fs::rename(install_dir, &parked) {
+                let _ = move_data_paths(version_dir, install_dir, data_paths);
+                return Err(ToyStudioError::io(install_dir, e));
+            }
+            Some(parked)
+        }
+        _ => None,
+    };
+    if install_dir.exists() {
+        // 安装目录不是git仓库（卸载时保留的数据），其中的数据同样移到新的版本中
+        move_data_paths(install_dir, version_dir, data_paths)?;
+        fs::remove_dir_all(install_dir).map_err(|e| ToyStudioError::io(install_dir, e))?;
+    }
+    if let Some(parent) = install_dir.parent() {
+        fs::create_dir_all(parent).map_err(|e| ToyStudioError::io(parent, e))?;
+    }
+    if let Err(e) = fs::rename(version_dir, install_dir) {
+        if let Some(parked) = &parked {
+            let _ = fs::rename(parked, install_dir);
+            let _ = move_data_paths(version_dir, install_dir, data_paths);
+        }
+        return Err(ToyStudioError::io(version_dir, e));
+    }
+    Ok(())
+}
+
+/// 删除未使用的版本
+pub fn delete_version(version_dir: &Path) -> ToyResult<()> {
+    println!("delete version:{:?}", version_dir);
+    fs::remove_dir_all(version_dir).map_err(|e| ToyStudioError::io(version_dir, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMIT: &str = "a1b2c3d4e5f60718293a4b5c6d7e8f9012345678";
+
+    #[test]
+    fn version_id_uses_short_commit() {
+        assert_eq!(version_id("1.2.0", COMMIT), "1.2.0-a1b2c3d");
+        assert_eq!(version_id(" 1.2.0 ", "a1b2"), "1.2.0-a1b2");
+    }
+
+    #[test]
+    fn version_id_replaces_path_unsafe_characters() {
+        assert_eq!(
+            version_id("1.2.0-rc.1+cu121", COMMIT),
+            "1.2.0_rc.1_cu121-a1b2c3d"
+        );
+        assert_eq!(version_id("../v1/2", COMMIT), ".._v1_2-a1b2c3d");
+        assert_eq!(version_id("", COMMIT), "unknown-a1b2c3d");
+    }
+
+    #[test]
+    fn version_of_strips_commit() {
+        assert_eq!(version_of("1.2.0-a1b2c3d"), "1.2.0");
+        assert_eq!(version_of("1.2.0_rc.1-a1b2c3d"), "1.2.0_rc.1");
+        assert_eq!(version_of("no_commit"), "no_commit");
+    }
+
+    #[test]
+    fn read_version_prefers_version_file() {
+        let dir =
+            std::env::temp_dir().join(format!("toy-studio-version-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join(".git")).unwrap();
+
+        let id = version_id("1.2.0-rc.1+cu121", COMMIT);
+        assert_eq!(read_version(&dir, &id), "1.2.0_rc.1_cu121");
+        write_version_file(&dir, "1.2.0-rc.1+cu121").unwrap();
+        assert_eq!(read_version(&dir, &id), "1.2.0-rc.1+cu121");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn find_version_rejects_paths_outside_versions_dir() {
+        let versions_dir = Path::new("versions");
+        for id in ["", ".", "..", "../other", "a/b", "/abs"] {
+            assert_eq!(find_version(versions_dir, id), None, "{id}");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use regex::Regex;
+use tauri::{AppHandle, Manager};
+use url::Url;
+
+use crate::{template_replace_single, LineWatcher, Supervisor};
+
+lazy_static! {
+    /// 产品输出中的本机地址，例如 Gradio 的 `Running on local URL:  http://127.0.0.1:7860`
+    static ref LOCAL_URL_REGEX: Regex = Regex::new(
+        r#"https?://(?:127\.0\.0\.1|localhost|0\.0\.0\.0|\[::1?\])(?::\d+)?[^\s'"<>]*"#
+    )
+    .unwrap();
+}
+
+/// 根据产品配置的 `web_url` 模板和识别到的地址得到 Web 界面地址
+///
+/// - 模板中的 `${port}` 使用识别到的端口替换
+/// - 没有模板时直接使用识别到的地址，`0.0.0.0` 换成 `127.0.0.1`
+/// - 模板中仍有无法替换的变量时返回 `None`
+pub fn resolve_ui_url(template: Option<&str>, detected: Option<&str>) -> Option<String> {
+    let detected = detected
+        .and_then(|url| Url::parse(url).ok())
+        .map(|mut url| {
+            if url.host_str() == Some("0.0.0.0") {
+                let _ = url.set_host(Some("127.0.0.1"));
+            }
+            url
+        });
+
+    match (template, detected) {
+        (Some(template), detected) => {
+            let url = match detected
+                .as_ref()
+                .and_then(|url| url.port_or_known_default())
+            {
+                Some(port) => template_replace_single(template, "port", &port.to_string()),
+                None => template.to_string(),
+            };
+            if url.contains("${") {
+                None
+            } else {
+                Some(url)
+            }
+        }
+        (None, Some(detected)) => Some(detected.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// 识别产品输出中的本机地址，识别到第一个地址后更新产品的 Web 界面地址
+pub fn ui_url_watcher(
+    app_handle: &AppHandle,
+    product_id: &str,
+    template: Option<String>,
+) -> LineWatcher {
+    let app_handle = app_handle.clone();
+    let product_id = product_id.to_string();
+    let found = AtomicBool::new(false);
+    Arc::new(move |line| {
+        if found.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(detected) = LOCAL_URL_REGEX.find(&line.line) else {
+            return;
+        };
+        if let Some(url) = resolve_ui_url(template.as_deref(), Some(detected.as_str())) {
+            found.store(true, Ordering::Relaxed);
+            println!("product {} web ui:{}", product_id, url);
+            app_handle
+                .state::<Supervisor>()
+                .set_ui_url(&product_id, &url);
+        }
+    })
+}
@@ -1,4 +1,7 @@
-use crate::{common::AppConfig, components::AdminRouter};
+use crate::{
+    common::AppConfig,
+    components::{AdminRouter, ExitDialog},
+};
 use sycamore::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 
@@ -26,5 +29,6 @@ pub fn App() -> View {
 
     view! {
         AdminRouter()
+        ExitDialog()
     }
 }
@@ -1,9 +1,11 @@
+use std::collections::{BTreeMap, HashMap};
+
 use sycamore::prelude::*;
 
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
-use super::{invoke_for_data, invoke_tauri};
+use super::{invoke_command, invoke_for_data, ToyStudioError};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct AppConfig {
@@ -12,6 +14,41 @@ pub struct AppConfig {
     pub enable_external_uv: bool,
     pub uv_cache_dir: String,
     pub dev_mode: Option<bool>,
+    pub exit_policy: Option<ExitPolicy>,
+    pub max_concurrent_jobs: Option<usize>,
+    pub max_backups: Option<usize>,
+    pub clone_depth: Option<u32>,
+    pub partial_clone: Option<bool>,
+    pub clone_retries: Option<u32>,
+    pub git_mirrors: Option<Vec<GitMirror>>,
+}
+
+/// 关闭 ToyStudio 时如何处理运行中的产品，与后端 `ExitPolicy` 保持一致
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitPolicy {
+    #[default]
+    StopAll,
+    Ask,
+    LeaveRunning,
+}
+
+impl ExitPolicy {
+    pub fn value(&self) -> &'static str {
+        match self {
+            ExitPolicy::StopAll => "stop_all",
+            ExitPolicy::Ask => "ask",
+            ExitPolicy::LeaveRunning => "leave_running",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "ask" => ExitPolicy::Ask,
+            "leave_running" => ExitPolicy::LeaveRunning,
+            _ => ExitPolicy::StopAll,
+        }
+    }
 }
 
 impl Default for AppConfig {
@@ -22,25 +59,36 @@ impl Default for AppConfig {
             enable_external_uv: false,
             uv_cache_dir: "./cache".to_string(),
             dev_mode: Some(false),
+            exit_policy: Some(ExitPolicy::StopAll),
+            max_concurrent_jobs: Some(1),
+            max_backups: Some(5),
+            clone_depth: None,
+            partial_clone: Some(false),
+            clone_retries: Some(3),
+            git_mirrors: Some(GitMirror::defaults()),
         }
     }
 }
 
 impl AppConfig {
-    pub async fn load() -> Result<Self, String> {
+    /// git镜像或代理，未配置时为默认镜像，与后端一致
+    pub fn git_mirrors(&self) -> Vec<GitMirror> {
+        self.git_mirrors.clone().unwrap_or_else(GitMirror::defaults)
+    }
+
+    pub async fn load() -> Result<Self, ToyStudioError> {
         invoke_for_data::<AppConfig>("get_config", JsValue::NULL).await
     }
 
-    pub async fn save(&self) -> Result<(), String> {
+    pub async fn save(&self) -> Result<(), ToyStudioError> {
         console_log!("AppConfig save: {:?}", self);
-        let config_str = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let config_str = serde_json::to_string(self).map_err(ToyStudioError::client)?;
         let args = serde_wasm_bindgen::to_value(&serde_json::json!({
             "config": config_str
         }))
-        .map_err(|e| e.to_string())?;
-        match invoke_tauri("set_config", args).await {
-            _ => Ok(()),
-        }
+        .map_err(ToyStudioError::client)?;
+        invoke_command("set_config", args).await?;
+        Ok(())
     }
 }
 
@@ -59,6 +107,13 @@ pub struct Product {
     pub category: String,
     pub install: Option<bool>,
     pub running: Option<bool>,
+    pub state: Option<ProcessState>,
+    pub ui_url: Option<String>,
+    pub assigned_port: Option<u16>,
+    pub launch_profiles: Option<Vec<String>>,
+    pub crash_count: Option<u32>,
+    pub last_exit_code: Option<i32>,
+    pub restart_at: Option<u64>,
     pub created_at: String,
     pub updated_at: String,
     pub device_support: DeviceSupport,
@@ -67,20 +122,737 @@ pub struct Product {
     pub windows: Windows,
     pub macos: Macos,
     pub linux: Linux,
+    pub web_url: Option<String>,
     pub publisher: Option<String>,
     pub file_size: Option<i64>,
+    pub data_paths: Option<Vec<String>>,
+    pub backup_at: Option<u64>,
 }
 
 impl Product {
-    pub async fn load_all_products() -> Result<Vec<Product>, String> {
+    pub async fn load_all_products() -> Result<Vec<Product>, ToyStudioError> {
         invoke_for_data::<Vec<Product>>("get_meta_product_list", JsValue::NULL).await
     }
 
-    pub async fn load_installed_products() -> Result<Vec<Product>, String> {
+    pub async fn load_installed_products() -> Result<Vec<Product>, ToyStudioError> {
         invoke_for_data::<Vec<Product>>("get_installed_product_list", JsValue::NULL).await
     }
 }
 
+/// git镜像或代理的类型，与后端 `MirrorKind` 保持一致
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorKind {
+    #[default]
+    Prefix,
+    HostRewrite,
+    HttpProxy,
+}
+
+impl MirrorKind {
+    pub fn value(&self) -> &'static str {
+        match self {
+            MirrorKind::Prefix => "prefix",
+            MirrorKind::HostRewrite => "host_rewrite",
+            MirrorKind::HttpProxy => "http_proxy",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "host_rewrite" => MirrorKind::HostRewrite,
+            "http_proxy" => MirrorKind::HttpProxy,
+            _ => MirrorKind::Prefix,
+        }
+    }
+
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            MirrorKind::Prefix => "https://ghfast.top",
+            MirrorKind::HostRewrite => "https://kkgithub.com",
+            MirrorKind::HttpProxy => "http://127.0.0.1:7890",
+        }
+    }
+}
+
+/// git镜像或代理，与后端 `GitMirror` 保持一致
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GitMirror {
+    pub name: String,
+    pub kind: MirrorKind,
+    pub url: String,
+    pub enabled: bool,
+}
+
+impl GitMirror {
+    pub fn defaults() -> Vec<GitMirror> {
+        vec![GitMirror {
+            name: "ghfast.top".to_string(),
+            kind: MirrorKind::Prefix,
+            url: "https://ghfast.top".to_string(),
+            enabled: true,
+        }]
+    }
+
+    /// 测试镜像的延迟，`mirrors` 可以是尚未保存的镜像列表
+    pub async fn probe(mirrors: &[GitMirror]) -> Result<Vec<MirrorProbe>, ToyStudioError> {
+        let mirrors = serde_json::to_string(mirrors).map_err(ToyStudioError::client)?;
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "mirrors": mirrors }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<MirrorProbe>>("git_probe_mirrors", args).await
+    }
+}
+
+/// 镜像的使用统计，与后端 `MirrorStats` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct MirrorStats {
+    pub successes: u32,
+    pub failures: u32,
+    pub last_used_at: Option<u64>,
+    pub latency_ms: Option<u64>,
+}
+
+impl MirrorStats {
+    /// 所有镜像的统计：镜像地址 -> 统计
+    pub async fn load_all() -> Result<HashMap<String, MirrorStats>, ToyStudioError> {
+        invoke_for_data::<HashMap<String, MirrorStats>>("git_mirror_stats", JsValue::NULL).await
+    }
+}
+
+/// 镜像的测速结果，与后端 `MirrorProbe` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MirrorProbe {
+    pub url: String,
+    pub latency_ms: Option<u64>,
+}
+
+/// 产品的备份，与后端 `Backup` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub id: String,
+    pub created_at: u64,
+    pub size_bytes: u64,
+}
+
+impl Backup {
+    pub async fn load(product_id: &str) -> Result<Vec<Backup>, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<Backup>>("product_list_backups", args).await
+    }
+
+    pub async fn restore(product_id: &str, backup_id: &str) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "backupId": backup_id,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("product_restore_backup", args).await?;
+        Ok(())
+    }
+
+    pub async fn delete(product_id: &str, backup_id: &str) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "backupId": backup_id,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("product_delete_backup", args).await?;
+        Ok(())
+    }
+}
+
+/// git 提交摘要，与后端 `GitCommit` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GitCommit {
+    pub hash: String,
+    pub author: String,
+    pub committed_at: u64,
+    pub summary: String,
+}
+
+/// 依赖的版本变化，与后端 `LockChange` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockChange {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// 检查更新的结果，与后端 `UpdateCheck` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct UpdateCheck {
+    pub product_id: String,
+    pub branch: String,
+    pub revision: Option<String>,
+    pub current_commit: String,
+    pub remote_commit: String,
+    pub installed_version: Option<String>,
+    pub latest_version: String,
+    pub commits: Vec<GitCommit>,
+    pub lock_changes: Vec<LockChange>,
+    pub has_update: bool,
+    pub skipped: bool,
+    pub pinned_commit: Option<String>,
+}
+
+impl UpdateCheck {
+    pub async fn load(product_id: &str) -> Result<UpdateCheck, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<UpdateCheck>("product_check_update", args).await
+    }
+
+    /// 升级到远程最新的提交，加入后台任务队列
+    pub async fn apply(&self) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": self.product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_command("product_upgrade", args).await?;
+        Ok(())
+    }
+
+    /// 跳过远程当前的提交
+    pub async fn skip(&self) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": self.product_id,
+            "commit": self.remote_commit,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("product_skip_update", args).await?;
+        Ok(())
+    }
+
+    /// 固定或取消固定当前安装的版本
+    pub async fn set_pinned(&self, pinned: bool) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": self.product_id }))
+            .map_err(ToyStudioError::client)?;
+        let command = if pinned {
+            "product_pin_version"
+        } else {
+            "product_unpin_version"
+        };
+        invoke_command(command, args).await?;
+        Ok(())
+    }
+}
+
+/// 产品的已安装版本，与后端 `InstalledVersion` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct InstalledVersion {
+    pub id: String,
+    pub version: String,
+    pub commit: String,
+    pub active: bool,
+}
+
+impl InstalledVersion {
+    pub async fn load(product_id: &str) -> Result<Vec<InstalledVersion>, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<InstalledVersion>>("product_list_versions", args).await
+    }
+
+    /// 与当前版本并行安装最新版本，加入后台任务队列
+    pub async fn install(product_id: &str) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_command("product_install_version", args).await?;
+        Ok(())
+    }
+
+    pub async fn activate(product_id: &str, version_id: &str) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "versionId": version_id,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("product_activate_version", args).await?;
+        Ok(())
+    }
+
+    pub async fn delete(product_id: &str, version_id: &str) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "versionId": version_id,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("product_delete_version", args).await?;
+        Ok(())
+    }
+
+    /// 删除所有未使用的版本
+    pub async fn prune(product_id: &str) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_command("product_prune_versions", args).await?;
+        Ok(())
+    }
+}
+
+/// 提交的短哈希
+pub fn short_commit(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
+
+/// 卸载方式，与后端 `UninstallMode` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UninstallMode {
+    #[default]
+    KeepData,
+    RemoveAll,
+}
+
+/// 产品启动配置，与后端 `LaunchProfile` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct LaunchProfile {
+    pub name: String,
+    pub args: String,
+    pub envs: BTreeMap<String, String>,
+    pub working_dir: Option<String>,
+}
+
+impl LaunchProfile {
+    pub async fn load(product_id: &str) -> Result<Vec<LaunchProfile>, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<LaunchProfile>>("get_launch_profiles", args).await
+    }
+
+    pub async fn save(product_id: &str, profiles: &[LaunchProfile]) -> Result<(), ToyStudioError> {
+        let profiles = serde_json::to_string(profiles).map_err(ToyStudioError::client)?;
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "profiles": profiles,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("set_launch_profiles", args).await?;
+        Ok(())
+    }
+}
+
+/// 产品退出后是否自动重启，与后端 `RestartMode` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartMode {
+    pub fn value(&self) -> &'static str {
+        match self {
+            RestartMode::Never => "never",
+            RestartMode::OnFailure => "on_failure",
+            RestartMode::Always => "always",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "on_failure" => RestartMode::OnFailure,
+            "always" => RestartMode::Always,
+            _ => RestartMode::Never,
+        }
+    }
+}
+
+/// 产品的自动重启策略，与后端 `RestartPolicy` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    pub max_retries: u32,
+    pub backoff_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            max_retries: 3,
+            backoff_secs: 2,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub async fn load(product_id: &str) -> Result<RestartPolicy, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<RestartPolicy>("get_restart_policy", args).await
+    }
+
+    pub async fn save(&self, product_id: &str) -> Result<(), ToyStudioError> {
+        let policy = serde_json::to_string(self).map_err(ToyStudioError::client)?;
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "policy": policy,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_command("set_restart_policy", args).await?;
+        Ok(())
+    }
+}
+
+/// 产品进程状态，与后端 `ProcessState` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "state", content = "code")]
+pub enum ProcessState {
+    Stopped,
+    Starting,
+    Running,
+    Ready,
+    Failed,
+    Stopping,
+    Crashed,
+    Exited(i32),
+}
+
+impl ProcessState {
+    pub fn is_alive(&self) -> bool {
+        matches!(
+            self,
+            ProcessState::Starting
+                | ProcessState::Running
+                | ProcessState::Ready
+                | ProcessState::Stopping
+        )
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            ProcessState::Stopped => "Stopped".to_string(),
+            ProcessState::Starting => "Starting".to_string(),
+            ProcessState::Running => "Running".to_string(),
+            ProcessState::Ready => "Ready".to_string(),
+            ProcessState::Failed => "Failed".to_string(),
+            ProcessState::Stopping => "Stopping".to_string(),
+            ProcessState::Crashed => "Crashed".to_string(),
+            ProcessState::Exited(code) => format!("Exited ({})", code),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProductStatus {
+    pub product_id: String,
+    pub installed: bool,
+    pub state: ProcessState,
+    pub os_pid: Option<u32>,
+    pub started_at: Option<u64>,
+    pub exited_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub log_session: Option<String>,
+    pub ui_url: Option<String>,
+    pub port: Option<u16>,
+    pub profile: Option<String>,
+    pub version: Option<String>,
+    pub crash_count: u32,
+    pub restart_at: Option<u64>,
+}
+
+impl ProductStatus {
+    pub async fn load(product_id: &str) -> Result<ProductStatus, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<ProductStatus>("product_status", args).await
+    }
+}
+
+/// 停止产品时最终使其退出的步骤，与后端 `ShutdownOutcome` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownOutcome {
+    NotRunning,
+    ShutdownCommand,
+    Terminated,
+    Killed,
+}
+
+impl ShutdownOutcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShutdownOutcome::NotRunning => "not running",
+            ShutdownOutcome::ShutdownCommand => "shutdown command",
+            ShutdownOutcome::Terminated => "terminated",
+            ShutdownOutcome::Killed => "force killed",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProductExitedEvent {
+    pub product_id: String,
+    pub state: ProcessState,
+    pub exit_code: Option<i32>,
+}
+
+/// 产品进程树的一次资源占用采样，与后端 `ProductMetrics` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProductMetrics {
+    pub product_id: String,
+    pub timestamp: u64,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    pub vram_bytes: Option<u64>,
+    pub process_count: usize,
+}
+
+impl ProductMetrics {
+    /// 获取产品本次运行的采样记录
+    pub async fn load(product_id: &str) -> Result<Vec<ProductMetrics>, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<ProductMetrics>>("product_metrics", args).await
+    }
+}
+
+/// 字节数格式化为便于阅读的大小，如 `1.5 GB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStage {
+    Clone,
+    Lfs,
+    CreateVenv,
+    Sync,
+}
+
+impl InstallStage {
+    /// 安装步骤，按执行顺序
+    pub const ALL: [InstallStage; 4] = [
+        InstallStage::Clone,
+        InstallStage::Lfs,
+        InstallStage::CreateVenv,
+        InstallStage::Sync,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InstallStage::Clone => "Cloning repository",
+            InstallStage::Lfs => "Downloading LFS files",
+            InstallStage::CreateVenv => "Creating virtual environment",
+            InstallStage::Sync => "Installing dependencies",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStageStatus {
+    Started,
+    Finished,
+    Failed,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct InstallProgressEvent {
+    pub product_id: String,
+    pub stage: InstallStage,
+    pub status: InstallStageStatus,
+    pub message: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub log_session: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct InstallFinishedEvent {
+    pub product_id: String,
+    pub log_session: String,
+    pub error: Option<ToyStudioError>,
+}
+
+/// 后台任务类型，与后端 `JobKind` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Install,
+    Reinstall,
+    Upgrade,
+    InstallVersion,
+    PullLfs,
+}
+
+impl JobKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobKind::Install => "Install",
+            JobKind::Reinstall => "Reinstall",
+            JobKind::Upgrade => "Upgrade",
+            JobKind::InstallVersion => "Install new version of",
+            JobKind::PullLfs => "Download model files of",
+        }
+    }
+}
+
+/// 后台任务状态，与后端 `JobState` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Succeeded | JobState::Failed | JobState::Cancelled
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobState::Queued => "Queued",
+            JobState::Running => "Running",
+            JobState::Succeeded => "Succeeded",
+            JobState::Failed => "Failed",
+            JobState::Cancelled => "Cancelled",
+        }
+    }
+}
+
+/// 安装、重新安装、升级的后台任务，与后端 `Job` 保持一致
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub id: u64,
+    pub product_id: String,
+    pub kind: JobKind,
+    pub state: JobState,
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub log_session: Option<String>,
+    pub error: Option<ToyStudioError>,
+}
+
+impl Job {
+    pub async fn load_all() -> Result<Vec<Job>, ToyStudioError> {
+        invoke_for_data::<Vec<Job>>("job_list", JsValue::NULL).await
+    }
+
+    pub async fn cancel(id: u64) -> Result<(), ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "id": id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_command("job_cancel", args).await?;
+        Ok(())
+    }
+
+    pub async fn clear_finished() -> Result<(), ToyStudioError> {
+        invoke_command("job_clear_finished", JsValue::NULL).await?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// 产品输出的一行日志
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub product_id: String,
+    pub session: String,
+    pub timestamp: u64,
+    pub stream: LogStream,
+    pub line: String,
+}
+
+impl LogLine {
+    /// 读取产品日志，`session` 为空时读取最新会话
+    pub async fn load(
+        product_id: &str,
+        session: Option<&str>,
+        tail: Option<usize>,
+        since: Option<u64>,
+    ) -> Result<Vec<LogLine>, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+            "pid": product_id,
+            "session": session,
+            "tail": tail,
+            "since": since,
+        }))
+        .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<LogLine>>("product_get_logs", args).await
+    }
+
+    /// 获取产品的日志会话，最新的在前
+    pub async fn load_sessions(product_id: &str) -> Result<Vec<String>, ToyStudioError> {
+        let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "pid": product_id }))
+            .map_err(ToyStudioError::client)?;
+        invoke_for_data::<Vec<String>>("product_list_log_sessions", args).await
+    }
+
+    /// 根据内容推断日志级别，Python 的 logging 默认输出到 stderr，所以不能只看输出流
+    pub fn level(&self) -> LogLevel {
+        let line = self.line.as_str();
+        if line.contains("ERROR")
+            || line.contains("CRITICAL")
+            || line.contains("Traceback")
+            || line.contains("Exception")
+        {
+            LogLevel::Error
+        } else if line.contains("WARN") {
+            LogLevel::Warn
+        } else if line.contains("DEBUG") {
+            LogLevel::Debug
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+/// 日志级别，用于日志页面过滤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn value(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        match value {
+            "info" => LogLevel::Info,
+            "warn" => LogLevel::Warn,
+            "error" => LogLevel::Error,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct DeviceSupport {
     pub cpu: bool,
@@ -98,9 +870,62 @@ pub struct Requirements {
 pub struct Download {
     pub git_url: String,
     pub branch: String,
+    pub tag: Option<String>,
+    pub commit: Option<String>,
+    pub submodules: Option<bool>,
+    pub lfs: Option<LfsMode>,
     pub python_version: String,
 }
 
+/// 产品配置中的 `lfs`，与后端 `LfsMode` 保持一致：`true`、`false` 或 `"lazy"`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "LfsValue", into = "LfsValue")]
+pub enum LfsMode {
+    Enabled,
+    Disabled,
+    Lazy,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LfsValue {
+    Bool(bool),
+    Mode(String),
+}
+
+impl TryFrom<LfsValue> for LfsMode {
+    type Error = String;
+
+    fn try_from(value: LfsValue) -> Result<Self, Self::Error> {
+        match value {
+            LfsValue::Bool(true) => Ok(LfsMode::Enabled),
+            LfsValue::Bool(false) => Ok(LfsMode::Disabled),
+            LfsValue::Mode(mode) if mode == "lazy" => Ok(LfsMode::Lazy),
+            LfsValue::Mode(mode) => Err(format!("invalid lfs value {}", mode)),
+        }
+    }
+}
+
+impl From<LfsMode> for LfsValue {
+    fn from(mode: LfsMode) -> Self {
+        match mode {
+            LfsMode::Enabled => LfsValue::Bool(true),
+            LfsMode::Disabled => LfsValue::Bool(false),
+            LfsMode::Lazy => LfsValue::Mode("lazy".to_string()),
+        }
+    }
+}
+
+impl LfsMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LfsMode::Enabled => "Downloaded on install",
+            LfsMode::Disabled => "Not downloaded",
+            LfsMode::Lazy => "Downloaded on first launch",
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Windows {
     pub startup: String,
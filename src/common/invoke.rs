@@ -1,32 +1,301 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sycamore::prelude::*;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+use super::short_commit;
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], js_name = listen, catch)]
+    async fn listen(event: &str, handler: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = writeText, catch)]
+    async fn write_text(text: &str) -> Result<JsValue, JsValue>;
+}
+
+/// 产品状态变化事件，负载为 `ProductStatus`
+pub const PRODUCT_STATE_CHANGED: &str = "product://state-changed";
+/// 产品进程退出事件，负载为 `ProductExitedEvent`
+pub const PRODUCT_EXITED: &str = "product://exited";
+/// 产品安装进度事件，负载为 `InstallProgressEvent`
+pub const PRODUCT_INSTALL_PROGRESS: &str = "product://install-progress";
+/// 产品安装结束事件，负载为 `InstallFinishedEvent`
+pub const PRODUCT_INSTALL_FINISHED: &str = "product://install-finished";
+/// 产品输出的一行日志，负载为 `LogLine`
+pub const PRODUCT_LOG_LINE: &str = "product://log-line";
+/// 产品资源占用采样事件，负载为 `ProductMetrics`
+pub const PRODUCT_METRICS: &str = "product://metrics";
+/// 后台任务状态变化事件，负载为 `Job`
+pub const JOB_UPDATED: &str = "job://updated";
+/// 关闭窗口时有产品在运行，需要用户确认，负载为运行中的产品ID列表
+pub const APP_EXIT_REQUESTED: &str = "app://exit-requested";
+
+/// 后端命令返回的错误，与 `src-tauri` 中的 `ToyStudioError` 保持一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ToyStudioError {
+    GitNotFound,
+    GitCommandFailed {
+        args: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    GitLfsNotFound,
+    RemoteUrlMismatch {
+        path: String,
+        current_url: String,
+        expected_url: String,
+    },
+    UvNotFound,
+    UvCommandFailed {
+        args: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    ProductNotFound {
+        product_id: String,
+    },
+    ProductAlreadyRunning {
+        product_id: String,
+    },
+    ProductBusy {
+        product_id: String,
+    },
+    BackupNotFound {
+        product_id: String,
+    },
+    VersionNotFound {
+        product_id: String,
+        version: String,
+    },
+    ProductPinned {
+        product_id: String,
+        commit: String,
+    },
+    LfsDownloadPending {
+        product_id: String,
+    },
+    ManifestInvalid {
+        path: String,
+        message: String,
+    },
+    ConfigInvalid {
+        message: String,
+    },
+    Cancelled,
+    ProcessSpawnFailed {
+        program: String,
+        message: String,
+    },
+    Io {
+        path: String,
+        message: String,
+    },
+    UnsupportedOs {
+        os: String,
+    },
+    InvalidArgument {
+        message: String,
+    },
+    Internal {
+        message: String,
+    },
+    /// 前端自身的错误：参数序列化、结果解析失败等
+    Client {
+        message: String,
+    },
+}
+
+impl ToyStudioError {
+    pub fn client<E: ToString>(err: E) -> Self {
+        ToyStudioError::Client {
+            message: err.to_string(),
+        }
+    }
+
+    /// 将 `invoke` 的 reject 值转换为错误，无法识别的错误统一为 `Internal`
+    fn from_js(value: JsValue) -> Self {
+        if let Some(message) = value.as_string() {
+            return ToyStudioError::Internal { message };
+        }
+        serde_wasm_bindgen::from_value::<ToyStudioError>(value.clone()).unwrap_or_else(|_| {
+            ToyStudioError::Internal {
+                message: format!("{:?}", value),
+            }
+        })
+    }
+}
+
+impl fmt::Display for ToyStudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToyStudioError::GitNotFound => write!(f, "Git is not installed or not in PATH"),
+            ToyStudioError::GitCommandFailed { args, stderr, .. } => {
+                write!(f, "git {} failed: {}", args, stderr)
+            }
+            ToyStudioError::GitLfsNotFound => write!(
+                f,
+                "Git LFS is not installed; install git-lfs to download the model files of this app"
+            ),
+            ToyStudioError::RemoteUrlMismatch {
+                current_url,
+                expected_url,
+                ..
+            } => write!(
+                f,
+                "Remote url mismatch: current {}, expected {}",
+                current_url, expected_url
+            ),
+            ToyStudioError::UvNotFound => write!(f, "uv is not installed or not in PATH"),
+            ToyStudioError::UvCommandFailed { args, stderr, .. } => {
+                write!(f, "uv {} failed: {}", args, stderr)
+            }
+            ToyStudioError::ProductNotFound { product_id } => {
+                write!(f, "Product {} not found", product_id)
+            }
+            ToyStudioError::ProductAlreadyRunning { product_id } => {
+                write!(f, "Product {} is already running", product_id)
+            }
+            ToyStudioError::ProductBusy { product_id } => {
+                write!(f, "Product {} is busy with another operation", product_id)
+            }
+            ToyStudioError::BackupNotFound { product_id } => {
+                write!(f, "No backup found for product {}", product_id)
+            }
+            ToyStudioError::VersionNotFound {
+                product_id,
+                version,
+            } => write!(f, "Version {} of product {} not found", version, product_id),
+            ToyStudioError::ProductPinned { product_id, commit } => {
+                write!(
+                    f,
+                    "Product {} is pinned to commit {}, unpin it to upgrade",
+                    product_id,
+                    short_commit(commit)
+                )
+            }
+            ToyStudioError::LfsDownloadPending { product_id } => write!(
+                f,
+                "Downloading model files of {}, launch it again when the download finishes",
+                product_id
+            ),
+            ToyStudioError::ManifestInvalid { path, message } => {
+                write!(f, "Invalid product manifest {}: {}", path, message)
+            }
+            ToyStudioError::ConfigInvalid { message } => write!(f, "Invalid config: {}", message),
+            ToyStudioError::Cancelled => write!(f, "Operation cancelled"),
+            ToyStudioError::ProcessSpawnFailed { program, message } => {
+                write!(f, "Failed to start {}: {}", program, message)
+            }
+            ToyStudioError::Io { path, message } => write!(f, "{}: {}", path, message),
+            ToyStudioError::UnsupportedOs { os } => write!(f, "Unsupported OS: {}", os),
+            ToyStudioError::InvalidArgument { message } => {
+                write!(f, "Invalid argument: {}", message)
+            }
+            ToyStudioError::Internal { message } => write!(f, "{}", message),
+            ToyStudioError::Client { message } => write!(f, "{}", message),
+        }
+    }
 }
 
 pub async fn invoke_tauri(cmd: &str, args: JsValue) -> JsValue {
-    invoke(cmd, args).await
+    match invoke(cmd, args).await {
+        Ok(value) => value,
+        Err(err) => err,
+    }
+}
+
+/// 调用后端命令，失败时返回 `ToyStudioError`
+pub async fn invoke_command(cmd: &str, args: JsValue) -> Result<JsValue, ToyStudioError> {
+    invoke(cmd, args).await.map_err(ToyStudioError::from_js)
 }
 
-pub async fn invoke_for_data<P: DeserializeOwned>(cmd: &str, args: JsValue) -> Result<P, String> {
-    let result: JsValue = invoke(cmd, args).await;
+pub async fn invoke_for_data<P: DeserializeOwned>(
+    cmd: &str,
+    args: JsValue,
+) -> Result<P, ToyStudioError> {
+    let result: JsValue = invoke_command(cmd, args).await?;
     let result_str = result.as_string();
 
     if let Some(result_str) = result_str {
         let value = serde_json::from_str::<P>(&result_str);
         match value {
             Ok(value) => Ok(value),
-            Err(e) => Err(e.to_string()),
+            Err(e) => Err(ToyStudioError::client(e)),
         }
     } else {
-        Err("Failed to convert result to string".to_string())
+        Err(ToyStudioError::client("Failed to convert result to string"))
     }
 }
 
 pub async fn invoke_for_string(cmd: &str, args: JsValue) -> Option<String> {
-    let result: JsValue = invoke(cmd, args).await;
+    let result: JsValue = invoke_command(cmd, args).await.ok()?;
     result.as_string()
 }
+
+/// 复制文本到剪贴板
+pub async fn copy_to_clipboard(text: &str) -> Result<(), ToyStudioError> {
+    write_text(text)
+        .await
+        .map(|_| ())
+        .map_err(|e| ToyStudioError::client(format!("{:?}", e)))
+}
+
+#[derive(Deserialize)]
+struct TauriEvent<T> {
+    payload: T,
+}
+
+/// 订阅后端事件，组件销毁时自动取消订阅
+///
+/// 必须在组件内调用；负载无法反序列化时忽略该事件。
+pub fn use_tauri_event<T, F>(event: &'static str, mut callback: F)
+where
+    T: DeserializeOwned + 'static,
+    F: FnMut(T) + 'static,
+{
+    let handler = Closure::<dyn FnMut(JsValue)>::new(move |value: JsValue| {
+        match serde_wasm_bindgen::from_value::<TauriEvent<T>>(value) {
+            Ok(data) => callback(data.payload),
+            Err(e) => console_log!("event {} parse error: {:?}", event, e),
+        }
+    })
+    .into_js_value();
+
+    let unlisten = Rc::new(RefCell::new(None::<js_sys::Function>));
+    let disposed = Rc::new(Cell::new(false));
+
+    spawn_local({
+        let unlisten = unlisten.clone();
+        let disposed = disposed.clone();
+        async move {
+            match listen(event, &handler).await {
+                Ok(f) => {
+                    let f: js_sys::Function = f.unchecked_into();
+                    if disposed.get() {
+                        let _ = f.call0(&JsValue::NULL);
+                    } else {
+                        *unlisten.borrow_mut() = Some(f);
+                    }
+                }
+                Err(e) => console_log!("listen {} error: {:?}", event, e),
+            }
+        }
+    });
+
+    on_cleanup(move || {
+        disposed.set(true);
+        if let Some(f) = unlisten.borrow_mut().take() {
+            let _ = f.call0(&JsValue::NULL);
+        }
+    });
+}
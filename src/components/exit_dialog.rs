@@ -0,0 +1,74 @@
+use sycamore::{futures::spawn_local, prelude::*};
+
+use crate::common::{invoke_command, use_tauri_event, APP_EXIT_REQUESTED};
+
+/// 关闭窗口时有产品在运行，询问用户是否停止产品
+#[component]
+pub fn ExitDialog() -> View {
+    let running = create_signal(Vec::<String>::new());
+    let exiting = create_signal(false);
+
+    use_tauri_event(APP_EXIT_REQUESTED, move |products: Vec<String>| {
+        running.set(products);
+    });
+
+    let handle_exit = move |stop_products: bool| {
+        exiting.set(true);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+                "stopProducts": stop_products
+            }));
+            if let Ok(args) = args {
+                if let Err(e) = invoke_command("app_exit", args).await {
+                    console_log!("app_exit error: {}", e);
+                    exiting.set(false);
+                }
+            }
+        });
+    };
+
+    view! {
+        (if running.with(|running| running.is_empty()) {
+            view! {}
+        } else {
+            view! {
+                div(class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-[9998]") {
+                    div(class="bg-white rounded-lg shadow-xl w-full max-w-md p-6") {
+                        h3(class="text-lg font-semibold text-gray-800 mb-2") { "Apps are still running" }
+                        p(class="text-sm text-gray-600 mb-2") {
+                            "The following apps are running. Stop them before exiting?"
+                        }
+                        ul(class="list-disc list-inside text-sm text-gray-700 mb-4") {
+                            Indexed(
+                                list=running,
+                                view=|product_id| view! { li { (product_id) } }
+                            )
+                        }
+                        (if exiting.get() {
+                            view! {
+                                p(class="text-sm text-gray-500") { "Exiting..." }
+                            }
+                        } else {
+                            view! {
+                                div(class="flex justify-end space-x-2") {
+                                    button(
+                                        class="px-4 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                                        on:click=move |_| running.set(Vec::new())
+                                    ) { "Cancel" }
+                                    button(
+                                        class="px-4 py-2 text-sm rounded-md bg-blue-500 text-white hover:bg-blue-600",
+                                        on:click=move |_| handle_exit(false)
+                                    ) { "Leave running" }
+                                    button(
+                                        class="px-4 py-2 text-sm rounded-md bg-red-500 text-white hover:bg-red-600",
+                                        on:click=move |_| handle_exit(true)
+                                    ) { "Stop all and exit" }
+                                }
+                            }
+                        })
+                    }
+                }
+            }
+        })
+    }
+}
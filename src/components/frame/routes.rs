@@ -1,7 +1,9 @@
 use crate::components::{
-    pages::{app_store_page::AppStorePage, dashboard_page::*},
     file_page::FilePage,
+    logs_page::LogsPage,
+    pages::{app_store_page::AppStorePage, dashboard_page::*},
     settings_page::SettingsPage,
+    tasks_page::TasksPage,
 };
 use sycamore::prelude::*;
 use sycamore_router::{HistoryIntegration, Route, Router};
@@ -16,6 +18,10 @@ pub enum AdminRoute {
     Settings,
     #[to("/file")]
     File,
+    #[to("/logs")]
+    Logs,
+    #[to("/tasks")]
+    Tasks,
     #[not_found]
     NotFound,
 }
@@ -40,6 +46,12 @@ pub fn AdminRouter() -> View {
                         AdminRoute::File => view! {
                             FilePage()
                         },
+                        AdminRoute::Logs => view! {
+                            LogsPage()
+                        },
+                        AdminRoute::Tasks => view! {
+                            TasksPage()
+                        },
                         AdminRoute::NotFound => view! { "404 Not Found" },
                     })
                 }
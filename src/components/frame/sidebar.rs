@@ -11,6 +11,8 @@ pub fn SidebarLi(text: String, img: String, activate: bool, route: AdminRoute) -
             AdminRoute::AppStore => "/appstore",
             AdminRoute::Settings => "/settings",
             AdminRoute::File => "/file",
+            AdminRoute::Logs => "/logs",
+            AdminRoute::Tasks => "/tasks",
             AdminRoute::NotFound => "/404",
         };
         navigate(path);
@@ -41,6 +43,8 @@ pub fn AdminSidebar(current_route: AdminRoute) -> View {
     let current_route_clone1 = current_route.clone();
     let current_route_clone2 = current_route.clone();
     let current_route_clone3 = current_route.clone();
+    let current_route_clone4 = current_route.clone();
+    let current_route_clone5 = current_route.clone();
 
     view! {
         nav(class="flex flex-col min-h-screen justify-between bg-white shadow-lg border-r pt-1 border-gray-200") {
@@ -67,6 +71,20 @@ pub fn AdminSidebar(current_route: AdminRoute) -> View {
                         activate=current_route_clone2==AdminRoute::File,
                         route=AdminRoute::File
                     )
+
+                    SidebarLi(
+                        text="Logs".to_string(),
+                        img="/public/log.svg".to_string(),
+                        activate=current_route_clone4==AdminRoute::Logs,
+                        route=AdminRoute::Logs
+                    )
+
+                    SidebarLi(
+                        text="Tasks".to_string(),
+                        img="/public/tasks.svg".to_string(),
+                        activate=current_route_clone5==AdminRoute::Tasks,
+                        route=AdminRoute::Tasks
+                    )
                 }
             }
 
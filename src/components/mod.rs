@@ -1,6 +1,8 @@
+pub mod exit_dialog;
 pub mod pages;
 pub mod toast;
 pub mod frame;
+pub use exit_dialog::*;
 pub use pages::*;
 pub use toast::*;
 pub use frame::*;
\ No newline at end of file
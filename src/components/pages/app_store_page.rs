@@ -1,12 +1,168 @@
 use sycamore::futures::spawn_local;
 use sycamore::prelude::*;
 
-use crate::common::invoke_tauri;
+use crate::common::invoke_command;
+use crate::common::invoke_for_data;
+use crate::common::use_tauri_event;
 use crate::common::Product;
 use crate::common::TabType;
+use crate::common::ToyStudioError;
+use crate::common::{
+    InstallFinishedEvent, InstallProgressEvent, InstallStage, InstallStageStatus, Job, JobState,
+    LogLine, ProductStatus, JOB_UPDATED, PRODUCT_INSTALL_FINISHED, PRODUCT_INSTALL_PROGRESS,
+    PRODUCT_LOG_LINE, PRODUCT_STATE_CHANGED,
+};
 use crate::components::toast::{Toast, ToastNotification, ToastType};
 use crate::components::{AdminLayout, AdminRoute};
 
+/// 安装进度弹出框中保留的日志行数
+const MAX_INSTALL_LOG_LINES: usize = 200;
+
+/// 安装步骤的进度
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InstallStep {
+    stage: InstallStage,
+    /// 尚未开始时为空
+    status: Option<InstallStageStatus>,
+    duration_ms: Option<u64>,
+    message: Option<String>,
+}
+
+/// 当前安装任务的进度
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InstallJob {
+    /// 后台任务ID，加入任务队列后获得
+    job_id: Option<u64>,
+    /// 是否在任务队列中等待
+    queued: bool,
+    product_id: String,
+    product_name: String,
+    steps: Vec<InstallStep>,
+    log_session: Option<String>,
+    logs: Vec<String>,
+    finished: bool,
+    error: Option<ToyStudioError>,
+}
+
+impl InstallJob {
+    fn new(product_id: &str, product_name: &str) -> Self {
+        Self {
+            job_id: None,
+            queued: false,
+            product_id: product_id.to_string(),
+            product_name: product_name.to_string(),
+            steps: InstallStage::ALL
+                .iter()
+                .map(|stage| InstallStep {
+                    stage: *stage,
+                    status: None,
+                    duration_ms: None,
+                    message: None,
+                })
+                .collect(),
+            log_session: None,
+            logs: Vec::new(),
+            finished: false,
+            error: None,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        !self.finished
+    }
+
+    /// 同步后台任务的状态，等待中被取消的任务不会发送安装结束事件，在这里结束
+    fn apply_job(&mut self, job: &Job) {
+        self.job_id = Some(job.id);
+        self.queued = job.state == JobState::Queued;
+        if job.log_session.is_some() {
+            self.log_session = job.log_session.clone();
+        }
+        if job.state == JobState::Cancelled && !self.finished {
+            self.finish(Some(ToyStudioError::Cancelled));
+        }
+    }
+
+    fn apply_progress(&mut self, event: InstallProgressEvent) {
+        self.log_session = Some(event.log_session);
+        if let Some(step) = self.steps.iter_mut().find(|step| step.stage == event.stage) {
+            step.status = Some(event.status);
+            step.duration_ms = event.duration_ms;
+            step.message = event.message;
+        }
+    }
+
+    fn push_log(&mut self, line: String) {
+        if self.logs.len() >= MAX_INSTALL_LOG_LINES {
+            self.logs.remove(0);
+        }
+        self.logs.push(line);
+    }
+
+    fn finish(&mut self, error: Option<ToyStudioError>) {
+        self.finished = true;
+        self.error = error;
+    }
+}
+
+/// 安装失败时提示给用户的信息
+fn install_error_message(error: &ToyStudioError) -> String {
+    match error {
+        ToyStudioError::GitNotFound => "Git is not installed, please install git first".to_string(),
+        ToyStudioError::UvNotFound => "uv is not installed, please install uv first".to_string(),
+        ToyStudioError::ProductBusy { .. } => "Product is already being installed".to_string(),
+        ToyStudioError::Cancelled => "Installation cancelled".to_string(),
+        ToyStudioError::RemoteUrlMismatch {
+            path, current_url, ..
+        } => format!(
+            "Directory {} already contains another repository ({})",
+            path, current_url
+        ),
+        ToyStudioError::UvCommandFailed { args, stderr, .. } => format!(
+            "Failed to prepare python environment (uv {}): {}",
+            args, stderr
+        ),
+        e => format!("Failed to setup product: {}", e),
+    }
+}
+
+/// 毫秒时间戳格式化为本地日期时间
+fn format_date_time(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp as f64));
+    String::from(date.to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED))
+}
+
+/// 安装步骤的进度行
+fn install_step_view(step: InstallStep) -> View {
+    let (icon, icon_class) = match step.status {
+        None => ("○", "text-gray-300"),
+        Some(InstallStageStatus::Started) => ("◌", "text-blue-500 animate-spin"),
+        Some(InstallStageStatus::Finished) => ("✓", "text-green-600"),
+        Some(InstallStageStatus::Failed) => ("✗", "text-red-600"),
+    };
+    let label_class = match step.status {
+        None => "flex-1 text-sm text-gray-400",
+        Some(InstallStageStatus::Failed) => "flex-1 text-sm text-red-600 font-medium",
+        _ => "flex-1 text-sm text-gray-700",
+    };
+    let label = step.stage.label();
+    let duration = step
+        .duration_ms
+        .map(|ms| format!("{:.1}s", ms as f64 / 1000.0))
+        .unwrap_or_default();
+    let message = step.message.unwrap_or_default();
+    view! {
+        li(class="py-1") {
+            div(class="flex items-center space-x-2") {
+                span(class=format!("inline-block w-4 text-center {}", icon_class)) { (icon) }
+                span(class=label_class) { (label) }
+                span(class="text-xs text-gray-400") { (duration) }
+            }
+            p(class="ml-6 text-xs text-red-600 break-all") { (message) }
+        }
+    }
+}
+
 #[component]
 pub fn AppStorePage() -> View {
     let apps = create_signal(Vec::<Product>::new());
@@ -14,6 +170,8 @@ pub fn AppStorePage() -> View {
     let selected_app = create_signal(None::<Product>);
     let active_tab = create_signal(TabType::Description);
     let toast = create_signal(None::<Toast>);
+    let install_job = create_signal(None::<InstallJob>);
+    let show_install_modal = create_signal(false);
 
     spawn_local({
         let apps = apps.clone();
@@ -32,6 +190,112 @@ pub fn AppStorePage() -> View {
         }
     });
 
+    use_tauri_event(
+        PRODUCT_INSTALL_PROGRESS,
+        move |event: InstallProgressEvent| {
+            install_job.update(|job| match job {
+                Some(job) if job.product_id == event.product_id => job.apply_progress(event),
+                _ => {}
+            });
+        },
+    );
+
+    // 任务加入队列、开始和取消
+    use_tauri_event(JOB_UPDATED, move |job: Job| {
+        let matches = install_job.with(|current| {
+            current
+                .as_ref()
+                .is_some_and(|current| match current.job_id {
+                    Some(id) => id == job.id,
+                    None => current.product_id == job.product_id,
+                })
+        });
+        if !matches {
+            return;
+        }
+        let cancelled = install_job.with(|current| {
+            current
+                .as_ref()
+                .is_some_and(|current| !current.finished && job.state == JobState::Cancelled)
+        });
+        install_job.update(|current| {
+            if let Some(current) = current {
+                current.apply_job(&job);
+            }
+        });
+        // 执行中的任务取消后会发送安装结束事件，只提示等待中被取消的任务
+        if cancelled && job.started_at.is_none() {
+            toast.set(Some(Toast {
+                message: install_error_message(&ToyStudioError::Cancelled),
+                toast_type: ToastType::Error,
+            }));
+        }
+    });
+
+    // 安装命令的输出
+    use_tauri_event(PRODUCT_LOG_LINE, move |line: LogLine| {
+        let matches = install_job.with(|job| {
+            job.as_ref()
+                .is_some_and(|job| job.log_session.as_deref() == Some(line.session.as_str()))
+        });
+        if matches {
+            install_job.update(|job| {
+                if let Some(job) = job {
+                    job.push_log(line.line);
+                }
+            });
+        }
+    });
+
+    use_tauri_event(
+        PRODUCT_INSTALL_FINISHED,
+        move |event: InstallFinishedEvent| {
+            let product_name = install_job.with(|job| match job {
+                Some(job) if job.product_id == event.product_id => Some(job.product_name.clone()),
+                _ => None,
+            });
+            let Some(product_name) = product_name else {
+                return;
+            };
+            match &event.error {
+                None => toast.set(Some(Toast {
+                    message: format!("{} installed successfully", product_name),
+                    toast_type: ToastType::Success,
+                })),
+                Some(e) => {
+                    let message = install_error_message(e);
+                    console_log!("install error:{}", message);
+                    toast.set(Some(Toast {
+                        message,
+                        toast_type: ToastType::Error,
+                    }));
+                }
+            }
+            install_job.update(|job| {
+                if let Some(job) = job {
+                    job.finish(event.error);
+                }
+            });
+        },
+    );
+
+    // 安装状态变化时同步列表和弹出框
+    use_tauri_event(PRODUCT_STATE_CHANGED, move |status: ProductStatus| {
+        let mut products = apps.get_clone();
+        if let Some(app) = products.iter_mut().find(|app| app.id == status.product_id) {
+            if app.install != Some(status.installed) {
+                app.install = Some(status.installed);
+                apps.set(products);
+            }
+        }
+        if let Some(mut app) = selected_app.get_clone() {
+            if app.id == status.product_id && app.install != Some(status.installed) {
+                app.install = Some(status.installed);
+                selected_app.set(Some(app));
+            }
+        }
+    });
+
     let open_app_info = move |app: Product| {
         selected_app.set(Some(app));
         show_modal.set(true);
@@ -42,41 +306,83 @@ pub fn AppStorePage() -> View {
         show_modal.set(false);
     };
 
+    // 安装在后台执行，进度通过事件更新
     let handle_install = move |app: Product| {
-        let toast = toast.clone();
+        install_job.set(Some(InstallJob::new(&app.id, &app.name)));
+        show_install_modal.set(true);
         spawn_local(async move {
             let json = serde_json::json!({
                 "pid": app.id.clone()
             });
-            let args = serde_wasm_bindgen::to_value(&json);
-            match args {
+            let result = match serde_wasm_bindgen::to_value(&json) {
+                Ok(args) => invoke_for_data::<Job>("product_install", args).await,
+                Err(e) => Err(ToyStudioError::client(e)),
+            };
+            match result {
+                Ok(job) => install_job.update(|current| {
+                    if let Some(current) = current {
+                        current.apply_job(&job);
+                    }
+                }),
                 Err(e) => {
-                    console_log!("Failed to parse response: {:?}", e);
+                    let message = install_error_message(&e);
+                    console_log!("install error:{}", message);
                     toast.set(Some(Toast {
-                        message: format!("Failed to parse request: {}", e),
+                        message,
                         toast_type: ToastType::Error,
                     }));
-                }
-                Ok(args) => {
-                    console_log!("args:{:?}", args);
-                    let result = invoke_tauri("product_install", args).await;
-                    match result.as_string() {
-                        None => {
-                            console_log!("Failed to get product list");
-                            toast.set(Some(Toast {
-                                message: "Failed to setup product".to_string(),
-                                toast_type: ToastType::Error,
-                            }));
+                    install_job.update(|job| {
+                        if let Some(job) = job {
+                            job.finish(Some(e));
                         }
-                        Some(raw) => {
-                            console_log!("raw:{}", raw);
-                            toast.set(Some(Toast {
-                                message: "Product installed successfully".to_string(),
-                                toast_type: ToastType::Success,
-                            }));
+                    });
+                }
+            }
+        });
+    };
+
+    // 从最近一次卸载的备份恢复
+    let handle_restore = move |app: Product| {
+        spawn_local(async move {
+            let json = serde_json::json!({
+                "pid": app.id.clone()
+            });
+            let result = match serde_wasm_bindgen::to_value(&json) {
+                Ok(args) => invoke_command("product_restore", args).await,
+                Err(e) => Err(ToyStudioError::client(e)),
+            };
+            match result {
+                Ok(_) => {
+                    toast.set(Some(Toast {
+                        message: format!("{} restored from backup", app.name),
+                        toast_type: ToastType::Success,
+                    }));
+                    if let Ok(products) = Product::load_all_products().await {
+                        if let Some(product) = products.iter().find(|product| product.id == app.id)
+                        {
+                            selected_app.set(Some(product.clone()));
                         }
+                        apps.set(products);
                     }
                 }
+                Err(e) => toast.set(Some(Toast {
+                    message: format!("Failed to restore {}: {}", app.name, e),
+                    toast_type: ToastType::Error,
+                })),
+            }
+        });
+    };
+
+    let handle_cancel_install = move |_| {
+        let Some(job_id) = install_job.with(|job| job.as_ref().and_then(|job| job.job_id)) else {
+            return;
+        };
+        spawn_local(async move {
+            if let Err(e) = Job::cancel(job_id).await {
+                toast.set(Some(Toast {
+                    message: format!("Failed to cancel installation: {}", e),
+                    toast_type: ToastType::Error,
+                }));
             }
         });
     };
@@ -129,6 +435,7 @@ pub fn AppStorePage() -> View {
                 div(class="relative mx-auto p-5 border w-11/12 md:w-3/4 lg:w-1/2 shadow-lg rounded-md bg-white/90 backdrop-blur-md") {
                     (if let Some(app) = selected_app.get_clone() {
                         let app_clone = app.clone();
+                        let app_id = app.id.clone();
                         let app_version = app.version.clone();
                         view! {
                             // Modal header
@@ -292,6 +599,31 @@ pub fn AppStorePage() -> View {
 
                                         p(class="text-sm font-medium text-gray-500 mt-2") { "Branch" }
                                         p(class="text-gray-600") { (app.download.branch) }
+
+                                        (match app.download.commit.clone().or(app.download.tag.clone()) {
+                                            Some(revision) => view! {
+                                                p(class="text-sm font-medium text-gray-500 mt-2") { "Pinned Revision" }
+                                                p(class="text-gray-600 break-all") { (revision) }
+                                            },
+                                            None => view! {},
+                                        })
+
+                                        (if app.download.submodules == Some(true) {
+                                            view! {
+                                                p(class="text-sm font-medium text-gray-500 mt-2") { "Submodules" }
+                                                p(class="text-gray-600") { "Cloned with the app" }
+                                            }
+                                        } else {
+                                            view! {}
+                                        })
+
+                                        (match app.download.lfs {
+                                            Some(lfs) => view! {
+                                                p(class="text-sm font-medium text-gray-500 mt-2") { "Model Files (Git LFS)" }
+                                                p(class="text-gray-600") { (lfs.label()) }
+                                            },
+                                            None => view! {},
+                                        })
                                     }
                                 }
 
@@ -302,14 +634,53 @@ pub fn AppStorePage() -> View {
                             }
 
                             // Modal footer
-                            div(class="mt-6 flex justify-end space-x-3 border-t pt-3") {
+                            div(class="mt-6 flex justify-end items-center space-x-3 border-t pt-3") {
+                                (if install_job.with(|job| job.as_ref().is_some_and(|job| job.product_id == app_id)) {
+                                    view! {
+                                        button(
+                                            class="flex-1 text-left text-sm text-blue-600 hover:underline",
+                                            on:click=move |_| show_install_modal.set(true)
+                                        ) {
+                                            "Show install progress"
+                                        }
+                                    }
+                                } else {
+                                    view! {}
+                                })
                                 button(on:click=close_modal, class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300") {
                                     "Close"
                                 }
                                 (if let Some(true) = app.install {
                                     view! {}
+                                } else if install_job.with(|job| job.as_ref().is_some_and(|job| job.product_id == app_clone.id && job.is_running())) {
+                                    view! {
+                                        button(
+                                            disabled=true,
+                                            class="px-4 py-2 bg-blue-300 text-white rounded cursor-not-allowed"
+                                        ) {
+                                            "Installing..."
+                                        }
+                                    }
                                 } else {
+                                    let app_clone = app_clone.clone();
+                                    let restore_view = match app_clone.backup_at {
+                                        Some(backup_at) => {
+                                            let app = app_clone.clone();
+                                            let title = format!("Backup from {}", format_date_time(backup_at));
+                                            view! {
+                                                button(
+                                                    title=title,
+                                                    on:click=move |_| handle_restore(app.clone()),
+                                                    class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300"
+                                                ) {
+                                                    "Restore backup"
+                                                }
+                                            }
+                                        }
+                                        None => view! {},
+                                    };
                                     view! {
+                                        (restore_view)
                                         button(
                                             on:click=move |_| {
                                                 let app = app_clone.clone();
@@ -329,6 +700,60 @@ pub fn AppStorePage() -> View {
                     })
                 }
             }
+
+            // Install Progress Modal
+            (match install_job.get_clone() {
+                Some(job) if show_install_modal.get() => {
+                    let title = if job.queued {
+                        format!("Waiting to install {}", job.product_name)
+                    } else if job.is_running() {
+                        format!("Installing {}", job.product_name)
+                    } else if job.error == Some(ToyStudioError::Cancelled) {
+                        format!("Installation of {} cancelled", job.product_name)
+                    } else if job.error.is_some() {
+                        format!("Failed to install {}", job.product_name)
+                    } else {
+                        format!("{} installed", job.product_name)
+                    };
+                    let cancellable = job.is_running() && job.job_id.is_some();
+                    let close_label = if job.finished { "Close" } else { "Hide" };
+                    let logs = job.logs.join("\n");
+                    let steps = job.steps.into_iter().map(install_step_view).collect::<Vec<_>>();
+                    view! {
+                        div(class="fixed inset-0 bg-gray-900/40 backdrop-blur-sm h-full w-full z-50 flex items-center justify-center") {
+                            div(class="relative mx-auto p-5 border w-11/12 md:w-3/4 lg:w-1/2 shadow-lg rounded-md bg-white") {
+                                h3(class="text-lg font-semibold text-gray-700 border-b pb-3") { (title) }
+                                ul(class="mt-3") { (steps) }
+                                pre(class="mt-3 h-64 overflow-y-auto bg-gray-900 text-gray-100 text-xs font-mono p-3 rounded-md whitespace-pre-wrap break-all") {
+                                    (logs)
+                                }
+                                div(class="mt-4 flex justify-end space-x-2") {
+                                    (if cancellable {
+                                        view! {
+                                            button(
+                                                class="px-4 py-2 bg-red-500 text-white rounded hover:bg-red-600",
+                                                on:click=handle_cancel_install
+                                            ) {
+                                                "Cancel"
+                                            }
+                                        }
+                                    } else {
+                                        view! {}
+                                    })
+                                    // 隐藏弹出框不影响后台安装
+                                    button(
+                                        class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                        on:click=move |_| show_install_modal.set(false)
+                                    ) {
+                                        (close_label)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => view! {},
+            })
         })
     }
 }
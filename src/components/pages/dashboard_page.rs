@@ -1,20 +1,252 @@
+use std::collections::HashMap;
+
 use sycamore::futures::spawn_local;
 use sycamore::prelude::*;
+use wasm_bindgen::JsValue;
 
-use crate::common::{invoke_for_string, invoke_tauri, Product};
+use crate::common::{
+    format_bytes, invoke_command, invoke_for_string, short_commit, use_tauri_event,
+    InstalledVersion, ProcessState, Product, ProductExitedEvent, ProductMetrics, ProductStatus,
+    ShutdownOutcome, ToyStudioError, UninstallMode, UpdateCheck, PRODUCT_EXITED, PRODUCT_METRICS,
+    PRODUCT_STATE_CHANGED,
+};
 use crate::components::{AdminLayout, AdminRoute, Toast, ToastNotification, ToastType};
 
+/// 卡片上显示的采样数
+const SPARKLINE_SAMPLES: usize = 30;
+
+/// 最近采样的迷你柱状图，`max` 为图表的满格值
+fn sparkline(values: Vec<f32>, max: f32, bar_class: &'static str) -> View {
+    let bars = values
+        .into_iter()
+        .map(|value| {
+            let height = if max > 0.0 {
+                (value / max * 100.0).clamp(4.0, 100.0)
+            } else {
+                4.0
+            };
+            view! {
+                div(class=format!("flex-1 rounded-sm {}", bar_class), style=format!("height: {:.0}%", height))
+            }
+        })
+        .collect::<Vec<_>>();
+    view! {
+        div(class="flex items-end h-5 w-20 gap-px bg-gray-50 rounded-sm") { (bars) }
+    }
+}
+
+/// 毫秒时间戳格式化为本地日期
+fn format_date(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp as f64));
+    String::from(date.to_locale_date_string("default", &JsValue::UNDEFINED))
+}
+
+/// 提交及版本，例如 `a1b2c3d (v1.2.0)`
+fn revision_label(commit: &str, version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!("{} (v{})", short_commit(commit), version),
+        None => short_commit(commit).to_string(),
+    }
+}
+
+/// 检查更新的结果：提交记录和依赖变化
+fn update_details_view(check: &UpdateCheck) -> View {
+    let current = format!(
+        "Installed: {}",
+        revision_label(&check.current_commit, check.installed_version.as_deref())
+    );
+    let source = match &check.revision {
+        Some(revision) => format!("Pinned by manifest to {}", revision),
+        None => format!("Latest on {}", check.branch),
+    };
+    let latest = format!(
+        "{}: {}",
+        source,
+        revision_label(&check.remote_commit, Some(&check.latest_version))
+    );
+    let status = match (&check.pinned_commit, check.has_update, check.skipped) {
+        (Some(commit), _, _) => format!(
+            "Pinned to {}, updates are not offered.",
+            short_commit(commit)
+        ),
+        (None, false, _) => "Up to date.".to_string(),
+        (None, true, true) => "You skipped this update.".to_string(),
+        (None, true, false) if check.commits.is_empty() => {
+            "The remote version differs from the installed one.".to_string()
+        }
+        (None, true, false) => format!("{} new commit(s).", check.commits.len()),
+    };
+
+    let commits = check
+        .commits
+        .iter()
+        .map(|commit| {
+            let hash = short_commit(&commit.hash).to_string();
+            let summary = commit.summary.clone();
+            let meta = format!("{}, {}", commit.author, format_date(commit.committed_at));
+            view! {
+                li(class="text-sm text-gray-700") {
+                    span(class="font-mono text-xs text-gray-500 mr-2") { (hash) }
+                    span { (summary) }
+                    span(class="text-xs text-gray-400 ml-2") { (meta) }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let commits_view = if commits.is_empty() {
+        view! {}
+    } else {
+        view! {
+            div {
+                h4(class="text-sm font-medium text-gray-700 mb-1") { "Commits" }
+                ul(class="max-h-48 overflow-y-auto space-y-1 border border-gray-200 rounded-md p-2") { (commits) }
+            }
+        }
+    };
+
+    let changes = check
+        .lock_changes
+        .iter()
+        .map(|change| {
+            let version = match (&change.from, &change.to) {
+                (Some(from), Some(to)) => format!("{} → {}", from, to),
+                (None, Some(to)) => format!("added {}", to),
+                (Some(from), None) => format!("removed {}", from),
+                (None, None) => String::new(),
+            };
+            let name = change.name.clone();
+            view! {
+                li(class="flex justify-between text-sm text-gray-700") {
+                    span { (name) }
+                    span(class="font-mono text-xs text-gray-500") { (version) }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let changes_view = if changes.is_empty() {
+        view! {}
+    } else {
+        view! {
+            div {
+                h4(class="text-sm font-medium text-gray-700 mb-1") { "Dependency changes (uv.lock)" }
+                ul(class="max-h-48 overflow-y-auto space-y-1 border border-gray-200 rounded-md p-2") { (changes) }
+            }
+        }
+    };
+
+    view! {
+        div(class="space-y-1 text-sm text-gray-600") {
+            p { (current) }
+            p { (latest) }
+            p(class="font-medium text-gray-800") { (status) }
+        }
+        (commits_view)
+        (changes_view)
+    }
+}
+
+/// 运行中产品的 CPU、内存、显存占用
+fn metrics_view(samples: Option<&Vec<ProductMetrics>>) -> View {
+    let Some(samples) = samples.filter(|samples| !samples.is_empty()) else {
+        return view! {};
+    };
+    let recent = &samples[samples.len().saturating_sub(SPARKLINE_SAMPLES)..];
+    let latest = &recent[recent.len() - 1];
+
+    let cpu_label = format!("CPU {:.0}%", latest.cpu_percent);
+    let cpu_chart = sparkline(
+        recent.iter().map(|sample| sample.cpu_percent).collect(),
+        100.0,
+        "bg-blue-400",
+    );
+
+    let memory = recent
+        .iter()
+        .map(|sample| sample.memory_bytes as f32)
+        .collect::<Vec<_>>();
+    let memory_max = memory.iter().cloned().fold(0.0, f32::max);
+    let memory_label = format!("RAM {}", format_bytes(latest.memory_bytes));
+    let memory_chart = sparkline(memory, memory_max, "bg-green-400");
+
+    // 没有 NVIDIA 显卡或无法获取显存时不显示
+    let vram_view = match latest.vram_bytes {
+        Some(vram_bytes) => {
+            let vram = recent
+                .iter()
+                .map(|sample| sample.vram_bytes.unwrap_or(0) as f32)
+                .collect::<Vec<_>>();
+            let vram_max = vram.iter().cloned().fold(0.0, f32::max);
+            let vram_label = format!("VRAM {}", format_bytes(vram_bytes));
+            let vram_chart = sparkline(vram, vram_max, "bg-purple-400");
+            view! {
+                div(class="flex items-center gap-1") {
+                    span { (vram_label) }
+                    (vram_chart)
+                }
+            }
+        }
+        None => view! {},
+    };
+    let title = format!("{} process(es)", latest.process_count);
+
+    view! {
+        div(class="flex items-center gap-4 mt-2 text-xs text-gray-500", title=title) {
+            div(class="flex items-center gap-1") {
+                span { (cpu_label) }
+                (cpu_chart)
+            }
+            div(class="flex items-center gap-1") {
+                span { (memory_label) }
+                (memory_chart)
+            }
+            (vram_view)
+        }
+    }
+}
+
+/// 已安装版本的管理操作
+#[derive(Clone, Copy)]
+enum VersionAction {
+    Install,
+    Activate,
+    Delete,
+    Prune,
+}
+
+/// 检查更新后用户的选择
+#[derive(Clone, Copy)]
+enum UpdateAction {
+    Apply,
+    Skip,
+    Pin,
+    Unpin,
+}
+
 #[component]
 pub fn DashboardPage() -> View {
     let apps = create_signal(Vec::<Product>::new());
     let toast = create_signal(None::<Toast>);
     let menu_open = create_signal(false);
+    // 产品ID -> 本次运行的资源占用采样
+    let metrics = create_signal(HashMap::<String, Vec<ProductMetrics>>::new());
 
     let load_products = async move || {
         let apps = apps.clone();
         match Product::load_installed_products().await {
             Ok(products) => {
+                let running = products
+                    .iter()
+                    .filter(|product| product.running == Some(true))
+                    .map(|product| product.id.clone())
+                    .collect::<Vec<_>>();
                 apps.set(products);
+                for product_id in running {
+                    if let Ok(samples) = ProductMetrics::load(&product_id).await {
+                        metrics.update(|metrics| {
+                            metrics.insert(product_id, samples);
+                        });
+                    }
+                }
             }
             Err(e) => {
                 toast.set(Some(Toast {
@@ -31,11 +263,92 @@ pub fn DashboardPage() -> View {
         load_products().await;
     });
 
-    let handle_launch_product = move |product_id: String| {
+    // 订阅后端产品状态变化，更新对应卡片
+    use_tauri_event(PRODUCT_STATE_CHANGED, move |status: ProductStatus| {
+        // 产品重新启动或已退出时清空采样
+        if status.state == ProcessState::Starting || !status.state.is_alive() {
+            metrics.update(|metrics| {
+                metrics.remove(&status.product_id);
+            });
+        }
+        let mut products = apps.get_clone();
+        match products.iter().position(|app| app.id == status.product_id) {
+            Some(index) if !status.installed => {
+                products.remove(index);
+                apps.set(products);
+            }
+            Some(index) => {
+                let app = &mut products[index];
+                if status.state == ProcessState::Ready && app.state != Some(ProcessState::Ready) {
+                    toast.set(Some(Toast {
+                        message: format!("Product {} is ready", app.name),
+                        toast_type: ToastType::Success,
+                    }));
+                }
+                app.install = Some(true);
+                app.running = Some(status.state.is_alive());
+                app.state = Some(status.state);
+                app.ui_url = status.ui_url;
+                app.assigned_port = status.port;
+                app.crash_count = Some(status.crash_count);
+                app.last_exit_code = status.exit_code;
+                app.restart_at = status.restart_at;
+                apps.set(products);
+            }
+            None if status.installed => {
+                spawn_local(async move {
+                    load_products().await;
+                });
+            }
+            None => {}
+        }
+    });
+
+    use_tauri_event(PRODUCT_METRICS, move |sample: ProductMetrics| {
+        metrics.update(|metrics| {
+            let samples = metrics.entry(sample.product_id.clone()).or_default();
+            if samples.len() >= SPARKLINE_SAMPLES {
+                samples.remove(0);
+            }
+            samples.push(sample);
+        });
+    });
+
+    use_tauri_event(PRODUCT_EXITED, move |event: ProductExitedEvent| {
+        if event.state == ProcessState::Failed {
+            toast.set(Some(Toast {
+                message: format!(
+                    "Product {} did not become ready in time and was stopped",
+                    event.product_id
+                ),
+                toast_type: ToastType::Error,
+            }));
+        } else if event.state == ProcessState::Crashed {
+            toast.set(Some(Toast {
+                message: format!(
+                    "Product {} exited unexpectedly (exit code: {})",
+                    event.product_id,
+                    event
+                        .exit_code
+                        .map(|code| code.to_string())
+                        .unwrap_or_else(|| "N/A".to_string())
+                ),
+                toast_type: ToastType::Error,
+            }));
+        }
+    });
+
+    // `profile` 为空时使用产品默认的启动命令
+    // `version` 为未使用版本的ID，为空时启动当前使用的版本
+    let handle_launch_product = move |product_id: String,
+                                      profile: String,
+                                      version: Option<String>| {
         let toast = toast.clone();
         spawn_local(async move {
             let json = serde_json::json!({
-                "pid": product_id.clone()
+                "pid": product_id.clone(),
+                "profile": profile,
+                "version": version,
             });
             let args = serde_wasm_bindgen::to_value(&json);
 
@@ -48,17 +361,99 @@ pub fn DashboardPage() -> View {
                     }));
                 }
                 Ok(args) => {
-                    let _msg = invoke_tauri("product_startup", args).await;
+                    match invoke_command("product_startup", args).await {
+                        Ok(_) => {
+                            toast.set(Some(Toast {
+                                message: "Product is starting".to_string(),
+                                toast_type: ToastType::Success,
+                            }));
+                        }
+                        Err(ToyStudioError::ProductAlreadyRunning { .. }) => {
+                            toast.set(Some(Toast {
+                                message: "Product is already running".to_string(),
+                                toast_type: ToastType::Error,
+                            }));
+                        }
+                        Err(ToyStudioError::UvNotFound) => {
+                            toast.set(Some(Toast {
+                                message: "uv is not installed, please install uv first".to_string(),
+                                toast_type: ToastType::Error,
+                            }));
+                        }
+                        Err(e) => {
+                            toast.set(Some(Toast {
+                                message: format!("Failed to launch product: {}", e),
+                                toast_type: ToastType::Error,
+                            }));
+                        }
+                    }
+                    load_products().await;
+                }
+            }
+        });
+    };
+
+    let handle_stop_product = move |product_id: String| {
+        let toast = toast.clone();
+        spawn_local(async move {
+            let json = serde_json::json!({
+                "pid": product_id.clone()
+            });
+            let args = serde_wasm_bindgen::to_value(&json);
+
+            match args {
+                Err(e) => {
+                    let message = format!("Failed to parse args: {:?}", e);
                     toast.set(Some(Toast {
-                        message: "Product launched successfully".to_string(),
-                        toast_type: ToastType::Success,
+                        message: message,
+                        toast_type: ToastType::Error,
                     }));
+                }
+                Ok(args) => {
+                    match invoke_command("product_shutdown", args).await {
+                        Ok(outcome) => {
+                            let outcome = outcome
+                                .as_string()
+                                .and_then(|s| serde_json::from_str::<ShutdownOutcome>(&s).ok());
+                            let message = match outcome {
+                                Some(outcome) => {
+                                    format!("Product stopped ({})", outcome.label())
+                                }
+                                None => "Product stopped successfully".to_string(),
+                            };
+                            toast.set(Some(Toast {
+                                message,
+                                toast_type: ToastType::Success,
+                            }));
+                        }
+                        Err(e) => {
+                            toast.set(Some(Toast {
+                                message: format!("Failed to stop product: {}", e),
+                                toast_type: ToastType::Error,
+                            }));
+                        }
+                    }
                     load_products().await;
                 }
             }
         });
     };
 
+    let handle_open_ui = move |product_id: String| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+                "pid": product_id
+            }))
+            .unwrap_or(JsValue::NULL);
+            if let Err(e) = invoke_command("product_open_ui", args).await {
+                toast.set(Some(Toast {
+                    message: format!("Failed to open web UI: {}", e),
+                    toast_type: ToastType::Error,
+                }));
+            }
+        });
+    };
+
     let handle_open_directory = move |product_id: String| {
         let toast = toast.clone();
         menu_open.clone().set(false);
@@ -87,11 +482,16 @@ pub fn DashboardPage() -> View {
         });
     };
 
-    let handle_uninstall = move |product_id: String| {
+    // 待确认卸载的产品
+    let uninstall_target = create_signal(None::<Product>);
+    let uninstall_mode = create_signal(UninstallMode::KeepData);
+
+    let handle_uninstall = move |product_id: String, mode: UninstallMode| {
         let toast = toast.clone();
         spawn_local(async move {
             let json = serde_json::json!({
-                "pid": product_id.clone()
+                "pid": product_id.clone(),
+                "mode": mode,
             });
             let args = serde_wasm_bindgen::to_value(&json);
 
@@ -103,16 +503,17 @@ pub fn DashboardPage() -> View {
                         toast_type: ToastType::Error,
                     }));
                 }
-                Ok(args) => match invoke_for_string("uninstall_product", args).await {
-                    Some(_) => {
+                Ok(args) => match invoke_command("product_uninstall", args).await {
+                    Ok(_) => {
                         toast.set(Some(Toast {
                             message: "Product uninstalled successfully".to_string(),
                             toast_type: ToastType::Success,
                         }));
+                        load_products().await;
                     }
-                    None => {
+                    Err(e) => {
                         toast.set(Some(Toast {
-                            message: "Failed to uninstall product".to_string(),
+                            message: format!("Failed to uninstall product: {}", e),
                             toast_type: ToastType::Error,
                         }));
                     }
@@ -137,16 +538,16 @@ pub fn DashboardPage() -> View {
                         toast_type: ToastType::Error,
                     }));
                 }
-                Ok(args) => match invoke_for_string("reinstall_product", args).await {
-                    Some(_) => {
+                Ok(args) => match invoke_command("product_reinstall", args).await {
+                    Ok(_) => {
                         toast.set(Some(Toast {
-                            message: "Product reinstalled successfully".to_string(),
+                            message: "Reinstall queued, see Tasks for progress".to_string(),
                             toast_type: ToastType::Success,
                         }));
                     }
-                    None => {
+                    Err(e) => {
                         toast.set(Some(Toast {
-                            message: "Failed to reinstall product".to_string(),
+                            message: format!("Failed to reinstall product: {}", e),
                             toast_type: ToastType::Error,
                         }));
                     }
@@ -155,6 +556,109 @@ pub fn DashboardPage() -> View {
         });
     };
 
+    // 检查更新的结果：产品名称和检查结果
+    let update_target = create_signal(None::<(String, UpdateCheck)>);
+
+    let handle_check_update = move |product_id: String, name: String| {
+        menu_open.set(false);
+        spawn_local(async move {
+            match UpdateCheck::load(&product_id).await {
+                Ok(check) => update_target.set(Some((name, check))),
+                Err(e) => toast.set(Some(Toast {
+                    message: format!("Failed to check updates: {}", e),
+                    toast_type: ToastType::Error,
+                })),
+            }
+        });
+    };
+
+    let handle_update_action = move |check: UpdateCheck, action: UpdateAction| {
+        update_target.set(None);
+        spawn_local(async move {
+            let (result, message) = match action {
+                UpdateAction::Apply => (
+                    check.apply().await,
+                    "Upgrade queued, see Tasks for progress",
+                ),
+                UpdateAction::Skip => (check.skip().await, "Update skipped"),
+                UpdateAction::Pin => (check.set_pinned(true).await, "Version pinned"),
+                UpdateAction::Unpin => (check.set_pinned(false).await, "Version unpinned"),
+            };
+            match result {
+                Ok(_) => toast.set(Some(Toast {
+                    message: message.to_string(),
+                    toast_type: ToastType::Success,
+                })),
+                Err(e) => toast.set(Some(Toast {
+                    message: e.to_string(),
+                    toast_type: ToastType::Error,
+                })),
+            }
+        });
+    };
+
+    // 管理已安装版本的产品：产品ID和名称
+    let versions_target = create_signal(None::<(String, String)>);
+    let versions = create_signal(Vec::<InstalledVersion>::new());
+
+    let show_error = move |message: String| {
+        toast.set(Some(Toast {
+            message,
+            toast_type: ToastType::Error,
+        }));
+    };
+
+    let load_versions = move |product_id: String| {
+        spawn_local(async move {
+            match InstalledVersion::load(&product_id).await {
+                Ok(list) => versions.set(list),
+                Err(e) => show_error(format!("Failed to load versions: {}", e)),
+            }
+        });
+    };
+
+    let handle_show_versions = move |product_id: String, name: String| {
+        menu_open.set(false);
+        versions.set(Vec::new());
+        load_versions(product_id.clone());
+        versions_target.set(Some((product_id, name)));
+    };
+
+    let handle_version_action =
+        move |product_id: String, version_id: String, action: VersionAction| {
+            spawn_local(async move {
+                let (result, message) = match action {
+                    VersionAction::Install => (
+                        InstalledVersion::install(&product_id).await,
+                        "New version queued, see Tasks for progress",
+                    ),
+                    VersionAction::Activate => (
+                        InstalledVersion::activate(&product_id, &version_id).await,
+                        "Version activated",
+                    ),
+                    VersionAction::Delete => (
+                        InstalledVersion::delete(&product_id, &version_id).await,
+                        "Version deleted",
+                    ),
+                    VersionAction::Prune => (
+                        InstalledVersion::prune(&product_id).await,
+                        "Inactive versions removed",
+                    ),
+                };
+                match result {
+                    Ok(_) => {
+                        toast.set(Some(Toast {
+                            message: message.to_string(),
+                            toast_type: ToastType::Success,
+                        }));
+                        load_versions(product_id);
+                        load_products().await;
+                    }
+                    Err(e) => show_error(e.to_string()),
+                }
+            });
+        };
+
     let handle_offline_import = move |product_id: String| {
         let toast = toast.clone();
         spawn_local(async move {
@@ -200,16 +704,36 @@ pub fn DashboardPage() -> View {
             div(class="bg-gray-100 p-4") {
                 Keyed(
                     list=apps,
-                    key=|app| app.id.clone(),
+                    // 状态变化时重新渲染卡片
+                    key=|app| format!("{}:{:?}:{:?}", app.id, app.state, app.restart_at),
                     view=move |app|{
                         let app_id = app.id.clone();
                         let app_id_for_menu = app.id.clone();
+                        let app_for_uninstall = app.clone();
+                        let app_name = app.name.clone();
+                        let app_id_for_metrics = app.id.clone();
+                        let app_id_for_ui = app.id.clone();
                         view! {
                             div(class="bg-white rounded-lg shadow-md p-4 hover:shadow-lg transition-shadow ") {
                                 div(class="flex flex-row justify-between items-center gap-4") {
                                     div(class="flex-grow") {
                                         h3(class="text-lg font-semibold text-gray-800") { (app.name) }
                                         p(class="text-sm text-gray-600 mt-1") { (app.description) }
+                                        (match app.crash_count.filter(|count| *count > 0) {
+                                            Some(count) => view! {
+                                                p(class="text-xs text-red-600 mt-1") {
+                                                    (format!(
+                                                        "Crashed {} time(s), last exit code: {}",
+                                                        count,
+                                                        app.last_exit_code
+                                                            .map(|code| code.to_string())
+                                                            .unwrap_or_else(|| "N/A".to_string())
+                                                    ))
+                                                }
+                                            },
+                                            None => view! {},
+                                        })
+                                        (metrics.with(|metrics| metrics_view(metrics.get(&app_id_for_metrics))))
                                     }
                                     div(class="relative") {
                                         button(
@@ -235,8 +759,12 @@ pub fn DashboardPage() -> View {
                                                     button(
                                                         class="w-full text-left px-4 py-2 text-sm text-gray-700 hover:bg-gray-100",
                                                         on:click={
-                                                            let id = app_id_for_menu.clone();
-                                                            move |_| handle_uninstall(id.clone())
+                                                            let app = app_for_uninstall.clone();
+                                                            move |_| {
+                                                                menu_open.set(false);
+                                                                uninstall_mode.set(UninstallMode::KeepData);
+                                                                uninstall_target.set(Some(app.clone()));
+                                                            }
                                                         }
                                                     ) { "Uninstall" }
 
@@ -248,6 +776,24 @@ pub fn DashboardPage() -> View {
                                                         }
                                                     ) { "Reinstall" }
 
+                                                    button(
+                                                        class="w-full text-left px-4 py-2 text-sm text-gray-700 hover:bg-gray-100",
+                                                        on:click={
+                                                            let id = app_id_for_menu.clone();
+                                                            let name = app_name.clone();
+                                                            move |_| handle_check_update(id.clone(), name.clone())
+                                                        }
+                                                    ) { "Check for Updates" }
+
+                                                    button(
+                                                        class="w-full text-left px-4 py-2 text-sm text-gray-700 hover:bg-gray-100",
+                                                        on:click={
+                                                            let id = app_id_for_menu.clone();
+                                                            let name = app_name.clone();
+                                                            move |_| handle_show_versions(id.clone(), name.clone())
+                                                        }
+                                                    ) { "Versions" }
+
                                                     button(
                                                         class="w-full text-left px-4 py-2 text-sm text-gray-700 hover:bg-gray-100",
                                                         on:click={
@@ -267,19 +813,91 @@ pub fn DashboardPage() -> View {
                                             view! {}
                                         })
                                     }
-                                    (if let Some(true) = app.running {
+                                    (match app.state {
+                                        Some(state) if state != ProcessState::Stopped => view! {
+                                            span(class=format!("px-2 py-1 text-xs rounded-full {}", match state {
+                                                ProcessState::Crashed | ProcessState::Failed => "bg-red-100 text-red-800",
+                                                ProcessState::Exited(_) => "bg-gray-100 text-gray-800",
+                                                ProcessState::Starting | ProcessState::Stopping => "bg-yellow-100 text-yellow-800",
+                                                _ => "bg-green-100 text-green-800",
+                                            })) {
+                                                (state.label())
+                                            }
+                                        },
+                                        _ => view! {},
+                                    })
+                                    (if app.restart_at.is_some() {
+                                        view! {
+                                            span(class="px-2 py-1 text-xs rounded-full bg-yellow-100 text-yellow-800") {
+                                                "Restarting..."
+                                            }
+                                        }
+                                    } else {
+                                        view! {}
+                                    })
+                                    (match app.assigned_port {
+                                        Some(port) => view! {
+                                            span(class="px-2 py-1 text-xs rounded-full bg-gray-100 text-gray-600", title="Port") {
+                                                (format!(":{}", port))
+                                            }
+                                        },
+                                        None => view! {},
+                                    })
+                                    (match (app.state, app.ui_url.clone()) {
+                                        (Some(ProcessState::Running | ProcessState::Ready), Some(ui_url)) => {
+                                            let product_id = app_id_for_ui.clone();
+                                            view! {
+                                                button(
+                                                    class="bg-blue-500 hover:bg-blue-600 text-white px-4 py-2 rounded-md text-sm transition-colors",
+                                                    title=ui_url,
+                                                    on:click=move |_| handle_open_ui(product_id.clone())
+                                                ) {
+                                                    "Open"
+                                                }
+                                            }
+                                        }
+                                        _ => view! {},
+                                    })
+                                    // 等待自动重启时，停止即取消重启
+                                    (if app.running == Some(true) || app.restart_at.is_some() {
+                                        let product_id = app_id.clone();
                                         view! {
-                                            div(class="bg-green-500 hover:bg-green-600 text-white px-4 py-2 rounded-md text-sm transition-colors") {
-                                                "Running"
+                                            button(
+                                                class="bg-red-500 hover:bg-red-600 text-white px-4 py-2 rounded-md text-sm transition-colors",
+                                                on:click=move |_| handle_stop_product(product_id.clone())
+                                            ) {
+                                                "Stop"
                                             }
                                         }
                                     } else {
+                                        let profiles = app.launch_profiles.clone().unwrap_or_default();
+                                        let profile = create_signal(String::new());
+                                        let product_id = app_id.clone();
                                         view! {
+                                            (if profiles.is_empty() {
+                                                view! {}
+                                            } else {
+                                                let profiles = profiles.clone();
+                                                view! {
+                                                    select(
+                                                        class="px-2 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                                                        title="Launch profile",
+                                                        bind:value=profile
+                                                    ) {
+                                                        option(value="") { "Default" }
+                                                        (profiles.clone().into_iter().map(|name| {
+                                                            let label = name.clone();
+                                                            view! {
+                                                                option(value=name) { (label) }
+                                                            }
+                                                        }).collect::<Vec<_>>())
+                                                    }
+                                                }
+                                            })
                                             button(
                                                 class="bg-green-500 hover:bg-green-600 text-white px-4 py-2 rounded-md text-sm transition-colors",
                                                 on:click=move |_| {
-                                                    let product_id = app_id.clone();
-                                                    handle_launch_product(product_id);
+                                                    handle_launch_product(product_id.clone(), profile.get_clone(), None);
                                                 }
                                             ) {
                                                 "Launch"
@@ -292,6 +910,202 @@ pub fn DashboardPage() -> View {
                     },
                 )
             }
+
+            // 卸载确认
+            (match uninstall_target.get_clone() {
+                Some(app) => {
+                    let title = format!("Uninstall {}", app.name);
+                    let data_paths = app.data_paths.clone().unwrap_or_default();
+                    let keep_label = if data_paths.is_empty() {
+                        "Keep data (this app declares no data paths)".to_string()
+                    } else {
+                        format!("Keep data: {}", data_paths.join(", "))
+                    };
+                    let product_id = app.id.clone();
+                    view! {
+                        div(class="fixed inset-0 bg-gray-900/40 backdrop-blur-sm h-full w-full z-50 flex items-center justify-center") {
+                            div(class="relative mx-auto p-5 border w-11/12 md:w-1/2 shadow-lg rounded-md bg-white space-y-4") {
+                                h3(class="text-lg font-semibold text-gray-700 border-b pb-3") { (title) }
+                                div(class="space-y-2") {
+                                    label(class="flex items-center space-x-2 text-sm text-gray-700") {
+                                        input(
+                                            r#type="radio",
+                                            name="uninstall-mode",
+                                            checked=uninstall_mode.get() == UninstallMode::KeepData,
+                                            on:change=move |_| uninstall_mode.set(UninstallMode::KeepData)
+                                        )
+                                        span { (keep_label) }
+                                    }
+                                    label(class="flex items-center space-x-2 text-sm text-gray-700") {
+                                        input(
+                                            r#type="radio",
+                                            name="uninstall-mode",
+                                            checked=uninstall_mode.get() == UninstallMode::RemoveAll,
+                                            on:change=move |_| uninstall_mode.set(UninstallMode::RemoveAll)
+                                        )
+                                        span { "Remove everything" }
+                                    }
+                                }
+                                p(class="text-xs text-gray-500") {
+                                    "The app folder is moved to the backup directory and can be restored from the App Store."
+                                }
+                                div(class="flex justify-end space-x-2") {
+                                    button(
+                                        class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                        on:click=move |_| uninstall_target.set(None)
+                                    ) { "Cancel" }
+                                    button(
+                                        class="px-4 py-2 bg-red-500 text-white rounded hover:bg-red-600",
+                                        on:click=move |_| {
+                                            uninstall_target.set(None);
+                                            handle_uninstall(product_id.clone(), uninstall_mode.get());
+                                        }
+                                    ) { "Uninstall" }
+                                }
+                            }
+                        }
+                    }
+                }
+                None => view! {},
+            })
+
+            // 已安装的版本
+            (match versions_target.get_clone() {
+                Some((product_id, name)) => {
+                    let title = format!("Versions of {}", name);
+                    let install_id = product_id.clone();
+                    let prune_id = product_id.clone();
+                    view! {
+                        div(class="fixed inset-0 bg-gray-900/40 backdrop-blur-sm h-full w-full z-50 flex items-center justify-center") {
+                            div(class="relative mx-auto p-5 border w-11/12 md:w-2/3 shadow-lg rounded-md bg-white space-y-4") {
+                                h3(class="text-lg font-semibold text-gray-700 border-b pb-3") { (title) }
+                                p(class="text-xs text-gray-500") {
+                                    "New versions are installed next to the active one. Activating a version moves the app's data paths to it."
+                                }
+                                div(class="space-y-2 max-h-80 overflow-y-auto") {
+                                    Keyed(
+                                        list=versions,
+                                        key=|version| (version.id.clone(), version.active),
+                                        view=move |version| {
+                                            let label = format!("v{}", version.version);
+                                            let commit = short_commit(&version.commit).to_string();
+                                            let actions = if version.active {
+                                                view! {
+                                                    span(class="px-2 py-1 text-xs rounded-full bg-green-100 text-green-800") { "Active" }
+                                                }
+                                            } else {
+                                                let launch = (product_id.clone(), version.id.clone());
+                                                let activate = (product_id.clone(), version.id.clone());
+                                                let delete = (product_id.clone(), version.id.clone());
+                                                view! {
+                                                    button(
+                                                        class="px-3 py-1 text-sm rounded-md bg-green-500 text-white hover:bg-green-600",
+                                                        on:click=move |_| {
+                                                            versions_target.set(None);
+                                                            handle_launch_product(launch.0.clone(), String::new(), Some(launch.1.clone()));
+                                                        }
+                                                    ) { "Launch" }
+                                                    button(
+                                                        class="px-3 py-1 text-sm rounded-md bg-indigo-600 text-white hover:bg-indigo-700",
+                                                        on:click=move |_| handle_version_action(activate.0.clone(), activate.1.clone(), VersionAction::Activate)
+                                                    ) { "Activate" }
+                                                    button(
+                                                        class="px-3 py-1 text-sm rounded-md bg-red-500 text-white hover:bg-red-600",
+                                                        on:click=move |_| handle_version_action(delete.0.clone(), delete.1.clone(), VersionAction::Delete)
+                                                    ) { "Delete" }
+                                                }
+                                            };
+                                            view! {
+                                                div(class="flex items-center justify-between border border-gray-200 rounded-md p-3") {
+                                                    div {
+                                                        p(class="text-sm font-medium text-gray-800") { (label) }
+                                                        p(class="font-mono text-xs text-gray-500") { (commit) }
+                                                    }
+                                                    div(class="flex items-center space-x-2") { (actions) }
+                                                }
+                                            }
+                                        }
+                                    )
+                                }
+                                div(class="flex justify-end space-x-2") {
+                                    button(
+                                        class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                        on:click=move |_| versions_target.set(None)
+                                    ) { "Close" }
+                                    button(
+                                        class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                        on:click=move |_| handle_version_action(prune_id.clone(), String::new(), VersionAction::Prune)
+                                    ) { "Remove Inactive" }
+                                    button(
+                                        class="px-4 py-2 bg-blue-500 text-white rounded hover:bg-blue-600",
+                                        on:click=move |_| handle_version_action(install_id.clone(), String::new(), VersionAction::Install)
+                                    ) { "Install Latest Alongside" }
+                                }
+                            }
+                        }
+                    }
+                }
+                None => view! {},
+            })
+
+            // 检查更新的结果
+            (match update_target.get_clone() {
+                Some((name, check)) => {
+                    let title = format!("Updates for {}", name);
+                    let details = update_details_view(&check);
+                    let pinned = check.pinned_commit.is_some();
+                    let can_apply = check.has_update && !pinned;
+                    let can_skip = can_apply && !check.skipped && !check.commits.is_empty();
+                    let apply_check = check.clone();
+                    let skip_check = check.clone();
+                    let pin_check = check.clone();
+                    view! {
+                        div(class="fixed inset-0 bg-gray-900/40 backdrop-blur-sm h-full w-full z-50 flex items-center justify-center") {
+                            div(class="relative mx-auto p-5 border w-11/12 md:w-2/3 shadow-lg rounded-md bg-white space-y-4") {
+                                h3(class="text-lg font-semibold text-gray-700 border-b pb-3") { (title) }
+                                (details)
+                                div(class="flex justify-end space-x-2") {
+                                    button(
+                                        class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                        on:click=move |_| update_target.set(None)
+                                    ) { "Close" }
+                                    button(
+                                        class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                        title="Keep the installed commit and stop offering updates",
+                                        on:click=move |_| handle_update_action(
+                                            pin_check.clone(),
+                                            if pinned { UpdateAction::Unpin } else { UpdateAction::Pin },
+                                        )
+                                    ) { (if pinned { "Unpin" } else { "Pin Current Version" }) }
+                                    (if can_skip {
+                                        let check = skip_check.clone();
+                                        view! {
+                                            button(
+                                                class="px-4 py-2 bg-gray-200 text-gray-800 rounded hover:bg-gray-300",
+                                                on:click=move |_| handle_update_action(check.clone(), UpdateAction::Skip)
+                                            ) { "Skip This Update" }
+                                        }
+                                    } else {
+                                        view! {}
+                                    })
+                                    (if can_apply {
+                                        let check = apply_check.clone();
+                                        view! {
+                                            button(
+                                                class="px-4 py-2 bg-blue-500 text-white rounded hover:bg-blue-600",
+                                                on:click=move |_| handle_update_action(check.clone(), UpdateAction::Apply)
+                                            ) { "Upgrade" }
+                                        }
+                                    } else {
+                                        view! {}
+                                    })
+                                }
+                            }
+                        }
+                    }
+                }
+                None => view! {},
+            })
         })
     }
 }
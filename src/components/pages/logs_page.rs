@@ -0,0 +1,327 @@
+use sycamore::futures::spawn_local;
+use sycamore::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::common::{
+    copy_to_clipboard, invoke_command, use_tauri_event, LogLevel, LogLine, LogStream, Product,
+    PRODUCT_LOG_LINE,
+};
+use crate::components::{AdminLayout, AdminRoute, Toast, ToastNotification, ToastType};
+
+/// 页面最多保留的日志行数
+const MAX_LINES: usize = 5000;
+
+/// 毫秒时间戳格式化为 `HH:MM:SS.mmm`
+fn format_time(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp as f64));
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds(),
+        date.get_milliseconds()
+    )
+}
+
+fn format_line(line: &LogLine) -> String {
+    format!("{} {}", format_time(line.timestamp), line.line)
+}
+
+#[component]
+pub fn LogsPage() -> View {
+    let toast = create_signal(None::<Toast>);
+    let products = create_signal(Vec::<Product>::new());
+    // 选中的产品ID，空字符串表示未选择
+    let product_id = create_signal(String::new());
+    let sessions = create_signal(Vec::<String>::new());
+    // 选中的会话，空字符串表示跟随最新会话
+    let session = create_signal(String::new());
+    // 当前显示的日志所属会话
+    let shown_session = create_signal(None::<String>);
+    let lines = create_signal(Vec::<LogLine>::new());
+    let level = create_signal(LogLevel::Debug.value().to_string());
+    let keyword = create_signal(String::new());
+    let auto_scroll = create_signal(true);
+    let log_ref = create_node_ref();
+
+    let show_error = move |message: String| {
+        toast.set(Some(Toast {
+            message,
+            toast_type: ToastType::Error,
+        }));
+    };
+
+    spawn_local(async move {
+        match Product::load_installed_products().await {
+            Ok(list) => {
+                if let Some(first) = list.first() {
+                    product_id.set(first.id.clone());
+                }
+                products.set(list);
+            }
+            Err(e) => show_error(format!("Failed to load products: {}", e)),
+        }
+    });
+
+    let load_sessions = move |pid: String| {
+        spawn_local(async move {
+            match LogLine::load_sessions(&pid).await {
+                Ok(list) => sessions.set(list),
+                Err(e) => show_error(format!("Failed to load log sessions: {}", e)),
+            }
+        });
+    };
+
+    let load_logs = move |pid: String, selected: String| {
+        spawn_local(async move {
+            let selected = if selected.is_empty() {
+                None
+            } else {
+                Some(selected.as_str())
+            };
+            match LogLine::load(&pid, selected, Some(MAX_LINES), None).await {
+                Ok(list) => {
+                    shown_session.set(
+                        list.first()
+                            .map(|line| line.session.clone())
+                            .or_else(|| selected.map(|s| s.to_string())),
+                    );
+                    lines.set(list);
+                }
+                Err(e) => show_error(format!("Failed to load logs: {}", e)),
+            }
+        });
+    };
+
+    // 切换产品时重新加载会话列表，并回到最新会话
+    create_effect(move || {
+        let pid = product_id.get_clone();
+        session.set(String::new());
+        sessions.set(Vec::new());
+        if !pid.is_empty() {
+            load_sessions(pid);
+        }
+    });
+
+    // 切换会话时重新加载日志
+    create_effect(move || {
+        let selected = session.get_clone();
+        let pid = product_id.get_clone_untracked();
+        lines.set(Vec::new());
+        shown_session.set(None);
+        if !pid.is_empty() {
+            load_logs(pid, selected);
+        }
+    });
+
+    // 实时追加日志；跟随最新会话时，产品重新启动会切换到新会话
+    use_tauri_event(PRODUCT_LOG_LINE, move |line: LogLine| {
+        if line.product_id != product_id.get_clone_untracked() {
+            return;
+        }
+        let selected = session.get_clone_untracked();
+        if selected.is_empty() {
+            if shown_session.get_clone_untracked().as_ref() != Some(&line.session) {
+                shown_session.set(Some(line.session.clone()));
+                lines.set(Vec::new());
+                load_sessions(line.product_id.clone());
+            }
+        } else if selected != line.session {
+            return;
+        }
+        lines.update(|lines| {
+            lines.push(line);
+            if lines.len() > MAX_LINES {
+                let overflow = lines.len() - MAX_LINES;
+                lines.drain(..overflow);
+            }
+        });
+    });
+
+    let filtered = create_memo(move || {
+        let level = LogLevel::from_value(&level.get_clone());
+        let keyword = keyword.get_clone().to_lowercase();
+        lines.with(|lines| {
+            lines
+                .iter()
+                .filter(|line| line.level() >= level)
+                .filter(|line| keyword.is_empty() || line.line.to_lowercase().contains(&keyword))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+
+    // 新日志渲染后滚动到底部
+    create_effect(move || {
+        filtered.track();
+        if !auto_scroll.get() {
+            return;
+        }
+        spawn_local(async move {
+            gloo_timers::future::sleep(std::time::Duration::from_millis(0)).await;
+            if let Ok(element) = log_ref.get().dyn_into::<web_sys::Element>() {
+                element.set_scroll_top(element.scroll_height());
+            }
+        });
+    });
+
+    let handle_refresh = move |_| {
+        let pid = product_id.get_clone();
+        if !pid.is_empty() {
+            load_sessions(pid.clone());
+            load_logs(pid, session.get_clone());
+        }
+    };
+
+    let handle_copy = move |_| {
+        let text =
+            filtered.with(|lines| lines.iter().map(format_line).collect::<Vec<_>>().join("\n"));
+        spawn_local(async move {
+            match copy_to_clipboard(&text).await {
+                Ok(_) => toast.set(Some(Toast {
+                    message: "Logs copied to clipboard".to_string(),
+                    toast_type: ToastType::Success,
+                })),
+                Err(e) => show_error(format!("Failed to copy logs: {}", e)),
+            }
+        });
+    };
+
+    let handle_save = move |_| {
+        let pid = product_id.get_clone();
+        let Some(selected) = shown_session.get_clone() else {
+            show_error("No logs to save".to_string());
+            return;
+        };
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+                "pid": pid,
+                "session": selected,
+            }))
+            .unwrap_or(JsValue::NULL);
+            match invoke_command("product_export_logs", args).await {
+                Ok(path) => {
+                    let path = path.as_string().unwrap_or_default();
+                    if !path.is_empty() {
+                        toast.set(Some(Toast {
+                            message: format!("Logs saved to {}", path),
+                            toast_type: ToastType::Success,
+                        }));
+                    }
+                }
+                Err(e) => show_error(format!("Failed to save logs: {}", e)),
+            }
+        });
+    };
+
+    view! {
+        AdminLayout(current_route=AdminRoute::Logs, inner_view=view! {
+            ToastNotification(toast=toast, duration_ms=3000u32)
+            div(class="flex space-x-4 border-b border-gray-200") {
+                button(class= "px-4 py-2 text-blue-600 border-b-2 border-blue-600 font-medium") {
+                    "Logs"
+                }
+            }
+            div(class="bg-gray-100 p-4 space-y-4") {
+                // 工具栏
+                div(class="flex flex-wrap items-center gap-2") {
+                    select(
+                        class="px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                        bind:value=product_id
+                    ) {
+                        Keyed(
+                            list=products,
+                            key=|product| product.id.clone(),
+                            view=|product| view! {
+                                option(value=product.id.clone()) { (product.name) }
+                            }
+                        )
+                    }
+                    select(
+                        class="px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                        bind:value=session
+                    ) {
+                        option(value="") { "Latest" }
+                        Keyed(
+                            list=sessions,
+                            key=|session| session.clone(),
+                            view=|session| {
+                                let label = session.clone();
+                                view! {
+                                    option(value=session) { (label) }
+                                }
+                            }
+                        )
+                    }
+                    select(
+                        class="px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                        bind:value=level
+                    ) {
+                        option(value=LogLevel::Debug.value()) { "All levels" }
+                        option(value=LogLevel::Info.value()) { "Info+" }
+                        option(value=LogLevel::Warn.value()) { "Warning+" }
+                        option(value=LogLevel::Error.value()) { "Error" }
+                    }
+                    input(
+                        class="flex-1 min-w-[12rem] px-3 py-2 text-sm border border-gray-300 rounded-md",
+                        r#type="text",
+                        placeholder="Filter by keyword",
+                        bind:value=keyword
+                    )
+                    label(class="flex items-center text-sm text-gray-700") {
+                        input(
+                            class="h-4 w-4 mr-1 border-gray-300 rounded",
+                            r#type="checkbox",
+                            bind:checked=auto_scroll
+                        )
+                        "Auto-scroll"
+                    }
+                    button(
+                        class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                        on:click=handle_refresh
+                    ) { "Refresh" }
+                    button(
+                        class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                        on:click=handle_copy
+                    ) { "Copy" }
+                    button(
+                        class="px-3 py-2 text-sm rounded-md bg-blue-500 text-white hover:bg-blue-600",
+                        on:click=handle_save
+                    ) { "Save As" }
+                }
+
+                // 日志内容
+                div(
+                    r#ref=log_ref,
+                    class="h-[70vh] overflow-y-auto rounded-lg bg-gray-900 p-3 font-mono text-xs leading-5"
+                ) {
+                    (if filtered.with(|lines| lines.is_empty()) {
+                        view! {
+                            p(class="text-gray-500") { "No logs" }
+                        }
+                    } else {
+                        view! {}
+                    })
+                    Indexed(
+                        list=filtered,
+                        view=|line| {
+                            let class = match (line.level(), line.stream) {
+                                (LogLevel::Error, _) => "text-red-400",
+                                (LogLevel::Warn, _) => "text-yellow-300",
+                                (LogLevel::Debug, _) => "text-gray-500",
+                                (LogLevel::Info, LogStream::Stderr) => "text-gray-300",
+                                (LogLevel::Info, LogStream::Stdout) => "text-gray-100",
+                            };
+                            view! {
+                                div(class=format!("whitespace-pre-wrap break-all {}", class)) {
+                                    span(class="text-gray-500 mr-2") { (format_time(line.timestamp)) }
+                                    (line.line)
+                                }
+                            }
+                        }
+                    )
+                }
+            }
+        })
+    }
+}
@@ -2,6 +2,8 @@ pub mod dashboard_page;
 pub mod app_store_page;
 pub mod settings;
 pub mod file_page;
+pub mod logs_page;
+pub mod tasks_page;
 pub use settings::*;
 pub const ADMIN_STYLES: &str = r#"
 .admin-layout {
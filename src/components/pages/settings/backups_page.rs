@@ -0,0 +1,145 @@
+use crate::common::{format_bytes, Backup, Product};
+use crate::components::toast::{Toast, ToastNotification, ToastType};
+use sycamore::prelude::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+/// 毫秒时间戳格式化为本地日期时间
+fn format_date_time(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp as f64));
+    String::from(date.to_locale_string("default", &JsValue::UNDEFINED))
+}
+
+#[component]
+pub fn BackupsPage() -> View {
+    let toast = create_signal(None::<Toast>);
+    let products = create_signal(Vec::<Product>::new());
+    let product_id = create_signal(String::new());
+    let backups = create_signal(Vec::<Backup>::new());
+
+    let show_error = move |message: String| {
+        toast.set(Some(Toast {
+            message,
+            toast_type: ToastType::Error,
+        }));
+    };
+
+    spawn_local(async move {
+        match Product::load_all_products().await {
+            Ok(list) => {
+                if let Some(first) = list.first() {
+                    product_id.set(first.id.clone());
+                }
+                products.set(list);
+            }
+            Err(e) => show_error(format!("Failed to load products: {}", e)),
+        }
+    });
+
+    let load_backups = move |pid: String| {
+        spawn_local(async move {
+            match Backup::load(&pid).await {
+                Ok(list) => backups.set(list),
+                Err(e) => show_error(format!("Failed to load backups: {}", e)),
+            }
+        });
+    };
+
+    // 切换产品时加载其备份
+    create_effect(move || {
+        let pid = product_id.get_clone();
+        backups.set(Vec::new());
+        if !pid.is_empty() {
+            load_backups(pid);
+        }
+    });
+
+    let handle_restore = move |backup_id: String| {
+        let pid = product_id.get_clone();
+        spawn_local(async move {
+            match Backup::restore(&pid, &backup_id).await {
+                Ok(_) => {
+                    toast.set(Some(Toast {
+                        message: "Backup restored successfully".to_string(),
+                        toast_type: ToastType::Success,
+                    }));
+                    load_backups(pid);
+                }
+                Err(e) => show_error(format!("Failed to restore backup: {}", e)),
+            }
+        });
+    };
+
+    let handle_delete = move |backup_id: String| {
+        let pid = product_id.get_clone();
+        spawn_local(async move {
+            match Backup::delete(&pid, &backup_id).await {
+                Ok(_) => backups.update(|backups| backups.retain(|backup| backup.id != backup_id)),
+                Err(e) => show_error(format!("Failed to delete backup: {}", e)),
+            }
+        });
+    };
+
+    view! {
+        div(class="bg-gray-50") {
+            ToastNotification(toast=toast, duration_ms=3000u32)
+            div(class="space-y-6 bg-white shadow rounded-lg p-6") {
+                select(
+                    class="w-full px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                    bind:value=product_id
+                ) {
+                    Keyed(
+                        list=products,
+                        key=|product| product.id.clone(),
+                        view=|product| view! {
+                            option(value=product.id.clone()) { (product.name) }
+                        }
+                    )
+                }
+
+                p(class="text-xs text-gray-500") {
+                    "Restoring a backup while the app is installed backs up the current installation first and keeps its data."
+                }
+
+                (if backups.with(|backups| backups.is_empty()) {
+                    view! {
+                        p(class="text-sm text-gray-500") { "No backups." }
+                    }
+                } else {
+                    view! {}
+                })
+
+                Keyed(
+                    list=backups,
+                    key=|backup| backup.id.clone(),
+                    view=move |backup| {
+                        let date = format_date_time(backup.created_at);
+                        let size = format_bytes(backup.size_bytes);
+                        let restore_id = backup.id.clone();
+                        let delete_id = backup.id.clone();
+                        view! {
+                            div(class="flex items-center justify-between border border-gray-200 rounded-md p-3") {
+                                div {
+                                    p(class="text-sm font-medium text-gray-800") { (date) }
+                                    p(class="text-xs text-gray-500") { (size) }
+                                }
+                                div(class="flex space-x-2") {
+                                    button(
+                                        class="px-3 py-1 text-sm rounded-md bg-indigo-600 text-white hover:bg-indigo-700",
+                                        r#type="button",
+                                        on:click=move |_| handle_restore(restore_id.clone())
+                                    ) { "Restore" }
+                                    button(
+                                        class="px-3 py-1 text-sm rounded-md bg-red-500 text-white hover:bg-red-600",
+                                        r#type="button",
+                                        on:click=move |_| handle_delete(delete_id.clone())
+                                    ) { "Delete" }
+                                }
+                            }
+                        }
+                    }
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use crate::common::{AppConfig, GitMirror, MirrorKind, MirrorStats};
+use crate::components::toast::{Toast, ToastNotification, ToastType};
+use sycamore::prelude::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement};
+
+/// 镜像的延迟和使用次数
+fn format_stats(stats: Option<&MirrorStats>) -> String {
+    let Some(stats) = stats else {
+        return "Not used yet".to_string();
+    };
+    let latency = match stats.latency_ms {
+        Some(latency) => format!("{} ms", latency),
+        None => "unreachable".to_string(),
+    };
+    format!(
+        "Latency: {} · {} succeeded · {} failed",
+        latency, stats.successes, stats.failures
+    )
+}
+
+#[component]
+pub fn GitMirrorsPage() -> View {
+    let toast = create_signal(None::<Toast>);
+    let config = use_context::<Signal<AppConfig>>();
+    let mirrors = create_signal(config.with(|config| config.git_mirrors()));
+    let stats = create_signal(HashMap::<String, MirrorStats>::new());
+    let probing = create_signal(false);
+
+    let show_error = move |message: String| {
+        toast.set(Some(Toast {
+            message,
+            toast_type: ToastType::Error,
+        }));
+    };
+
+    spawn_local(async move {
+        match MirrorStats::load_all().await {
+            Ok(loaded) => stats.set(loaded),
+            Err(e) => show_error(format!("Failed to load mirror stats: {}", e)),
+        }
+    });
+
+    let handle_add = move |_| {
+        mirrors.update(|mirrors| {
+            mirrors.push(GitMirror {
+                name: format!("Mirror {}", mirrors.len() + 1),
+                kind: MirrorKind::default(),
+                url: String::new(),
+                enabled: true,
+            })
+        });
+    };
+
+    let handle_probe = move |_| {
+        let list = mirrors.get_clone();
+        probing.set(true);
+        spawn_local(async move {
+            match GitMirror::probe(&list).await {
+                Ok(probes) => stats.update(|stats| {
+                    for probe in probes {
+                        stats.entry(probe.url).or_default().latency_ms = probe.latency_ms;
+                    }
+                }),
+                Err(e) => show_error(format!("Failed to test mirrors: {}", e)),
+            }
+            probing.set(false);
+        });
+    };
+
+    let handle_save = move |_| {
+        let mut new_config = config.get_clone();
+        new_config.git_mirrors = Some(mirrors.get_clone());
+        spawn_local(async move {
+            match new_config.save().await {
+                Ok(_) => {
+                    toast.set(Some(Toast {
+                        message: "Mirrors saved successfully".to_string(),
+                        toast_type: ToastType::Success,
+                    }));
+                    if let Ok(loaded_config) = AppConfig::load().await {
+                        config.set(loaded_config);
+                    }
+                }
+                Err(e) => show_error(format!("Failed to save mirrors: {}", e)),
+            }
+        });
+    };
+
+    // 按序号更新镜像，输入框失去焦点时触发，避免输入时重新渲染
+    let update = move |index: usize, apply: fn(&mut GitMirror, String)| {
+        move |ev: Event| {
+            let Some(target) = ev.target() else {
+                return;
+            };
+            let value = match target.clone().dyn_into::<HtmlInputElement>() {
+                Ok(input) if input.type_() == "checkbox" => input.checked().to_string(),
+                Ok(input) => input.value(),
+                Err(_) => match target.dyn_into::<HtmlSelectElement>() {
+                    Ok(select) => select.value(),
+                    Err(_) => return,
+                },
+            };
+            mirrors.update(|mirrors| {
+                if let Some(mirror) = mirrors.get_mut(index) {
+                    apply(mirror, value);
+                }
+            });
+        }
+    };
+
+    // 与前一个镜像交换位置，镜像按列表顺序尝试
+    let move_up = move |index: usize| {
+        mirrors.update(|mirrors| {
+            if index > 0 && index < mirrors.len() {
+                mirrors.swap(index - 1, index);
+            }
+        });
+    };
+
+    let indexed = create_memo(move || {
+        mirrors.with(|mirrors| mirrors.iter().cloned().enumerate().collect::<Vec<_>>())
+    });
+
+    view! {
+        div(class="bg-gray-50") {
+            ToastNotification(toast=toast, duration_ms=3000u32)
+            div(class="space-y-6 bg-white shadow rounded-lg p-6") {
+                div(class="flex items-center justify-between") {
+                    p(class="text-xs text-gray-500") {
+                        "When a download from GitHub fails, the enabled mirrors are tried from top to bottom."
+                    }
+                    div(class="flex space-x-2") {
+                        button(
+                            class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300 disabled:opacity-50",
+                            r#type="button",
+                            disabled=probing.get(),
+                            on:click=handle_probe
+                        ) { (if probing.get() { "Testing..." } else { "Test Latency" }) }
+                        button(
+                            class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                            r#type="button",
+                            on:click=handle_add
+                        ) { "Add Mirror" }
+                    }
+                }
+
+                (if mirrors.with(|mirrors| mirrors.is_empty()) {
+                    view! {
+                        p(class="text-sm text-gray-500") { "No mirrors. Downloads only connect directly." }
+                    }
+                } else {
+                    view! {}
+                })
+
+                Indexed(
+                    list=indexed,
+                    view=move |(index, mirror)| {
+                        let url = mirror.url.clone();
+                        view! {
+                            div(class="border border-gray-200 rounded-md p-4 space-y-3") {
+                                div(class="flex items-center space-x-2") {
+                                    input(
+                                        class="h-4 w-4 text-indigo-600 focus:ring-indigo-500 border-gray-300 rounded",
+                                        r#type="checkbox",
+                                        checked=mirror.enabled,
+                                        on:change=update(index, |mirror, value| mirror.enabled = value == "true")
+                                    )
+                                    input(
+                                        class="flex-1 px-3 py-2 text-sm border border-gray-300 rounded-md font-medium",
+                                        r#type="text",
+                                        placeholder="Mirror name",
+                                        value=mirror.name.clone(),
+                                        on:change=update(index, |mirror, value| mirror.name = value)
+                                    )
+                                    button(
+                                        class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                                        r#type="button",
+                                        on:click=move |_| move_up(index)
+                                    ) { "Move Up" }
+                                    button(
+                                        class="px-3 py-2 text-sm rounded-md bg-red-500 text-white hover:bg-red-600",
+                                        r#type="button",
+                                        on:click=move |_| mirrors.update(|mirrors| {
+                                            if index < mirrors.len() {
+                                                mirrors.remove(index);
+                                            }
+                                        })
+                                    ) { "Remove" }
+                                }
+                                div(class="grid grid-cols-3 gap-3") {
+                                    select(
+                                        class="px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                                        value=mirror.kind.value(),
+                                        on:change=update(index, |mirror, value| mirror.kind = MirrorKind::from_value(&value))
+                                    ) {
+                                        option(value=MirrorKind::Prefix.value()) { "URL prefix" }
+                                        option(value=MirrorKind::HostRewrite.value()) { "Replace github.com" }
+                                        option(value=MirrorKind::HttpProxy.value()) { "HTTP proxy" }
+                                    }
+                                    input(
+                                        class="col-span-2 px-3 py-2 text-sm border border-gray-300 rounded-md font-mono",
+                                        r#type="text",
+                                        placeholder=mirror.kind.placeholder(),
+                                        value=mirror.url.clone(),
+                                        on:change=update(index, |mirror, value| mirror.url = value.trim().to_string())
+                                    )
+                                }
+                                p(class="text-xs text-gray-500") {
+                                    (stats.with(|stats| format_stats(stats.get(&url))))
+                                }
+                            }
+                        }
+                    }
+                )
+
+                div(class="flex justify-end") {
+                    button(
+                        class="inline-flex justify-center py-2 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700",
+                        r#type="button",
+                        on:click=handle_save
+                    ) { "Save" }
+                }
+            }
+        }
+    }
+}
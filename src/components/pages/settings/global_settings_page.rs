@@ -1,4 +1,4 @@
-use crate::common::{invoke_tauri, AppConfig};
+use crate::common::{invoke_tauri, AppConfig, ExitPolicy};
 use crate::components::toast::{Toast, ToastNotification, ToastType};
 use serde_wasm_bindgen::from_value;
 use sycamore::prelude::*;
@@ -96,6 +96,92 @@ pub fn GlobalSettingsPage() -> View {
         }
     };
 
+    let update_exit_policy = {
+        let config = config.clone();
+        move |ev: Event| {
+            if let Some(target) = ev.target() {
+                if let Ok(target) = target.dyn_into::<HtmlSelectElement>() {
+                    let mut new_config = config.get_clone();
+                    new_config.exit_policy = Some(ExitPolicy::from_value(&target.value()));
+                    config.set(new_config);
+                }
+            }
+        }
+    };
+
+    let update_max_concurrent_jobs = {
+        let config = config.clone();
+        move |ev: Event| {
+            if let Some(target) = ev.target() {
+                if let Ok(target) = target.dyn_into::<HtmlInputElement>() {
+                    if let Ok(value) = target.value().parse::<usize>() {
+                        let mut new_config = config.get_clone();
+                        new_config.max_concurrent_jobs = Some(value.max(1));
+                        config.set(new_config);
+                    }
+                }
+            }
+        }
+    };
+
+    let update_max_backups = {
+        let config = config.clone();
+        move |ev: Event| {
+            if let Some(target) = ev.target() {
+                if let Ok(target) = target.dyn_into::<HtmlInputElement>() {
+                    if let Ok(value) = target.value().parse::<usize>() {
+                        let mut new_config = config.get_clone();
+                        new_config.max_backups = Some(value.max(1));
+                        config.set(new_config);
+                    }
+                }
+            }
+        }
+    };
+
+    let update_clone_depth = {
+        let config = config.clone();
+        move |ev: Event| {
+            if let Some(target) = ev.target() {
+                if let Ok(target) = target.dyn_into::<HtmlInputElement>() {
+                    if let Ok(value) = target.value().parse::<u32>() {
+                        let mut new_config = config.get_clone();
+                        new_config.clone_depth = (value > 0).then_some(value);
+                        config.set(new_config);
+                    }
+                }
+            }
+        }
+    };
+
+    let update_partial_clone = {
+        let config = config.clone();
+        move |ev: Event| {
+            if let Some(target) = ev.target() {
+                if let Ok(target) = target.dyn_into::<HtmlInputElement>() {
+                    let mut new_config = config.get_clone();
+                    new_config.partial_clone = Some(target.checked());
+                    config.set(new_config);
+                }
+            }
+        }
+    };
+
+    let update_clone_retries = {
+        let config = config.clone();
+        move |ev: Event| {
+            if let Some(target) = ev.target() {
+                if let Ok(target) = target.dyn_into::<HtmlInputElement>() {
+                    if let Ok(value) = target.value().parse::<u32>() {
+                        let mut new_config = config.get_clone();
+                        new_config.clone_retries = Some(value);
+                        config.set(new_config);
+                    }
+                }
+            }
+        }
+    };
+
     let browse_directory = {
         let config = config.clone();
         let toast = toast.clone();
@@ -185,6 +271,95 @@ pub fn GlobalSettingsPage() -> View {
                         }
                     }
 
+                    // 退出时运行中的产品
+                    div(class="flex flex-col") {
+                        label(class="block text-sm font-medium text-gray-700 mb-1") {
+                            "When closing with running apps"
+                        }
+                        select(
+                            class="mt-1 block w-full pl-3 pr-10 py-2 text-base border border-gray-300 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 rounded-md",
+                            value=create_memo(move || config.get_clone().exit_policy.unwrap_or_default().value()),
+                            on:change=update_exit_policy
+                        ) {
+                            option(value=ExitPolicy::StopAll.value()) { "Stop all apps" }
+                            option(value=ExitPolicy::Ask.value()) { "Ask me" }
+                            option(value=ExitPolicy::LeaveRunning.value()) { "Leave them running" }
+                        }
+                    }
+
+                    // 同时执行的安装任务数
+                    div(class="flex flex-col") {
+                        label(class="block text-sm font-medium text-gray-700 mb-1") {
+                            "Concurrent install jobs"
+                        }
+                        input(
+                            class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-indigo-500 focus:border-indigo-500",
+                            r#type="number",
+                            min="1",
+                            value=create_memo(move || config.get_clone().max_concurrent_jobs.unwrap_or(1).to_string()),
+                            on:change=update_max_concurrent_jobs
+                        )
+                    }
+
+                    // 每个产品保留的备份数
+                    div(class="flex flex-col") {
+                        label(class="block text-sm font-medium text-gray-700 mb-1") {
+                            "Backups kept per app"
+                        }
+                        input(
+                            class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-indigo-500 focus:border-indigo-500",
+                            r#type="number",
+                            min="1",
+                            value=create_memo(move || config.get_clone().max_backups.unwrap_or(5).to_string()),
+                            on:change=update_max_backups
+                        )
+                    }
+
+                    // 克隆深度
+                    div(class="flex flex-col") {
+                        label(class="block text-sm font-medium text-gray-700 mb-1") {
+                            "Clone depth (0 = full history)"
+                        }
+                        input(
+                            class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-indigo-500 focus:border-indigo-500",
+                            r#type="number",
+                            min="0",
+                            value=create_memo(move || config.get_clone().clone_depth.unwrap_or(0).to_string()),
+                            on:change=update_clone_depth
+                        )
+                    }
+
+                    // 克隆失败后的重试次数
+                    div(class="flex flex-col") {
+                        label(class="block text-sm font-medium text-gray-700 mb-1") {
+                            "Download retries"
+                        }
+                        input(
+                            class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-indigo-500 focus:border-indigo-500",
+                            r#type="number",
+                            min="0",
+                            value=create_memo(move || config.get_clone().clone_retries.unwrap_or(3).to_string()),
+                            on:change=update_clone_retries
+                        )
+                    }
+
+                    // 部分克隆
+                    div(class="flex items-center") {
+                        input(
+                            class="h-4 w-4 text-indigo-600 focus:ring-indigo-500 border-gray-300 rounded",
+                            r#type="checkbox",
+                            id="partial-clone",
+                            checked=create_memo(move || config.get_clone().partial_clone == Some(true)),
+                            on:change=update_partial_clone
+                        )
+                        label(
+                            class="ml-2 block text-sm text-gray-700",
+                            r#for="partial-clone"
+                        ) {
+                            "Download file contents on checkout (partial clone)"
+                        }
+                    }
+
                      // 开发者模式
                      div(class="flex items-center") {
                         input(
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use crate::common::{LaunchProfile, Product, RestartMode, RestartPolicy};
+use crate::components::toast::{Toast, ToastNotification, ToastType};
+use sycamore::prelude::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+
+/// 环境变量格式化为每行一个 `KEY=VALUE`
+fn format_envs(envs: &BTreeMap<String, String>) -> String {
+    envs.iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 解析每行一个 `KEY=VALUE` 的环境变量，忽略空行和没有 `=` 的行
+fn parse_envs(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+fn input_value(ev: &Event) -> Option<String> {
+    let target = ev.target()?;
+    if let Ok(input) = target.clone().dyn_into::<HtmlInputElement>() {
+        return Some(input.value());
+    }
+    target
+        .dyn_into::<HtmlTextAreaElement>()
+        .ok()
+        .map(|textarea| textarea.value())
+}
+
+#[component]
+pub fn LaunchProfilesPage() -> View {
+    let toast = create_signal(None::<Toast>);
+    let products = create_signal(Vec::<Product>::new());
+    let product_id = create_signal(String::new());
+    let profiles = create_signal(Vec::<LaunchProfile>::new());
+    let policy = create_signal(RestartPolicy::default());
+
+    let show_error = move |message: String| {
+        toast.set(Some(Toast {
+            message,
+            toast_type: ToastType::Error,
+        }));
+    };
+
+    spawn_local(async move {
+        match Product::load_installed_products().await {
+            Ok(list) => {
+                if let Some(first) = list.first() {
+                    product_id.set(first.id.clone());
+                }
+                products.set(list);
+            }
+            Err(e) => show_error(format!("Failed to load products: {}", e)),
+        }
+    });
+
+    // 切换产品时加载其启动配置和重启策略
+    create_effect(move || {
+        let pid = product_id.get_clone();
+        profiles.set(Vec::new());
+        policy.set(RestartPolicy::default());
+        if pid.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            match LaunchProfile::load(&pid).await {
+                Ok(list) => profiles.set(list),
+                Err(e) => show_error(format!("Failed to load launch profiles: {}", e)),
+            }
+            match RestartPolicy::load(&pid).await {
+                Ok(loaded) => policy.set(loaded),
+                Err(e) => show_error(format!("Failed to load restart policy: {}", e)),
+            }
+        });
+    });
+
+    let handle_add = move |_| {
+        profiles.update(|profiles| {
+            profiles.push(LaunchProfile {
+                name: format!("Profile {}", profiles.len() + 1),
+                ..Default::default()
+            })
+        });
+    };
+
+    let handle_save = move |_| {
+        let pid = product_id.get_clone();
+        let list = profiles.get_clone();
+        let restart_policy = policy.get_clone();
+        spawn_local(async move {
+            if let Err(e) = LaunchProfile::save(&pid, &list).await {
+                show_error(format!("Failed to save launch profiles: {}", e));
+                return;
+            }
+            match restart_policy.save(&pid).await {
+                Ok(_) => toast.set(Some(Toast {
+                    message: "Launch settings saved successfully".to_string(),
+                    toast_type: ToastType::Success,
+                })),
+                Err(e) => show_error(format!("Failed to save restart policy: {}", e)),
+            }
+        });
+    };
+
+    // 按序号更新启动配置，输入框失去焦点时触发，避免输入时重新渲染
+    let update = move |index: usize, apply: fn(&mut LaunchProfile, String)| {
+        move |ev: Event| {
+            if let Some(value) = input_value(&ev) {
+                profiles.update(|profiles| {
+                    if let Some(profile) = profiles.get_mut(index) {
+                        apply(profile, value);
+                    }
+                });
+            }
+        }
+    };
+
+    let update_restart_mode = move |ev: Event| {
+        if let Some(target) = ev.target() {
+            if let Ok(target) = target.dyn_into::<HtmlSelectElement>() {
+                policy.update(|policy| policy.mode = RestartMode::from_value(&target.value()));
+            }
+        }
+    };
+
+    let update_max_retries = move |ev: Event| {
+        if let Some(value) = input_value(&ev).and_then(|value| value.parse::<u32>().ok()) {
+            policy.update(|policy| policy.max_retries = value);
+        }
+    };
+
+    let update_backoff = move |ev: Event| {
+        if let Some(value) = input_value(&ev).and_then(|value| value.parse::<u64>().ok()) {
+            policy.update(|policy| policy.backoff_secs = value);
+        }
+    };
+
+    let indexed = create_memo(move || {
+        profiles.with(|profiles| profiles.iter().cloned().enumerate().collect::<Vec<_>>())
+    });
+
+    view! {
+        div(class="bg-gray-50") {
+            ToastNotification(toast=toast, duration_ms=3000u32)
+            div(class="space-y-6 bg-white shadow rounded-lg p-6") {
+                div(class="flex items-center space-x-2") {
+                    select(
+                        class="flex-1 px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                        bind:value=product_id
+                    ) {
+                        Keyed(
+                            list=products,
+                            key=|product| product.id.clone(),
+                            view=|product| view! {
+                                option(value=product.id.clone()) { (product.name) }
+                            }
+                        )
+                    }
+                    button(
+                        class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                        r#type="button",
+                        on:click=handle_add
+                    ) { "Add Profile" }
+                }
+
+                // 自动重启策略
+                div(class="border border-gray-200 rounded-md p-4 space-y-3") {
+                    h4(class="text-sm font-semibold text-gray-800") { "Restart policy" }
+                    div(class="grid grid-cols-3 gap-3") {
+                        div(class="flex flex-col") {
+                            label(class="block text-sm font-medium text-gray-700 mb-1") { "When the app exits" }
+                            select(
+                                class="px-3 py-2 text-sm border border-gray-300 rounded-md bg-white",
+                                value=create_memo(move || policy.with(|policy| policy.mode.value())),
+                                on:change=update_restart_mode
+                            ) {
+                                option(value=RestartMode::Never.value()) { "Never restart" }
+                                option(value=RestartMode::OnFailure.value()) { "Restart on failure" }
+                                option(value=RestartMode::Always.value()) { "Always restart" }
+                            }
+                        }
+                        div(class="flex flex-col") {
+                            label(class="block text-sm font-medium text-gray-700 mb-1") { "Max retries (0 = unlimited)" }
+                            input(
+                                class="px-3 py-2 text-sm border border-gray-300 rounded-md",
+                                r#type="number",
+                                min="0",
+                                value=create_memo(move || policy.with(|policy| policy.max_retries.to_string())),
+                                on:change=update_max_retries
+                            )
+                        }
+                        div(class="flex flex-col") {
+                            label(class="block text-sm font-medium text-gray-700 mb-1") { "Initial backoff (seconds)" }
+                            input(
+                                class="px-3 py-2 text-sm border border-gray-300 rounded-md",
+                                r#type="number",
+                                min="0",
+                                value=create_memo(move || policy.with(|policy| policy.backoff_secs.to_string())),
+                                on:change=update_backoff
+                            )
+                        }
+                    }
+                }
+
+                (if profiles.with(|profiles| profiles.is_empty()) {
+                    view! {
+                        p(class="text-sm text-gray-500") {
+                            "No launch profiles. The product starts with its default command."
+                        }
+                    }
+                } else {
+                    view! {}
+                })
+
+                Indexed(
+                    list=indexed,
+                    view=move |(index, profile)| view! {
+                        div(class="border border-gray-200 rounded-md p-4 space-y-3") {
+                            div(class="flex items-center space-x-2") {
+                                input(
+                                    class="flex-1 px-3 py-2 text-sm border border-gray-300 rounded-md font-medium",
+                                    r#type="text",
+                                    placeholder="Profile name",
+                                    value=profile.name.clone(),
+                                    on:change=update(index, |profile, value| profile.name = value)
+                                )
+                                button(
+                                    class="px-3 py-2 text-sm rounded-md bg-red-500 text-white hover:bg-red-600",
+                                    r#type="button",
+                                    on:click=move |_| profiles.update(|profiles| {
+                                        if index < profiles.len() {
+                                            profiles.remove(index);
+                                        }
+                                    })
+                                ) { "Remove" }
+                            }
+                            div(class="flex flex-col") {
+                                label(class="block text-sm font-medium text-gray-700 mb-1") {
+                                    "Extra arguments"
+                                }
+                                input(
+                                    class="px-3 py-2 text-sm border border-gray-300 rounded-md font-mono",
+                                    r#type="text",
+                                    placeholder="--share --port ${port}",
+                                    value=profile.args.clone(),
+                                    on:change=update(index, |profile, value| profile.args = value)
+                                )
+                            }
+                            div(class="flex flex-col") {
+                                label(class="block text-sm font-medium text-gray-700 mb-1") {
+                                    "Environment variables (KEY=VALUE per line)"
+                                }
+                                textarea(
+                                    class="px-3 py-2 text-sm border border-gray-300 rounded-md font-mono",
+                                    rows="3",
+                                    placeholder="CUDA_VISIBLE_DEVICES=0\nHF_HOME=D:/models",
+                                    on:change=update(index, |profile, value| profile.envs = parse_envs(&value))
+                                ) { (format_envs(&profile.envs)) }
+                            }
+                            div(class="flex flex-col") {
+                                label(class="block text-sm font-medium text-gray-700 mb-1") {
+                                    "Working directory (relative to the install directory)"
+                                }
+                                input(
+                                    class="px-3 py-2 text-sm border border-gray-300 rounded-md font-mono",
+                                    r#type="text",
+                                    placeholder="Install directory",
+                                    value=profile.working_dir.clone().unwrap_or_default(),
+                                    on:change=update(index, |profile, value| {
+                                        profile.working_dir = Some(value).filter(|dir| !dir.trim().is_empty());
+                                    })
+                                )
+                            }
+                        }
+                    }
+                )
+
+                div(class="flex justify-end") {
+                    button(
+                        class="inline-flex justify-center py-2 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700",
+                        r#type="button",
+                        on:click=handle_save
+                    ) { "Save" }
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
+pub mod backups_page;
+pub mod git_mirrors_page;
 pub mod global_settings_page;
+pub mod launch_profiles_page;
 pub mod uv_settings_page;
 pub mod settings_page;
 pub mod uv_pythons_page;
@@ -1,7 +1,7 @@
 use sycamore::prelude::*;
 
 use crate::components::{
-    global_settings_page::GlobalSettingsPage, uv_pythons_page::UVPythonsPage, uv_settings_page::UVSettingsPage, AdminLayout, AdminRoute
+    backups_page::BackupsPage, git_mirrors_page::GitMirrorsPage, global_settings_page::GlobalSettingsPage, launch_profiles_page::LaunchProfilesPage, uv_pythons_page::UVPythonsPage, uv_settings_page::UVSettingsPage, AdminLayout, AdminRoute
 };
 
 #[component]
@@ -37,6 +37,30 @@ pub fn SettingsPage() -> View {
                     },
                     on:click=move |_| active_tab.set(2)
                 ) { "Python Environment" }
+                button(
+                    class=if active_tab.get() == 3 {
+                        "px-4 py-2 text-blue-600 border-b-2 border-blue-600 font-medium"
+                    } else {
+                        "px-4 py-2 text-gray-500 hover:text-gray-700"
+                    },
+                    on:click=move |_| active_tab.set(3)
+                ) { "Launch Settings" }
+                button(
+                    class=if active_tab.get() == 4 {
+                        "px-4 py-2 text-blue-600 border-b-2 border-blue-600 font-medium"
+                    } else {
+                        "px-4 py-2 text-gray-500 hover:text-gray-700"
+                    },
+                    on:click=move |_| active_tab.set(4)
+                ) { "Backups" }
+                button(
+                    class=if active_tab.get() == 5 {
+                        "px-4 py-2 text-blue-600 border-b-2 border-blue-600 font-medium"
+                    } else {
+                        "px-4 py-2 text-gray-500 hover:text-gray-700"
+                    },
+                    on:click=move |_| active_tab.set(5)
+                ) { "Git Mirrors" }
             }
 
             div(class="p-4") {
@@ -45,6 +69,9 @@ pub fn SettingsPage() -> View {
                     0 => GlobalSettingsPage(),
                     1 => UVSettingsPage(),
                     2 => UVPythonsPage(),
+                    3 => LaunchProfilesPage(),
+                    4 => BackupsPage(),
+                    5 => GitMirrorsPage(),
                     _ => view! { "" }
                 })
             }
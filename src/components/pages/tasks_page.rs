@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use sycamore::futures::spawn_local;
+use sycamore::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::common::{
+    use_tauri_event, InstallProgressEvent, InstallStageStatus, Job, JobState, Product, JOB_UPDATED,
+    PRODUCT_INSTALL_PROGRESS,
+};
+use crate::components::{AdminLayout, AdminRoute, Toast, ToastNotification, ToastType};
+
+/// 毫秒时间戳格式化为 `HH:MM:SS`
+fn format_time(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp as f64));
+    format!(
+        "{:02}:{:02}:{:02}",
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds()
+    )
+}
+
+/// 毫秒时长格式化为 `1m 05s`、`12s`
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+fn state_class(state: JobState) -> &'static str {
+    match state {
+        JobState::Queued => "px-2 py-0.5 rounded text-xs font-medium bg-gray-100 text-gray-700",
+        JobState::Running => "px-2 py-0.5 rounded text-xs font-medium bg-blue-100 text-blue-700",
+        JobState::Succeeded => {
+            "px-2 py-0.5 rounded text-xs font-medium bg-green-100 text-green-700"
+        }
+        JobState::Failed => "px-2 py-0.5 rounded text-xs font-medium bg-red-100 text-red-700",
+        JobState::Cancelled => {
+            "px-2 py-0.5 rounded text-xs font-medium bg-yellow-100 text-yellow-700"
+        }
+    }
+}
+
+/// 任务的耗时说明：等待中显示加入时间，执行中显示开始时间，结束后显示总耗时
+fn job_time(job: &Job) -> String {
+    match (job.started_at, job.finished_at) {
+        (Some(started), Some(finished)) => format!(
+            "Started {} · took {}",
+            format_time(started),
+            format_duration(finished.saturating_sub(started))
+        ),
+        (Some(started), None) => format!("Started {}", format_time(started)),
+        (None, Some(finished)) => format!("Finished {}", format_time(finished)),
+        (None, None) => format!("Queued {}", format_time(job.created_at)),
+    }
+}
+
+#[component]
+pub fn TasksPage() -> View {
+    let toast = create_signal(None::<Toast>);
+    let jobs = create_signal(Vec::<Job>::new());
+    // 产品ID -> 产品名称
+    let names = create_signal(HashMap::<String, String>::new());
+    // 日志会话 -> 正在执行的步骤
+    let steps = create_signal(HashMap::<String, String>::new());
+
+    let show_error = move |message: String| {
+        toast.set(Some(Toast {
+            message,
+            toast_type: ToastType::Error,
+        }));
+    };
+
+    let load_jobs = move || {
+        spawn_local(async move {
+            match Job::load_all().await {
+                Ok(list) => jobs.set(list),
+                Err(e) => show_error(format!("Failed to load tasks: {}", e)),
+            }
+        });
+    };
+    load_jobs();
+
+    spawn_local(async move {
+        if let Ok(products) = Product::load_all_products().await {
+            names.set(
+                products
+                    .into_iter()
+                    .map(|product| (product.id, product.name))
+                    .collect(),
+            );
+        }
+    });
+
+    use_tauri_event(JOB_UPDATED, move |job: Job| {
+        jobs.update(
+            |jobs| match jobs.iter_mut().find(|item| item.id == job.id) {
+                Some(item) => *item = job,
+                None => jobs.push(job),
+            },
+        );
+    });
+
+    use_tauri_event(
+        PRODUCT_INSTALL_PROGRESS,
+        move |event: InstallProgressEvent| {
+            let step = match event.status {
+                InstallStageStatus::Started => event.stage.label().to_string(),
+                InstallStageStatus::Finished => format!("{} done", event.stage.label()),
+                InstallStageStatus::Failed => format!("{} failed", event.stage.label()),
+            };
+            steps.update(|steps| {
+                steps.insert(event.log_session, step);
+            });
+        },
+    );
+
+    let handle_cancel = move |id: u64| {
+        spawn_local(async move {
+            if let Err(e) = Job::cancel(id).await {
+                show_error(format!("Failed to cancel task: {}", e));
+            }
+        });
+    };
+
+    let handle_clear = move |_| {
+        spawn_local(async move {
+            match Job::clear_finished().await {
+                Ok(_) => jobs.update(|jobs| jobs.retain(|job| !job.state.is_finished())),
+                Err(e) => show_error(format!("Failed to clear tasks: {}", e)),
+            }
+        });
+    };
+
+    // 最新的任务在前
+    let sorted = create_memo(move || {
+        let mut list = jobs.get_clone();
+        list.sort_by(|a, b| b.id.cmp(&a.id));
+        list
+    });
+
+    view! {
+        AdminLayout(current_route=AdminRoute::Tasks, inner_view=view! {
+            ToastNotification(toast=toast, duration_ms=3000u32)
+            div(class="flex space-x-4 border-b border-gray-200") {
+                button(class= "px-4 py-2 text-blue-600 border-b-2 border-blue-600 font-medium") {
+                    "Tasks"
+                }
+            }
+            div(class="bg-gray-100 p-4 space-y-4") {
+                div(class="flex justify-end") {
+                    button(
+                        class="px-3 py-2 text-sm rounded-md bg-gray-200 text-gray-700 hover:bg-gray-300",
+                        on:click=handle_clear
+                    ) { "Clear finished" }
+                }
+
+                (if jobs.with(|jobs| jobs.is_empty()) {
+                    view! {
+                        p(class="text-sm text-gray-500") { "No tasks." }
+                    }
+                } else {
+                    view! {}
+                })
+
+                Keyed(
+                    list=sorted,
+                    key=|job| (job.id, job.state, job.log_session.clone(), job.finished_at),
+                    view=move |job| {
+                        let name = names
+                            .with(|names| names.get(&job.product_id).cloned())
+                            .unwrap_or_else(|| job.product_id.clone());
+                        let title = format!("{} {}", job.kind.label(), name);
+                        let time = job_time(&job);
+                        let badge_class = state_class(job.state);
+                        let badge = job.state.label();
+                        let session = job.log_session.clone().unwrap_or_default();
+                        let running = job.state == JobState::Running;
+                        let step = create_memo(move || {
+                            if running {
+                                steps.with(|steps| steps.get(&session).cloned()).unwrap_or_default()
+                            } else {
+                                String::new()
+                            }
+                        });
+                        let error = job.error.as_ref().map(|e| e.to_string()).unwrap_or_default();
+                        let has_error = !error.is_empty();
+                        let cancellable = !job.state.is_finished();
+                        let id = job.id;
+                        view! {
+                            div(class="bg-white shadow rounded-lg p-4 space-y-1") {
+                                div(class="flex items-center justify-between") {
+                                    div(class="flex items-center space-x-2") {
+                                        span(class="text-sm font-semibold text-gray-800") { (title) }
+                                        span(class=badge_class) { (badge) }
+                                    }
+                                    (if cancellable {
+                                        view! {
+                                            button(
+                                                class="px-3 py-1 text-sm rounded-md bg-red-500 text-white hover:bg-red-600",
+                                                on:click=move |_| handle_cancel(id)
+                                            ) { "Cancel" }
+                                        }
+                                    } else {
+                                        view! {}
+                                    })
+                                }
+                                p(class="text-xs text-gray-500") { (time) }
+                                p(class="text-xs text-blue-600") { (step.get_clone()) }
+                                (if has_error {
+                                    let error = error.clone();
+                                    view! {
+                                        p(class="text-xs text-red-600 break-all") { (error) }
+                                    }
+                                } else {
+                                    view! {}
+                                })
+                            }
+                        }
+                    }
+                )
+            }
+        })
+    }
+}